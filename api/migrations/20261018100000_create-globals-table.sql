@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS globals (
+  key TEXT PRIMARY KEY NOT NULL,
+  value TEXT NOT NULL,
+  type TEXT NOT NULL,
+  enabled BOOLEAN NOT NULL
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS runs (
+  id TEXT PRIMARY KEY NOT NULL,
+  collection_id TEXT NOT NULL,
+  started_at TEXT NOT NULL,
+  report JSON NOT NULL
+);
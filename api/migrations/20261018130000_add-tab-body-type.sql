@@ -0,0 +1 @@
+ALTER TABLE tabs ADD COLUMN body_type TEXT;
@@ -0,0 +1 @@
+ALTER TABLE tabs ADD COLUMN timeouts TEXT;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS oauth_tokens (
+  key TEXT PRIMARY KEY NOT NULL,
+  access_token TEXT NOT NULL,
+  token_type TEXT NOT NULL,
+  refresh_token TEXT,
+  expires_at TEXT,
+  scope TEXT
+);
@@ -1,21 +1,27 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 use cargo_packager_resource_resolver::{resources_dir, PackageFormat};
 use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
 use serde_json::from_str;
 use sqlx::{
-  sqlite::{SqlitePoolOptions, SqliteRow},
+  sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow},
   Row, SqlitePool,
 };
 use uuid::Uuid;
 
 use crate::domain::{
-  collection::{Collection, CollectionAuth, CollectionInfo, CollectionItemOrFolder},
-  environment::EnvironmentFile,
+  auth::OAuth2Token,
+  collection::{
+    Collection, CollectionAuth, CollectionInfo, CollectionItemOrFolder, CollectionVariable,
+  },
+  cookie::Cookie,
+  environment::{EnvironmentFile, EnvironmentValue},
   header::{Header, Headers},
-  request::{DBRequest, HttpMethod},
+  request::{BodyType, DBRequest, HttpMethod},
   request_item::RequestHistoryItem,
-  response::DBResponse,
+  response::{DBResponse, TestResult},
+  run::RunReport,
   tab::Tab,
 };
 
@@ -24,20 +30,26 @@ use crate::domain::{
  * 1. Determine the path (Priority: CLI Arg -> Persistent Folder -> Local)
  * 2. Ensure the directory exists (e.g., ~/.local/share/postie/)
  * 3. Connect to the pool, (sqlx will create file if it doesn't exist)
+ * 4. Run any migrations the db hasn't had yet
  */
 pub async fn initialize_db() -> anyhow::Result<SqlitePool> {
-  println!("Acquiring sqlite connection...");
-
   let args: Vec<String> = std::env::args().collect();
   let db_path = if args.len() == 2 {
     PathBuf::from(&args[1])
   } else {
     get_persistent_db_path()
   };
+  connect_db(db_path).await
+}
+
+// Same as initialize_db, but for callers that already know which file to use
+// (e.g. the cli, whose arguments aren't a db path)
+pub async fn connect_db(db_path: PathBuf) -> anyhow::Result<SqlitePool> {
+  debug!("Acquiring sqlite connection...");
 
   if let Some(parent) = db_path.parent() {
     if !parent.exists() {
-      println!("Creating database directory: {:?}", parent);
+      info!("Creating database directory: {:?}", parent);
       std::fs::create_dir_all(parent)?;
     }
   }
@@ -46,26 +58,29 @@ pub async fn initialize_db() -> anyhow::Result<SqlitePool> {
   if !db_path.exists() {
     if let Some(bundled_db) = get_bundled_db_path() {
       if bundled_db.exists() {
-        println!("Seeding empty database to ~/.local/postie from app bundle");
+        info!("Seeding empty database to ~/.local/postie from app bundle");
         std::fs::copy(&bundled_db, &db_path)?;
       }
     } else {
-      println!("could not find bundled db");
+      warn!("could not find bundled db");
     }
   }
 
   let db_url = format!("sqlite:{}", db_path.to_string_lossy());
+  let options = SqliteConnectOptions::from_str(&db_url)?.create_if_missing(true);
   let connection = SqlitePoolOptions::new()
     .max_connections(5)
-    .connect(&db_url)
+    .connect_with(options)
     .await?;
+  // brings databases made by older versions (or a new empty file) up to the current schema
+  sqlx::migrate!().run(&connection).await?;
 
-  println!("Sqlite connection established at: {:?}", db_path);
+  info!("Sqlite connection established at: {:?}", db_path);
   Ok(connection)
 }
 
 fn get_bundled_db_path() -> Option<PathBuf> {
-  println!("checking for empty bundled db");
+  debug!("checking for empty bundled db");
   match resources_dir(PackageFormat::AppImage) {
     Ok(p) => Some(p.join("postie.sqlite")),
     Err(_) => None,
@@ -75,7 +90,7 @@ fn get_bundled_db_path() -> Option<PathBuf> {
 // Linux:   /home/alice/.local/share/postie/postie.sqlite
 // Windows: C:\Users\Alice\AppData\Roaming\mblydenburgh\postie\data\postie.sqlite
 // macOS:   /Users/Alice/Library/Application Support/com.mblydenburgh.postie/postie.sqlite
-fn get_persistent_db_path() -> PathBuf {
+pub fn get_persistent_db_path() -> PathBuf {
   if let Some(proj_dirs) = directories::ProjectDirs::from("com", "mblydenburgh", "postie") {
     return proj_dirs.data_dir().join("postie.sqlite");
   }
@@ -91,13 +106,18 @@ impl PostieDb {
     PostieDb {
       pool: initialize_db()
         .await
-        .ok()
         .expect("could not establish database connection"),
     }
   }
 
+  pub async fn from_path(db_path: PathBuf) -> anyhow::Result<Self> {
+    Ok(PostieDb {
+      pool: connect_db(db_path).await?,
+    })
+  }
+
   pub async fn save_request_history(&mut self, request: &DBRequest) -> anyhow::Result<()> {
-    println!("got request: {:?}", request);
+    debug!("got request: {:?}", request);
     let mut transaction = self.pool.begin().await?;
     let header_json = serde_json::to_string(&request.headers)?;
     let _request = sqlx::query!(
@@ -116,7 +136,7 @@ impl PostieDb {
     .await?;
 
     transaction.commit().await?;
-    println!("transaction committed");
+    debug!("transaction committed");
 
     Ok(())
   }
@@ -127,22 +147,25 @@ impl PostieDb {
     response: &DBResponse,
     sent_at: &DateTime<Utc>,
     response_time: &u128,
+    test_results: &[TestResult],
   ) -> anyhow::Result<()> {
-    println!("Saving request response history item");
+    debug!("Saving request response history item");
     let mut transaction = self.pool.begin().await?;
     let id = Uuid::new_v4().to_string();
     let converted_sent = sent_at.to_string();
     let converted_response_time = response_time.to_string();
+    let test_results_json = serde_json::to_string(test_results)?;
     _ = sqlx::query!(
       r#"
-            INSERT INTO request_history (id, request_id, response_id, sent_at, response_time_ms)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO request_history (id, request_id, response_id, sent_at, response_time_ms, test_results)
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
       id,
       request.id,
       response.id,
       converted_sent,
-      converted_response_time
+      converted_response_time,
+      test_results_json
     )
     .execute(&mut *transaction)
     .await
@@ -152,7 +175,7 @@ impl PostieDb {
   }
 
   pub async fn get_request_response_items(&mut self) -> anyhow::Result<Vec<RequestHistoryItem>> {
-    println!("getting all request response items");
+    debug!("getting all request response items");
     let rows = sqlx::query("SELECT * FROM request_history")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -160,12 +183,17 @@ impl PostieDb {
         let response_id: String = row.get("response_id");
         let sent_at: String = row.get("sent_at");
         let response_time: String = row.get("response_time_ms");
+        let raw_test_results: Option<String> = row.get("test_results");
+        let test_results = raw_test_results
+          .and_then(|r| serde_json::from_str(&r).ok())
+          .unwrap_or_default();
         RequestHistoryItem {
           id,
           request_id,
           response_id,
           response_time: from_str::<usize>(&response_time).unwrap(),
           sent_at,
+          test_results,
         }
       })
       .fetch_all(&self.pool)
@@ -174,16 +202,21 @@ impl PostieDb {
     Ok(rows)
   }
 
+  // Saving an environment with an existing id replaces it, an empty id always adds a new one
   pub async fn save_environment(&mut self, environment: EnvironmentFile) -> anyhow::Result<()> {
     let mut transaction = self.pool.begin().await?;
     let value_json = match environment.values {
       None => serde_json::json!("[]"),
       Some(values) => serde_json::Value::String(serde_json::to_string(&values).unwrap()),
     };
-    let uuid = Uuid::new_v4().to_string();
+    let uuid = if environment.id.is_empty() {
+      Uuid::new_v4().to_string()
+    } else {
+      environment.id
+    };
     _ = sqlx::query!(
       r#"
-            INSERT INTO environment (id, name, `values`)
+            INSERT OR REPLACE INTO environment (id, name, `values`)
             VALUES ($1, $2, $3)
             "#,
       uuid,
@@ -197,21 +230,48 @@ impl PostieDb {
     Ok(())
   }
 
+  // Globals are saved as a whole, replacing whatever was there before
+  pub async fn save_globals(&mut self, globals: &[EnvironmentValue]) -> anyhow::Result<()> {
+    debug!("Saving {} globals to db", globals.len());
+    let mut transaction = self.pool.begin().await?;
+    sqlx::query!("DELETE FROM globals")
+      .execute(&mut *transaction)
+      .await?;
+    for global in globals.iter().filter(|g| !g.key.is_empty()) {
+      sqlx::query!(
+        r#"
+            INSERT OR REPLACE INTO globals (key, value, type, enabled)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        global.key,
+        global.value,
+        global.r#type,
+        global.enabled
+      )
+      .execute(&mut *transaction)
+      .await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+  }
+
   pub async fn save_collection(&mut self, collection: Collection) -> anyhow::Result<()> {
-    println!("Saving collection {:#?} to db", collection.info);
+    debug!("Saving collection {:#?} to db", collection.info);
     let mut transaction = self.pool.begin().await?;
     let items_json = serde_json::to_string(&collection.item)?;
     let auth_json = serde_json::to_string(&collection.auth)?;
+    let variable_json = serde_json::to_string(&collection.variable)?;
     _ = sqlx::query!(
       r#"
-            INSERT OR REPLACE INTO collections (id, name, description, item, auth)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT OR REPLACE INTO collections (id, name, description, item, auth, variable)
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
       collection.info.id,
       collection.info.name,
       collection.info.description,
       items_json,
-      auth_json
+      auth_json,
+      variable_json
     )
     .execute(&mut *transaction)
     .await
@@ -221,7 +281,7 @@ impl PostieDb {
   }
 
   pub async fn save_response(&mut self, response: &DBResponse) -> anyhow::Result<()> {
-    println!("Saving response to db");
+    debug!("Saving response to db");
     let mut transaction = self.pool.begin().await?;
     let header_json = serde_json::to_string(&response.headers)?;
     _ = sqlx::query!(
@@ -243,18 +303,26 @@ impl PostieDb {
   }
 
   pub async fn save_tab(&mut self, tab: &Tab) -> anyhow::Result<()> {
-    println!("Saving tab to db: {:#?}", tab);
+    debug!("Saving tab to db: {:#?}", tab);
     let method = tab.method.to_string();
     let req_headers = serde_json::to_string(&tab.req_headers).unwrap();
     let res_headers = serde_json::to_string(&tab.res_headers).unwrap();
+    let body_type = tab.body_type.to_string();
+    let timeouts = serde_json::to_string(&tab.timeouts)?;
+    let auth = serde_json::to_string(&tab.auth)?;
+    let scripts = serde_json::to_string(&tab.scripts)?;
+    let assertions = serde_json::to_string(&tab.assertions)?;
+    let extract = serde_json::to_string(&tab.extract)?;
     let mut transaction = self.pool.begin().await?;
     let id = tab.clone().id.to_string();
     _ = sqlx::query!(
             r#"
-            INSERT INTO tabs (id, method, url, req_body, req_headers, res_status, res_body, res_headers)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO tabs (id, method, url, req_body, req_headers, res_status, res_body, res_headers, collection_id, request_id, body_type, timeouts, auth, scripts, assertions, extract)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             ON CONFLICT (id) DO UPDATE SET 
-            method = $2, url = $3, req_body = $4, req_headers = $5, res_status = $6, res_body = $7, res_headers = $8
+            method = $2, url = $3, req_body = $4, req_headers = $5, res_status = $6, res_body = $7, res_headers = $8,
+            collection_id = COALESCE($9, collection_id), request_id = COALESCE($10, request_id), body_type = $11,
+            timeouts = $12, auth = $13, scripts = $14, assertions = $15, extract = $16
             "#,
             id,
             method,
@@ -263,7 +331,15 @@ impl PostieDb {
             req_headers,
             tab.res_status,
             tab.res_body,
-            res_headers
+            res_headers,
+            tab.collection_id,
+            tab.request_id,
+            body_type,
+            timeouts,
+            auth,
+            scripts,
+            assertions,
+            extract
         )
         .execute(&mut *transaction)
         .await
@@ -273,7 +349,7 @@ impl PostieDb {
   }
 
   pub async fn get_all_requests(&mut self) -> anyhow::Result<Vec<DBRequest>> {
-    println!("getting all saved requests");
+    debug!("getting all saved requests");
     let rows = sqlx::query("SELECT * FROM request")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -303,7 +379,7 @@ impl PostieDb {
   }
 
   pub async fn get_all_collections(&self) -> anyhow::Result<Vec<Collection>> {
-    println!("getting all saved collections");
+    debug!("getting all saved collections");
     let rows = sqlx::query("SELECT * from collections")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -316,6 +392,11 @@ impl PostieDb {
           Some(a) => serde_json::from_str(&a).unwrap(),
           None => None,
         };
+        let raw_variable: Option<String> = row.get("variable");
+        let variable: Option<Vec<CollectionVariable>> = match raw_variable {
+          Some(v) => serde_json::from_str(&v).unwrap(),
+          None => None,
+        };
         Collection {
           info: CollectionInfo {
             id,
@@ -324,6 +405,7 @@ impl PostieDb {
           },
           item,
           auth,
+          variable,
         }
       })
       .fetch_all(&self.pool)
@@ -333,7 +415,7 @@ impl PostieDb {
   }
 
   pub async fn get_all_responses(&mut self) -> anyhow::Result<Vec<DBResponse>> {
-    println!("getting all saved responses");
+    debug!("getting all saved responses");
     let rows = sqlx::query("SELECT * from response")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -362,7 +444,7 @@ impl PostieDb {
   }
 
   pub async fn get_all_environments(&self) -> anyhow::Result<Vec<EnvironmentFile>> {
-    println!("getting all envs");
+    debug!("getting all envs");
     let rows = sqlx::query("SELECT * FROM environment")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -380,7 +462,7 @@ impl PostieDb {
               }
             }
             Err(e) => {
-              println!("error: {:#?}", e);
+              error!("error: {:#?}", e);
               EnvironmentFile {
                 id,
                 name,
@@ -401,13 +483,185 @@ impl PostieDb {
       .unwrap();
 
     for row in rows.clone().into_iter() {
-      println!("row: {:?}", &row);
+      debug!("row: {:?}", &row);
     }
     Ok(rows)
   }
 
+  pub async fn get_all_globals(&self) -> anyhow::Result<Vec<EnvironmentValue>> {
+    debug!("getting all globals");
+    let rows = sqlx::query("SELECT * FROM globals ORDER BY rowid")
+      .map(|row: SqliteRow| EnvironmentValue {
+        key: row.get("key"),
+        value: row.get("value"),
+        r#type: row.get("type"),
+        enabled: row.get("enabled"),
+      })
+      .fetch_all(&self.pool)
+      .await?;
+    Ok(rows)
+  }
+
+  // Settings are json values by name, e.g. the default timeouts
+  pub async fn get_setting(&self, key: &str) -> anyhow::Result<Option<String>> {
+    debug!("getting setting {}", key);
+    let value = sqlx::query("SELECT value FROM settings WHERE key = $1")
+      .bind(key)
+      .map(|row: SqliteRow| row.get("value"))
+      .fetch_optional(&self.pool)
+      .await?;
+    Ok(value)
+  }
+
+  pub async fn save_setting(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+    debug!("Saving setting {} to db", key);
+    sqlx::query!(
+      "INSERT OR REPLACE INTO settings (key, value) VALUES ($1, $2)",
+      key,
+      value
+    )
+    .execute(&self.pool)
+    .await?;
+    Ok(())
+  }
+
+  // Cookies are kept per environment id, an empty scope is the workspace's jar used without one
+  pub async fn get_cookies(&self, scope: &str) -> anyhow::Result<Vec<Cookie>> {
+    debug!("getting cookies for scope {:?}", scope);
+    let rows = sqlx::query("SELECT * FROM cookies WHERE scope = $1 ORDER BY domain, path, rowid")
+      .bind(scope)
+      .map(|row: SqliteRow| Cookie {
+        name: row.get("name"),
+        value: row.get("value"),
+        domain: row.get("domain"),
+        host_only: row.get("host_only"),
+        path: row.get("path"),
+        expires: row
+          .get::<Option<String>, _>("expires")
+          .and_then(|expires| DateTime::parse_from_rfc3339(&expires).ok())
+          .map(|expires| expires.with_timezone(&Utc)),
+        secure: row.get("secure"),
+        http_only: row.get("http_only"),
+        same_site: row.get("same_site"),
+      })
+      .fetch_all(&self.pool)
+      .await?;
+    Ok(rows)
+  }
+
+  // Replaces every cookie in the scope, like globals
+  pub async fn save_cookies(&mut self, scope: &str, cookies: &[Cookie]) -> anyhow::Result<()> {
+    debug!(
+      "Saving {} cookies for scope {:?} to db",
+      cookies.len(),
+      scope
+    );
+    let mut transaction = self.pool.begin().await?;
+    sqlx::query!("DELETE FROM cookies WHERE scope = $1", scope)
+      .execute(&mut *transaction)
+      .await?;
+    for cookie in cookies.iter().filter(|c| !c.name.is_empty()) {
+      let expires = cookie.expires.map(|expires| expires.to_rfc3339());
+      sqlx::query!(
+        r#"
+            INSERT OR REPLACE INTO cookies
+              (scope, domain, path, name, value, host_only, expires, secure, http_only, same_site)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        scope,
+        cookie.domain,
+        cookie.path,
+        cookie.name,
+        cookie.value,
+        cookie.host_only,
+        expires,
+        cookie.secure,
+        cookie.http_only,
+        cookie.same_site
+      )
+      .execute(&mut *transaction)
+      .await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+  }
+
+  // OAuth 2.0 tokens by the token url, client and scope they were given for
+  pub async fn get_oauth_token(&self, key: &str) -> anyhow::Result<Option<OAuth2Token>> {
+    debug!("getting oauth token");
+    let token = sqlx::query("SELECT * FROM oauth_tokens WHERE key = $1")
+      .bind(key)
+      .map(|row: SqliteRow| OAuth2Token {
+        access_token: row.get("access_token"),
+        token_type: row.get("token_type"),
+        refresh_token: row.get("refresh_token"),
+        expires_at: row
+          .get::<Option<String>, _>("expires_at")
+          .and_then(|expires| DateTime::parse_from_rfc3339(&expires).ok())
+          .map(|expires| expires.with_timezone(&Utc)),
+        scope: row.get("scope"),
+      })
+      .fetch_optional(&self.pool)
+      .await?;
+    Ok(token)
+  }
+
+  pub async fn save_oauth_token(&mut self, key: &str, token: &OAuth2Token) -> anyhow::Result<()> {
+    debug!("Saving oauth token to db");
+    let expires_at = token.expires_at.map(|expires| expires.to_rfc3339());
+    sqlx::query!(
+      r#"
+            INSERT OR REPLACE INTO oauth_tokens
+              (key, access_token, token_type, refresh_token, expires_at, scope)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+      key,
+      token.access_token,
+      token.token_type,
+      token.refresh_token,
+      expires_at,
+      token.scope
+    )
+    .execute(&self.pool)
+    .await?;
+    Ok(())
+  }
+
+  pub async fn save_run_report(&mut self, report: &RunReport) -> anyhow::Result<()> {
+    debug!("Saving run report {} to db", report.id);
+    let mut transaction = self.pool.begin().await?;
+    let report_json = serde_json::to_string(report)?;
+    sqlx::query!(
+      r#"
+            INSERT OR REPLACE INTO runs (id, collection_id, started_at, report)
+            VALUES ($1, $2, $3, $4)
+            "#,
+      report.id,
+      report.collection_id,
+      report.started_at,
+      report_json
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    Ok(())
+  }
+
+  // Newest first
+  pub async fn get_all_run_reports(&self) -> anyhow::Result<Vec<RunReport>> {
+    debug!("getting all run reports");
+    let rows = sqlx::query("SELECT report FROM runs ORDER BY started_at DESC")
+      .map(|row: SqliteRow| {
+        let report: String = row.get("report");
+        serde_json::from_str(&report)
+      })
+      .fetch_all(&self.pool)
+      .await?;
+    Ok(rows.into_iter().collect::<Result<_, _>>()?)
+  }
+
   pub async fn get_all_tabs(&mut self) -> anyhow::Result<Vec<Tab>> {
-    println!("getting all tabs");
+    debug!("getting all tabs");
     let rows = sqlx::query("SELECT * FROM tabs")
       .map(|row: SqliteRow| {
         let id: String = row.get("id");
@@ -417,7 +671,15 @@ impl PostieDb {
         let method: String = row.get("method");
         let res_status: Option<String> = row.get("res_status");
         let raw_req_headers: String = row.get("req_headers");
-        println!("raw_req_headers: {:?}", raw_req_headers);
+        let collection_id: Option<String> = row.get("collection_id");
+        let request_id: Option<String> = row.get("request_id");
+        let body_type: Option<String> = row.get("body_type");
+        let timeouts: Option<String> = row.get("timeouts");
+        let auth: Option<String> = row.get("auth");
+        let scripts: Option<String> = row.get("scripts");
+        let assertions: Option<String> = row.get("assertions");
+        let extract: Option<String> = row.get("extract");
+        debug!("raw_req_headers: {:?}", raw_req_headers);
         let mut req_body: Option<String> = None;
         let mut res_body: String = "".into();
         let headers: Headers =
@@ -443,6 +705,26 @@ impl PostieDb {
           req_headers: headers,
           res_body,
           res_headers: Headers(vec![]),
+          body_type: body_type
+            .and_then(|t| BodyType::from_str(&t).ok())
+            .unwrap_or_default(),
+          collection_id,
+          request_id,
+          scripts: scripts
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+          assertions: assertions
+            .and_then(|a| serde_json::from_str(&a).ok())
+            .unwrap_or_default(),
+          extract: extract
+            .and_then(|e| serde_json::from_str(&e).ok())
+            .unwrap_or_default(),
+          timeouts: timeouts
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default(),
+          auth: auth
+            .and_then(|a| serde_json::from_str(&a).ok())
+            .unwrap_or_default(),
         }
       })
       .fetch_all(&self.pool)
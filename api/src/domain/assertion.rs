@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+// Checks run against every response of a request, without needing a test script
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+  StatusEquals {
+    status: u16,
+  },
+  StatusInRange {
+    min: u16,
+    max: u16,
+  },
+  HeaderPresent {
+    name: String,
+  },
+  HeaderMatches {
+    name: String,
+    pattern: String,
+  },
+  JsonPathEquals {
+    path: String,
+    value: serde_json::Value,
+  },
+  BodyContains {
+    text: String,
+  },
+  ResponseTimeUnder {
+    ms: u64,
+  },
+}
+
+impl Assertion {
+  // Blank assertions of every kind, in the order the gui offers them
+  pub fn all_kinds() -> Vec<Assertion> {
+    vec![
+      Assertion::StatusEquals { status: 200 },
+      Assertion::StatusInRange { min: 200, max: 299 },
+      Assertion::HeaderPresent {
+        name: String::new(),
+      },
+      Assertion::HeaderMatches {
+        name: String::new(),
+        pattern: String::new(),
+      },
+      Assertion::JsonPathEquals {
+        path: String::from("$"),
+        value: serde_json::Value::Null,
+      },
+      Assertion::BodyContains {
+        text: String::new(),
+      },
+      Assertion::ResponseTimeUnder { ms: 1000 },
+    ]
+  }
+
+  pub fn kind(&self) -> &'static str {
+    match self {
+      Assertion::StatusEquals { .. } => "Status equals",
+      Assertion::StatusInRange { .. } => "Status in range",
+      Assertion::HeaderPresent { .. } => "Header present",
+      Assertion::HeaderMatches { .. } => "Header matches",
+      Assertion::JsonPathEquals { .. } => "JSONPath equals",
+      Assertion::BodyContains { .. } => "Body contains",
+      Assertion::ResponseTimeUnder { .. } => "Response time under",
+    }
+  }
+}
+
+impl std::fmt::Display for Assertion {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Assertion::StatusEquals { status } => write!(f, "Status is {}", status),
+      Assertion::StatusInRange { min, max } => write!(f, "Status is between {} and {}", min, max),
+      Assertion::HeaderPresent { name } => write!(f, "Header {} is present", name),
+      Assertion::HeaderMatches { name, pattern } => {
+        write!(f, "Header {} matches {}", name, pattern)
+      }
+      Assertion::JsonPathEquals { path, value } => write!(f, "{} equals {}", path, value),
+      Assertion::BodyContains { text } => write!(f, "Body contains {:?}", text),
+      Assertion::ResponseTimeUnder { ms } => write!(f, "Response time is under {} ms", ms),
+    }
+  }
+}
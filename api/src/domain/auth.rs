@@ -0,0 +1,354 @@
+use base64::Engine as _;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{request::OAuth2Request, ui::AuthMode};
+
+// How a request authenticates, applied when it is sent
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum RequestAuth {
+  #[default]
+  NONE,
+  // takes the auth of the folders or collection the request is in, see Collection::inherited_auth_for.
+  // Requests that aren't in a collection are sent without auth
+  INHERIT,
+  BEARER(String),
+  APIKEY {
+    key: String,
+    value: String,
+    placement: ApiKeyPlacement,
+  },
+  BASIC {
+    username: String,
+    password: String,
+  },
+  // answers the server's challenge, so the request is sent twice
+  DIGEST {
+    username: String,
+    password: String,
+  },
+  // signs the request as it is sent, after every other header was added
+  AWSSIGV4(AwsSigV4Auth),
+  // a token signed from the claims each time the request is sent
+  JWT(JwtAuth),
+  // sends the saved token for the config, refreshing or fetching it first when needed
+  OAUTH2(OAuth2Request),
+}
+impl std::fmt::Display for RequestAuth {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RequestAuth::NONE => write!(f, "No Auth"),
+      RequestAuth::INHERIT => write!(f, "Inherit"),
+      RequestAuth::BEARER(_) => write!(f, "Bearer Token"),
+      RequestAuth::APIKEY { .. } => write!(f, "API Key"),
+      RequestAuth::BASIC { .. } => write!(f, "Basic Auth"),
+      RequestAuth::DIGEST { .. } => write!(f, "Digest Auth"),
+      RequestAuth::AWSSIGV4(_) => write!(f, "AWS Signature"),
+      RequestAuth::JWT(_) => write!(f, "JWT Bearer"),
+      RequestAuth::OAUTH2(_) => write!(f, "OAuth 2.0"),
+    }
+  }
+}
+
+impl RequestAuth {
+  // The header sent for auth that is the same whatever the request, None for the rest
+  pub fn header(&self) -> Option<(String, String)> {
+    match self {
+      RequestAuth::BEARER(token) => Some(("Authorization".into(), format!("Bearer {}", token))),
+      RequestAuth::APIKEY {
+        key,
+        value,
+        placement: ApiKeyPlacement::HEADER,
+      } => Some((key.clone(), value.clone())),
+      RequestAuth::BASIC { username, password } => {
+        let credentials =
+          base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+        Some(("Authorization".into(), format!("Basic {}", credentials)))
+      }
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ApiKeyPlacement {
+  #[default]
+  HEADER,
+  QUERY,
+}
+impl std::fmt::Display for ApiKeyPlacement {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ApiKeyPlacement::HEADER => write!(f, "Header"),
+      ApiKeyPlacement::QUERY => write!(f, "Query Params"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AwsSigV4Auth {
+  pub access_key: String,
+  pub secret_key: String,
+  // for temporary credentials
+  pub session_token: String,
+  pub region: String,
+  pub service: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JwtAuth {
+  pub algorithm: String,
+  // the shared secret for HS algorithms, a PEM private key for the others
+  pub secret: String,
+  pub secret_base64: bool,
+  // the claims, a json object
+  pub payload: String,
+  pub header_prefix: String,
+}
+impl JwtAuth {
+  pub const ALGORITHMS: [&'static str; 12] = [
+    "HS256", "HS384", "HS512", "RS256", "RS384", "RS512", "PS256", "PS384", "PS512", "ES256",
+    "ES384", "EdDSA",
+  ];
+
+  // HS algorithms sign with a shared secret, the others with a private key
+  pub fn uses_secret(&self) -> bool {
+    self.algorithm.starts_with("HS")
+  }
+}
+impl Default for JwtAuth {
+  fn default() -> Self {
+    JwtAuth {
+      algorithm: "HS256".into(),
+      secret: "".into(),
+      secret_base64: false,
+      payload: "{}".into(),
+      header_prefix: "Bearer".into(),
+    }
+  }
+}
+
+// What the auth tab edits, only the settings of the selected mode are sent. Saved with the tab
+// so the settings of the other modes aren't lost when switching between them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthSettings {
+  pub mode: AuthMode,
+  pub api_key_name: String,
+  pub api_key: String,
+  pub api_key_placement: ApiKeyPlacement,
+  pub bearer_token: String,
+  // for basic and digest auth
+  pub username: String,
+  pub password: String,
+  pub aws: AwsSigV4Auth,
+  pub jwt: JwtAuth,
+  pub oauth: OAuth2Request,
+}
+impl AuthSettings {
+  pub fn to_request_auth(&self) -> RequestAuth {
+    match self.mode {
+      AuthMode::INHERIT => RequestAuth::INHERIT,
+      AuthMode::NONE => RequestAuth::NONE,
+      AuthMode::APIKEY => RequestAuth::APIKEY {
+        key: self.api_key_name.clone(),
+        value: self.api_key.clone(),
+        placement: self.api_key_placement,
+      },
+      AuthMode::BEARER => RequestAuth::BEARER(self.bearer_token.clone()),
+      AuthMode::BASIC => RequestAuth::BASIC {
+        username: self.username.clone(),
+        password: self.password.clone(),
+      },
+      AuthMode::DIGEST => RequestAuth::DIGEST {
+        username: self.username.clone(),
+        password: self.password.clone(),
+      },
+      AuthMode::AWSSIGV4 => RequestAuth::AWSSIGV4(self.aws.clone()),
+      AuthMode::JWT => RequestAuth::JWT(self.jwt.clone()),
+      AuthMode::OAUTH2 => RequestAuth::OAUTH2(self.oauth.clone()),
+    }
+  }
+
+  // The settings that send the auth, e.g. of a saved collection request
+  pub fn from_request_auth(auth: &RequestAuth) -> Self {
+    let defaults = AuthSettings::default();
+    match auth.clone() {
+      RequestAuth::INHERIT => defaults,
+      RequestAuth::NONE => AuthSettings {
+        mode: AuthMode::NONE,
+        ..defaults
+      },
+      RequestAuth::BEARER(bearer_token) => AuthSettings {
+        mode: AuthMode::BEARER,
+        bearer_token,
+        ..defaults
+      },
+      RequestAuth::APIKEY {
+        key,
+        value,
+        placement,
+      } => AuthSettings {
+        mode: AuthMode::APIKEY,
+        api_key_name: key,
+        api_key: value,
+        api_key_placement: placement,
+        ..defaults
+      },
+      RequestAuth::BASIC { username, password } => AuthSettings {
+        mode: AuthMode::BASIC,
+        username,
+        password,
+        ..defaults
+      },
+      RequestAuth::DIGEST { username, password } => AuthSettings {
+        mode: AuthMode::DIGEST,
+        username,
+        password,
+        ..defaults
+      },
+      RequestAuth::AWSSIGV4(aws) => AuthSettings {
+        mode: AuthMode::AWSSIGV4,
+        aws,
+        ..defaults
+      },
+      RequestAuth::JWT(jwt) => AuthSettings {
+        mode: AuthMode::JWT,
+        jwt,
+        ..defaults
+      },
+      RequestAuth::OAUTH2(oauth) => AuthSettings {
+        mode: AuthMode::OAUTH2,
+        oauth,
+        ..defaults
+      },
+    }
+  }
+}
+
+// Where the auth of a request in a collection was set
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthSource {
+  REQUEST,
+  // the folder's name
+  FOLDER(String),
+  // the collection's name
+  COLLECTION(String),
+}
+impl std::fmt::Display for AuthSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      AuthSource::REQUEST => write!(f, "the request"),
+      AuthSource::FOLDER(name) => write!(f, "folder {}", name),
+      AuthSource::COLLECTION(name) => write!(f, "collection {}", name),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OAuth2Grant {
+  #[default]
+  CLIENTCREDENTIALS,
+  // the user signs in with a browser, which is redirected back to a local listener
+  AUTHORIZATIONCODE,
+  PASSWORD,
+  // the user enters a code on another device
+  DEVICECODE,
+}
+impl OAuth2Grant {
+  pub fn all() -> Vec<OAuth2Grant> {
+    vec![
+      OAuth2Grant::CLIENTCREDENTIALS,
+      OAuth2Grant::AUTHORIZATIONCODE,
+      OAuth2Grant::PASSWORD,
+      OAuth2Grant::DEVICECODE,
+    ]
+  }
+
+  // The grant_type sent to the token endpoint
+  pub fn grant_type(&self) -> &'static str {
+    match self {
+      OAuth2Grant::CLIENTCREDENTIALS => "client_credentials",
+      OAuth2Grant::AUTHORIZATIONCODE => "authorization_code",
+      OAuth2Grant::PASSWORD => "password",
+      OAuth2Grant::DEVICECODE => "urn:ietf:params:oauth:grant-type:device_code",
+    }
+  }
+
+  // Whether getting a token needs the user, those tokens are never fetched while sending
+  pub fn is_interactive(&self) -> bool {
+    matches!(
+      self,
+      OAuth2Grant::AUTHORIZATIONCODE | OAuth2Grant::DEVICECODE
+    )
+  }
+}
+impl std::fmt::Display for OAuth2Grant {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OAuth2Grant::CLIENTCREDENTIALS => write!(f, "Client Credentials"),
+      OAuth2Grant::AUTHORIZATIONCODE => write!(f, "Authorization Code (PKCE)"),
+      OAuth2Grant::PASSWORD => write!(f, "Password"),
+      OAuth2Grant::DEVICECODE => write!(f, "Device Code"),
+    }
+  }
+}
+
+// Where the client id and secret go in token requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OAuth2ClientAuth {
+  #[default]
+  HEADER,
+  BODY,
+}
+impl std::fmt::Display for OAuth2ClientAuth {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OAuth2ClientAuth::HEADER => write!(f, "Basic auth header"),
+      OAuth2ClientAuth::BODY => write!(f, "In the body"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OAuth2Token {
+  pub access_token: String,
+  pub token_type: String,
+  pub refresh_token: Option<String>,
+  // None when the server didn't say
+  pub expires_at: Option<DateTime<Utc>>,
+  pub scope: Option<String>,
+}
+impl OAuth2Token {
+  // Tokens this close to expiring are refreshed before they are sent
+  pub const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+  pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires| expires - Duration::seconds(Self::REFRESH_MARGIN_SECONDS) <= now)
+  }
+
+  // The Authorization header value
+  pub fn header_value(&self) -> String {
+    // servers answer `bearer` as often as `Bearer`, resource servers expect the latter
+    if self.token_type.is_empty() || self.token_type.eq_ignore_ascii_case("bearer") {
+      format!("Bearer {}", self.access_token)
+    } else {
+      format!("{} {}", self.token_type, self.access_token)
+    }
+  }
+}
+
+// What the user needs to do while an interactive grant waits for them
+#[derive(Debug, Clone, PartialEq)]
+pub enum OAuth2Prompt {
+  OpenBrowser(String),
+  EnterCode {
+    user_code: String,
+    verification_uri: String,
+    // the verification uri with the code filled in, when the server has one
+    verification_uri_complete: Option<String>,
+  },
+}
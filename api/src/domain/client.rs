@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+// How requests are sent, from Menu > Settings. Empty values leave reqwest's defaults
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientSettings {
+  // proxy urls, e.g. http://localhost:8888
+  pub http_proxy: String,
+  pub https_proxy: String,
+  // comma separated hosts, domains and ip ranges that skip the proxies
+  pub no_proxy: String,
+  // pem files with certificate authorities to trust besides the system ones
+  pub ca_certificates: Vec<String>,
+  // hosts whose certificates aren't verified, *.example.com matches its subdomains
+  pub insecure_hosts: Vec<String>,
+  pub client_certificates: Vec<ClientCertificate>,
+  pub redirects: RedirectPolicy,
+  pub http_version: HttpVersion,
+}
+
+// A certificate presented to hosts that ask for one (mTLS)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientCertificate {
+  // matched like insecure_hosts
+  pub host: String,
+  // a pem certificate, or a .p12/.pfx file holding both the certificate and its key
+  pub certificate: String,
+  // pkcs8 pem private key, not used with a .p12/.pfx file
+  pub key: String,
+  // of a .p12/.pfx file
+  pub password: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum RedirectPolicy {
+  // up to 10 redirects
+  #[default]
+  FOLLOW,
+  LIMIT(usize),
+  // redirects are returned as the response
+  OFF,
+}
+impl std::fmt::Display for RedirectPolicy {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RedirectPolicy::FOLLOW => write!(f, "Follow"),
+      RedirectPolicy::LIMIT(limit) => write!(f, "Follow up to {}", limit),
+      RedirectPolicy::OFF => write!(f, "Off"),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum HttpVersion {
+  // whatever the server agrees to
+  #[default]
+  AUTO,
+  HTTP1,
+  // without asking the server first
+  HTTP2,
+}
+impl std::fmt::Display for HttpVersion {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      HttpVersion::AUTO => write!(f, "Auto"),
+      HttpVersion::HTTP1 => write!(f, "HTTP/1.1"),
+      HttpVersion::HTTP2 => write!(f, "HTTP/2"),
+    }
+  }
+}
+
+// Whether a host from the settings, which can start with *. for any subdomain, matches `host`
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+  let pattern = pattern.trim().to_lowercase();
+  let host = host.to_lowercase();
+  match pattern.strip_prefix("*.") {
+    Some(domain) => host.ends_with(&format!(".{}", domain)),
+    None => !pattern.is_empty() && pattern == host,
+  }
+}
@@ -1,10 +1,23 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 
+use crate::domain::{
+  assertion::Assertion,
+  auth::{
+    ApiKeyPlacement, AuthSource, AwsSigV4Auth, JwtAuth, OAuth2ClientAuth, OAuth2Grant, RequestAuth,
+  },
+  extract::ExtractRule,
+  request::{self, HttpRequest, OAuth2Request, OAuthRequestBody, RequestTimeouts},
+};
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Collection {
   pub info: CollectionInfo,
   pub item: Vec<CollectionItemOrFolder>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub auth: Option<CollectionAuth>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub variable: Option<Vec<CollectionVariable>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -12,9 +25,152 @@ pub struct CollectionInfo {
   #[serde(rename = "_postman_id")]
   pub id: String,
   pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub description: Option<String>,
 }
 
+impl Collection {
+  // Every request in the collection, depth first in the same order as the tree
+  pub fn requests(&self) -> Vec<&CollectionItem> {
+    flatten_requests(&self.item)
+  }
+
+  pub fn find_folder(&self, name_or_id: &str) -> Option<&CollectionFolder> {
+    find_folder(&self.item, name_or_id)
+  }
+
+  // Enabled variables in scope for a request, lowest precedence first: the collection's own,
+  // then each enclosing folder from the outermost in, then the request's
+  pub fn variables_for(&self, item_id: &str) -> Vec<(String, String)> {
+    let mut scopes = vec![&self.variable];
+    if !scoped_variables(&self.item, item_id, &mut scopes) {
+      return vec![];
+    }
+    scopes
+      .into_iter()
+      .flatten()
+      .flatten()
+      .filter(|v| !v.disabled && !v.key.is_empty())
+      .map(|v| (v.key.clone(), v.value.clone()))
+      .collect()
+  }
+
+  // The auth a request is sent with and where it was set: the request's own, else the closest
+  // enclosing folder's, else the collection's. Levels without auth or set to inherit are skipped,
+  // None when no level has any
+  pub fn auth_for(&self, item_id: &str) -> Option<(RequestAuth, AuthSource)> {
+    self.auth_levels(item_id, true)
+  }
+
+  // Same as auth_for, but starting from the request's folder. For tabs set to inherit, which
+  // leave out the auth saved on the request
+  pub fn inherited_auth_for(&self, item_id: &str) -> Option<(RequestAuth, AuthSource)> {
+    self.auth_levels(item_id, false)
+  }
+
+  fn auth_levels(&self, item_id: &str, own: bool) -> Option<(RequestAuth, AuthSource)> {
+    let mut folders = vec![];
+    let item = path_to(&self.item, item_id, &mut folders)?;
+    let mut levels = vec![];
+    if own {
+      levels.push((&item.request.auth, AuthSource::REQUEST));
+    }
+    levels.extend(
+      folders
+        .iter()
+        .rev()
+        .map(|f| (&f.auth, AuthSource::FOLDER(f.name.clone()))),
+    );
+    levels.push((&self.auth, AuthSource::COLLECTION(self.info.name.clone())));
+    levels.into_iter().find_map(|(auth, source)| {
+      let auth = auth.as_ref().filter(|a| a.r#type != "inherit")?;
+      Some((auth.to_request_auth(), source))
+    })
+  }
+}
+
+// Finds the item, pushing every folder on the way down to it
+fn path_to<'a>(
+  items: &'a [CollectionItemOrFolder],
+  item_id: &str,
+  folders: &mut Vec<&'a CollectionFolder>,
+) -> Option<&'a CollectionItem> {
+  for item in items {
+    match item {
+      CollectionItemOrFolder::Item(i) if i.id == item_id => return Some(i),
+      CollectionItemOrFolder::Item(_) => {}
+      CollectionItemOrFolder::Folder(f) => {
+        folders.push(f);
+        if let Some(found) = path_to(&f.item, item_id, folders) {
+          return Some(found);
+        }
+        folders.pop();
+      }
+    }
+  }
+  None
+}
+
+// Pushes the variables of every folder on the way down to the item, and the item's own
+fn scoped_variables<'a>(
+  items: &'a [CollectionItemOrFolder],
+  item_id: &str,
+  scopes: &mut Vec<&'a Option<Vec<CollectionVariable>>>,
+) -> bool {
+  for item in items {
+    match item {
+      CollectionItemOrFolder::Item(i) if i.id == item_id => {
+        scopes.push(&i.variable);
+        return true;
+      }
+      CollectionItemOrFolder::Item(_) => {}
+      CollectionItemOrFolder::Folder(f) => {
+        scopes.push(&f.variable);
+        if scoped_variables(&f.item, item_id, scopes) {
+          return true;
+        }
+        scopes.pop();
+      }
+    }
+  }
+  false
+}
+
+impl CollectionFolder {
+  pub fn requests(&self) -> Vec<&CollectionItem> {
+    flatten_requests(&self.item)
+  }
+}
+
+fn flatten_requests(items: &[CollectionItemOrFolder]) -> Vec<&CollectionItem> {
+  let mut requests = vec![];
+  for item in items {
+    match item {
+      CollectionItemOrFolder::Item(i) => requests.push(i),
+      CollectionItemOrFolder::Folder(f) => requests.extend(flatten_requests(&f.item)),
+    }
+  }
+  requests
+}
+
+fn find_folder<'a>(
+  items: &'a [CollectionItemOrFolder],
+  name_or_id: &str,
+) -> Option<&'a CollectionFolder> {
+  for item in items {
+    if let CollectionItemOrFolder::Folder(f) = item {
+      if f.id == name_or_id || f.name == name_or_id {
+        return Some(f);
+      }
+      if let Some(found) = find_folder(&f.item, name_or_id) {
+        return Some(found);
+      }
+    }
+  }
+  None
+}
+
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum CollectionItemOrFolder {
@@ -28,6 +184,10 @@ pub struct CollectionFolder {
   pub id: String,
   pub name: String,
   pub item: Vec<CollectionItemOrFolder>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub auth: Option<CollectionAuth>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub variable: Option<Vec<CollectionVariable>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -36,33 +196,461 @@ pub struct CollectionItem {
   pub id: String,
   pub name: String,
   pub request: CollectionRequest,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub variable: Option<Vec<CollectionVariable>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub event: Option<Vec<CollectionEvent>>,
+  // not part of the postman format, postman ignores it on import
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub assertions: Option<Vec<Assertion>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub extract: Option<Vec<ExtractRule>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub timeouts: Option<RequestTimeouts>,
+}
+
+impl CollectionItem {
+  // Builds a collection entry from a request sent from the gui, keeping its headers and body
+  pub fn from_http_request(req: &HttpRequest) -> Self {
+    let headers = req
+      .headers
+      .clone()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(key, value)| CollectionRequestHeader {
+        key,
+        value,
+        r#type: String::from("text"),
+      })
+      .collect();
+    let body = req.body.as_ref().map(RequestBody::from_request_body);
+    CollectionItem {
+      id: uuid::Uuid::new_v4().to_string(),
+      name: req.name.clone().unwrap_or(req.url.clone()),
+      request: CollectionRequest {
+        method: req.method.to_string(),
+        url: CollectionUrl {
+          raw: req.url.clone(),
+          host: None,
+          path: None,
+        },
+        auth: CollectionAuth::from_request_auth(&req.auth),
+        header: Some(headers),
+        body,
+      },
+      variable: None,
+      event: events_from_scripts(&req.scripts),
+      assertions: if req.assertions.is_empty() {
+        None
+      } else {
+        Some(req.assertions.clone())
+      },
+      extract: if req.extract.is_empty() {
+        None
+      } else {
+        Some(req.extract.clone())
+      },
+      timeouts: (!req.timeouts.is_empty()).then_some(req.timeouts),
+    }
+  }
+
+  // Only rhai scripts are run, javascript ones imported from postman are skipped
+  pub fn scripts(&self) -> request::RequestScripts {
+    let mut scripts = request::RequestScripts::default();
+    for event in self.event.iter().flatten() {
+      if event.script.r#type.as_deref() != Some(RHAI_SCRIPT_TYPE) {
+        warn!(
+          "skipping {} script of {}, only {} scripts are supported",
+          event.listen, self.name, RHAI_SCRIPT_TYPE
+        );
+        continue;
+      }
+      let source = event.script.exec.join("\n");
+      match event.listen.as_str() {
+        "prerequest" => scripts.pre_request = source,
+        "test" => scripts.test = source,
+        _ => {}
+      }
+    }
+    scripts
+  }
+}
+
+const RHAI_SCRIPT_TYPE: &str = "text/rhai";
+
+fn events_from_scripts(scripts: &request::RequestScripts) -> Option<Vec<CollectionEvent>> {
+  let events: Vec<CollectionEvent> = [
+    ("prerequest", &scripts.pre_request),
+    ("test", &scripts.test),
+  ]
+  .into_iter()
+  .filter(|(_, source)| !source.trim().is_empty())
+  .map(|(listen, source)| CollectionEvent {
+    listen: listen.to_string(),
+    script: CollectionScript {
+      r#type: Some(RHAI_SCRIPT_TYPE.to_string()),
+      exec: source.lines().map(String::from).collect(),
+    },
+  })
+  .collect();
+  if events.is_empty() {
+    None
+  } else {
+    Some(events)
+  }
 }
 
 fn generate_uuid() -> String {
   uuid::Uuid::new_v4().to_string()
 }
 
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct CollectionEvent {
+  pub listen: String,
+  pub script: CollectionScript,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct CollectionScript {
+  #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+  pub r#type: Option<String>,
+  #[serde(default, deserialize_with = "script_exec_lines")]
+  pub exec: Vec<String>,
+}
+
+// exec is usually a list of lines, but postman also accepts a single string
+fn script_exec_lines<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Exec {
+    Lines(Vec<String>),
+    Source(String),
+  }
+  Ok(match Exec::deserialize(deserializer)? {
+    Exec::Lines(lines) => lines,
+    Exec::Source(source) => source.lines().map(String::from).collect(),
+  })
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct CollectionVariable {
+  // postman exports can contain placeholder entries without a key
+  #[serde(default)]
+  pub key: String,
+  #[serde(default, deserialize_with = "variable_value_to_string")]
+  pub value: String,
+  #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+  pub r#type: Option<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub disabled: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+  !*b
+}
+
+// Postman allows numbers and booleans as variable values, they are always substituted as text
+fn variable_value_to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+  Ok(match value {
+    serde_json::Value::String(s) => s,
+    serde_json::Value::Null => String::new(),
+    other => other.to_string(),
+  })
+}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CollectionRequest {
   pub method: String,
   pub url: CollectionUrl,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub auth: Option<CollectionAuth>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub header: Option<Vec<CollectionRequestHeader>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub body: Option<RequestBody>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CollectionUrl {
   pub raw: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub host: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub path: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct RequestBody {
   pub mode: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub raw: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub options: Option<BodyOptions>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub urlencoded: Option<Vec<FormParam>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub formdata: Option<Vec<FormParam>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub file: Option<BodyFile>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub graphql: Option<GraphQLParams>,
+}
+
+// Postman keeps graphql variables as a string, some exports have them as an object
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct GraphQLParams {
+  #[serde(default)]
+  pub query: String,
+  #[serde(default, deserialize_with = "variable_value_to_string")]
+  pub variables: String,
+  #[serde(
+    default,
+    rename = "operationName",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub operation_name: Option<String>,
+}
+
+impl RequestBody {
+  fn raw(raw: String, language: &str) -> Self {
+    RequestBody {
+      mode: String::from("raw"),
+      raw: Some(raw),
+      options: Some(BodyOptions {
+        raw: BodyOptionsRaw {
+          language: String::from(language),
+        },
+      }),
+      urlencoded: None,
+      formdata: None,
+      file: None,
+      graphql: None,
+    }
+  }
+
+  pub fn from_request_body(body: &request::RequestBody) -> Self {
+    match body {
+      request::RequestBody::JSON(j) => {
+        RequestBody::raw(serde_json::to_string_pretty(j).unwrap_or_default(), "json")
+      }
+      request::RequestBody::FORM(fields) => RequestBody {
+        mode: String::from("urlencoded"),
+        raw: None,
+        options: None,
+        urlencoded: Some(
+          fields
+            .iter()
+            .map(|field| FormParam {
+              key: field.key.clone(),
+              value: field.value.clone(),
+              r#type: Some(String::from("text")),
+              disabled: !field.enabled,
+              src: None,
+              content_type: None,
+            })
+            .collect(),
+        ),
+        formdata: None,
+        file: None,
+        graphql: None,
+      },
+      request::RequestBody::TEXT(t) => RequestBody::raw(t.clone(), "text"),
+      request::RequestBody::XML(t) => RequestBody::raw(t.clone(), "xml"),
+      request::RequestBody::HTML(t) => RequestBody::raw(t.clone(), "html"),
+      request::RequestBody::JAVASCRIPT(t) => RequestBody::raw(t.clone(), "javascript"),
+      request::RequestBody::BINARY(path) => RequestBody {
+        mode: String::from("file"),
+        raw: None,
+        options: None,
+        urlencoded: None,
+        formdata: None,
+        file: Some(BodyFile {
+          src: Some(path.clone()),
+        }),
+        graphql: None,
+      },
+      request::RequestBody::MULTIPART(parts) => RequestBody {
+        mode: String::from("formdata"),
+        raw: None,
+        options: None,
+        urlencoded: None,
+        formdata: Some(parts.iter().map(FormParam::from_multipart_part).collect()),
+        file: None,
+        graphql: None,
+      },
+      request::RequestBody::GRAPHQL(graphql) => RequestBody {
+        mode: String::from("graphql"),
+        raw: None,
+        options: None,
+        urlencoded: None,
+        formdata: None,
+        file: None,
+        graphql: Some(GraphQLParams {
+          query: graphql.query.clone(),
+          variables: graphql.variables.clone(),
+          operation_name: graphql.operation_name.clone(),
+        }),
+      },
+    }
+  }
+
+  // Maps a postman body onto one postie can send, None for modes that aren't supported
+  pub fn to_request_body(&self) -> Option<request::RequestBody> {
+    match self.mode.as_str() {
+      "raw" => {
+        let raw = self.raw.clone().unwrap_or_default();
+        let language = self.options.as_ref().map(|o| o.raw.language.as_str());
+        Some(match language {
+          // json with unquoted variables doesn't parse, it is still sent as written
+          Some("json") => match serde_json::from_str(&raw) {
+            Ok(json) => request::RequestBody::JSON(json),
+            Err(_) => request::RequestBody::TEXT(raw),
+          },
+          Some("xml") => request::RequestBody::XML(raw),
+          Some("html") => request::RequestBody::HTML(raw),
+          Some("javascript") => request::RequestBody::JAVASCRIPT(raw),
+          _ => request::RequestBody::TEXT(raw),
+        })
+      }
+      "urlencoded" => Some(request::RequestBody::FORM(
+        self
+          .urlencoded
+          .iter()
+          .flatten()
+          .map(|p| request::FormField {
+            key: p.key.clone(),
+            value: p.value.clone(),
+            enabled: !p.disabled,
+          })
+          .collect(),
+      )),
+      "formdata" => Some(request::RequestBody::MULTIPART(
+        self
+          .formdata
+          .iter()
+          .flatten()
+          .map(FormParam::to_multipart_part)
+          .collect(),
+      )),
+      "file" => self
+        .file
+        .as_ref()
+        .and_then(|f| f.src.clone())
+        .map(request::RequestBody::BINARY),
+      "graphql" => self.graphql.as_ref().map(|g| {
+        request::RequestBody::GRAPHQL(request::GraphQLBody {
+          query: g.query.clone(),
+          variables: g.variables.clone(),
+          operation_name: g.operation_name.clone(),
+        })
+      }),
+      other => {
+        warn!("Skipping unsupported body mode {}", other);
+        None
+      }
+    }
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct FormParam {
+  pub key: String,
+  #[serde(default, deserialize_with = "variable_value_to_string")]
+  pub value: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub r#type: Option<String>,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub disabled: bool,
+  // formdata file parts only
+  #[serde(
+    default,
+    deserialize_with = "file_src",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub src: Option<String>,
+  #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
+  pub content_type: Option<String>,
+}
+
+impl FormParam {
+  fn from_multipart_part(part: &request::MultipartPart) -> Self {
+    match part {
+      request::MultipartPart::Text {
+        name,
+        value,
+        enabled,
+      } => FormParam {
+        key: name.clone(),
+        value: value.clone(),
+        r#type: Some(String::from("text")),
+        disabled: !enabled,
+        src: None,
+        content_type: None,
+      },
+      // postman has nowhere to keep a file name, it is taken from the path again on import
+      request::MultipartPart::File {
+        name,
+        path,
+        content_type,
+        enabled,
+        ..
+      } => FormParam {
+        key: name.clone(),
+        value: String::new(),
+        r#type: Some(String::from("file")),
+        disabled: !enabled,
+        src: Some(path.clone()),
+        content_type: content_type.clone(),
+      },
+    }
+  }
+
+  fn to_multipart_part(&self) -> request::MultipartPart {
+    if self.r#type.as_deref() == Some("file") {
+      request::MultipartPart::File {
+        name: self.key.clone(),
+        path: self.src.clone().unwrap_or_default(),
+        filename: None,
+        content_type: self.content_type.clone(),
+        enabled: !self.disabled,
+      }
+    } else {
+      request::MultipartPart::Text {
+        name: self.key.clone(),
+        value: self.value.clone(),
+        enabled: !self.disabled,
+      }
+    }
+  }
+}
+
+// postman writes a single path, a list of paths or null, only the first path is used
+fn file_src<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let value: serde_json::Value = Deserialize::deserialize(deserializer)?;
+  Ok(match value {
+    serde_json::Value::String(s) => Some(s),
+    serde_json::Value::Array(paths) => paths.into_iter().find_map(|p| match p {
+      serde_json::Value::String(s) => Some(s),
+      _ => None,
+    }),
+    _ => None,
+  })
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct BodyFile {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub src: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -72,14 +660,263 @@ pub struct BodyOptions {
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct BodyOptionsRaw {
-  language: String,
+  pub language: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CollectionAuth {
   pub r#type: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub bearer: Option<Vec<AuthValue>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   pub oauth2: Option<Vec<AuthValue>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub apikey: Option<Vec<AuthValue>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub basic: Option<Vec<AuthValue>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub digest: Option<Vec<AuthValue>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub awsv4: Option<Vec<AuthValue>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub jwt: Option<Vec<AuthValue>>,
+}
+
+impl CollectionAuth {
+  // What the postman auth sends. Types that aren't supported are sent without auth
+  pub fn to_request_auth(&self) -> RequestAuth {
+    match self.r#type.as_str() {
+      "noauth" => RequestAuth::NONE,
+      "inherit" => RequestAuth::INHERIT,
+      "bearer" => RequestAuth::BEARER(auth_value(&self.bearer, "token")),
+      "apikey" => RequestAuth::APIKEY {
+        key: auth_value(&self.apikey, "key"),
+        value: auth_value(&self.apikey, "value"),
+        placement: match auth_value(&self.apikey, "in").as_str() {
+          "query" => ApiKeyPlacement::QUERY,
+          _ => ApiKeyPlacement::HEADER,
+        },
+      },
+      "basic" => RequestAuth::BASIC {
+        username: auth_value(&self.basic, "username"),
+        password: auth_value(&self.basic, "password"),
+      },
+      "digest" => RequestAuth::DIGEST {
+        username: auth_value(&self.digest, "username"),
+        password: auth_value(&self.digest, "password"),
+      },
+      "awsv4" => RequestAuth::AWSSIGV4(AwsSigV4Auth {
+        access_key: auth_value(&self.awsv4, "accessKey"),
+        secret_key: auth_value(&self.awsv4, "secretKey"),
+        session_token: auth_value(&self.awsv4, "sessionToken"),
+        region: auth_value(&self.awsv4, "region"),
+        service: auth_value(&self.awsv4, "service"),
+      }),
+      "jwt" => {
+        let value = |key| auth_value(&self.jwt, key);
+        let defaults = JwtAuth::default();
+        let mut jwt = JwtAuth {
+          algorithm: Some(value("algorithm"))
+            .filter(|a| !a.is_empty())
+            .unwrap_or(defaults.algorithm),
+          secret: String::new(),
+          secret_base64: value("isSecretBase64Encoded") == "true",
+          payload: Some(value("payload"))
+            .filter(|p| !p.is_empty())
+            .unwrap_or(defaults.payload),
+          header_prefix: match self.jwt.iter().flatten().any(|v| v.key == "headerPrefix") {
+            true => value("headerPrefix"),
+            false => defaults.header_prefix,
+          },
+        };
+        // postman keeps private keys in privateKey, falling back to the other one in case the
+        // algorithm was changed without moving the key
+        let (key, other) = match jwt.uses_secret() {
+          true => ("secret", "privateKey"),
+          false => ("privateKey", "secret"),
+        };
+        jwt.secret = Some(value(key))
+          .filter(|s| !s.is_empty())
+          .unwrap_or_else(|| value(other));
+        RequestAuth::JWT(jwt)
+      }
+      "oauth2" => {
+        let value = |key| auth_value(&self.oauth2, key);
+        let access_token = value("accessToken");
+        // a token saved with the collection and no way to get a new one
+        if value("accessTokenUrl").is_empty() && !access_token.is_empty() {
+          return RequestAuth::BEARER(access_token);
+        }
+        RequestAuth::OAUTH2(OAuth2Request {
+          grant: match value("grant_type").as_str() {
+            "authorization_code" | "authorization_code_with_pkce" => OAuth2Grant::AUTHORIZATIONCODE,
+            "password_credentials" => OAuth2Grant::PASSWORD,
+            // not a postman grant, only written by postie
+            "device_code" => OAuth2Grant::DEVICECODE,
+            _ => OAuth2Grant::CLIENTCREDENTIALS,
+          },
+          access_token_url: value("accessTokenUrl"),
+          refresh_url: value("refreshTokenUrl"),
+          auth_url: value("authUrl"),
+          device_authorization_url: value("deviceAuthorizationUrl"),
+          redirect_uri: value("redirect_uri"),
+          client_id: value("clientId"),
+          client_secret: value("clientSecret"),
+          client_authentication: match value("client_authentication").as_str() {
+            "body" => OAuth2ClientAuth::BODY,
+            _ => OAuth2ClientAuth::HEADER,
+          },
+          username: value("username"),
+          password: value("password"),
+          request: OAuthRequestBody {
+            scope: value("scope"),
+            audience: value("audience"),
+          },
+          ..Default::default()
+        })
+      }
+      other => {
+        warn!("{} auth is not supported, sending without auth", other);
+        RequestAuth::NONE
+      }
+    }
+  }
+  // The postman auth saved for a request, None for inherit which postman leaves out
+  pub fn from_request_auth(auth: &RequestAuth) -> Option<Self> {
+    let auth = match auth.clone() {
+      RequestAuth::INHERIT => return None,
+      RequestAuth::NONE => CollectionAuth {
+        r#type: "noauth".into(),
+        ..Default::default()
+      },
+      RequestAuth::BEARER(token) => CollectionAuth {
+        r#type: "bearer".into(),
+        bearer: auth_values(vec![("token", token)]),
+        ..Default::default()
+      },
+      RequestAuth::APIKEY {
+        key,
+        value,
+        placement,
+      } => CollectionAuth {
+        r#type: "apikey".into(),
+        apikey: auth_values(vec![
+          ("key", key),
+          ("value", value),
+          (
+            "in",
+            match placement {
+              ApiKeyPlacement::HEADER => "header".into(),
+              ApiKeyPlacement::QUERY => "query".into(),
+            },
+          ),
+        ]),
+        ..Default::default()
+      },
+      RequestAuth::BASIC { username, password } => CollectionAuth {
+        r#type: "basic".into(),
+        basic: auth_values(vec![("username", username), ("password", password)]),
+        ..Default::default()
+      },
+      RequestAuth::DIGEST { username, password } => CollectionAuth {
+        r#type: "digest".into(),
+        digest: auth_values(vec![("username", username), ("password", password)]),
+        ..Default::default()
+      },
+      RequestAuth::AWSSIGV4(config) => CollectionAuth {
+        r#type: "awsv4".into(),
+        awsv4: auth_values(vec![
+          ("accessKey", config.access_key),
+          ("secretKey", config.secret_key),
+          ("sessionToken", config.session_token),
+          ("region", config.region),
+          ("service", config.service),
+        ]),
+        ..Default::default()
+      },
+      RequestAuth::JWT(config) => {
+        let key = match config.uses_secret() {
+          true => "secret",
+          false => "privateKey",
+        };
+        let mut jwt = auth_values(vec![
+          ("algorithm", config.algorithm),
+          (key, config.secret),
+          ("payload", config.payload),
+          ("headerPrefix", config.header_prefix),
+        ]);
+        jwt.get_or_insert_with(Vec::new).push(AuthValue {
+          key: "isSecretBase64Encoded".into(),
+          value: AuthValueUnion::Object(serde_json::Value::Bool(config.secret_base64)),
+          r#type: "boolean".into(),
+        });
+        CollectionAuth {
+          r#type: "jwt".into(),
+          jwt,
+          ..Default::default()
+        }
+      }
+      RequestAuth::OAUTH2(config) => CollectionAuth {
+        r#type: "oauth2".into(),
+        oauth2: auth_values(vec![
+          (
+            "grant_type",
+            match config.grant {
+              OAuth2Grant::CLIENTCREDENTIALS => "client_credentials".into(),
+              OAuth2Grant::AUTHORIZATIONCODE => "authorization_code_with_pkce".into(),
+              OAuth2Grant::PASSWORD => "password_credentials".into(),
+              OAuth2Grant::DEVICECODE => "device_code".into(),
+            },
+          ),
+          ("accessTokenUrl", config.access_token_url),
+          ("refreshTokenUrl", config.refresh_url),
+          ("authUrl", config.auth_url),
+          ("deviceAuthorizationUrl", config.device_authorization_url),
+          ("redirect_uri", config.redirect_uri),
+          ("clientId", config.client_id),
+          ("clientSecret", config.client_secret),
+          (
+            "client_authentication",
+            match config.client_authentication {
+              OAuth2ClientAuth::HEADER => "header".into(),
+              OAuth2ClientAuth::BODY => "body".into(),
+            },
+          ),
+          ("username", config.username),
+          ("password", config.password),
+          ("scope", config.request.scope),
+          ("audience", config.request.audience),
+        ]),
+        ..Default::default()
+      },
+    };
+    Some(auth)
+  }
+}
+
+// Postman auth keys of string values
+fn auth_values(values: Vec<(&str, String)>) -> Option<Vec<AuthValue>> {
+  Some(
+    values
+      .into_iter()
+      .map(|(key, value)| AuthValue {
+        key: key.into(),
+        value: AuthValueUnion::String(value),
+        r#type: "string".into(),
+      })
+      .collect(),
+  )
+}
+
+// The value of a postman auth key, empty when it isn't there
+fn auth_value(values: &Option<Vec<AuthValue>>, key: &str) -> String {
+  let Some(value) = values.iter().flatten().find(|v| v.key == key) else {
+    return String::new();
+  };
+  match &value.value {
+    AuthValueUnion::String(s) => s.clone(),
+    AuthValueUnion::Object(v) => v.to_string(),
+  }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+
+// A cookie kept in the jar, from a Set-Cookie header or added in the cookie manager
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Cookie {
+  pub name: String,
+  pub value: String,
+  // lowercase and without a leading dot
+  pub domain: String,
+  // only sent to the domain itself, set without a Domain attribute
+  pub host_only: bool,
+  pub path: String,
+  // None is a session cookie, it's kept until it's deleted
+  pub expires: Option<DateTime<Utc>>,
+  // only sent over https
+  pub secure: bool,
+  pub http_only: bool,
+  pub same_site: Option<String>,
+}
+
+impl Cookie {
+  pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+    self.expires.is_some_and(|expires| expires <= now)
+  }
+
+  // Whether both are the same cookie, a new one with the same name, domain and path replaces it
+  pub fn same_as(&self, other: &Cookie) -> bool {
+    self.name == other.name && self.domain == other.domain && self.path == other.path
+  }
+}
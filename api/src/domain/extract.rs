@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+// Copies a value out of a successful response into an environment variable, so the next
+// request can use it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtractRule {
+  pub variable: String,
+  #[serde(flatten)]
+  pub source: ExtractSource,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExtractSource {
+  JsonPath { path: String },
+  Header { name: String },
+  // the first capture group is used when there is one, otherwise the whole match
+  Regex { pattern: String },
+}
+
+impl ExtractSource {
+  // Blank sources of every kind, in the order the gui offers them
+  pub fn all_kinds() -> Vec<ExtractSource> {
+    vec![
+      ExtractSource::JsonPath {
+        path: String::from("$"),
+      },
+      ExtractSource::Header {
+        name: String::new(),
+      },
+      ExtractSource::Regex {
+        pattern: String::new(),
+      },
+    ]
+  }
+
+  pub fn kind(&self) -> &'static str {
+    match self {
+      ExtractSource::JsonPath { .. } => "JSONPath",
+      ExtractSource::Header { .. } => "Header",
+      ExtractSource::Regex { .. } => "Regex",
+    }
+  }
+}
+
+impl std::fmt::Display for ExtractRule {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.source {
+      ExtractSource::JsonPath { path } => write!(f, "{} from {}", self.variable, path),
+      ExtractSource::Header { name } => write!(f, "{} from header {}", self.variable, name),
+      ExtractSource::Regex { pattern } => write!(f, "{} from /{}/", self.variable, pattern),
+    }
+  }
+}
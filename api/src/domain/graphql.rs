@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Asks the endpoint for every type with its fields, enough to complete selections
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      fields(includeDeprecated: true) {
+        name
+        description
+        type { ...TypeRef }
+      }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+}";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLSchema {
+  pub query_type: Option<String>,
+  pub mutation_type: Option<String>,
+  pub subscription_type: Option<String>,
+  pub types: Vec<GraphQLType>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLType {
+  pub name: String,
+  pub kind: String,
+  pub fields: Vec<GraphQLField>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLField {
+  pub name: String,
+  // the type as it is written in the schema, like [User!]!
+  pub type_name: String,
+  // the type with lists and non null stripped, the one selections are made on
+  pub named_type: String,
+  pub description: Option<String>,
+}
+
+impl GraphQLSchema {
+  // Reads the response to INTROSPECTION_QUERY
+  pub fn from_introspection(response: &Value) -> Result<GraphQLSchema, String> {
+    if let Some(errors) = response.get("errors").filter(|e| !e.is_null()) {
+      return Err(format!("Introspection failed: {}", errors));
+    }
+    let schema = response
+      .pointer("/data/__schema")
+      .ok_or("Introspection response has no data.__schema")?;
+    let root_name = |key: &str| {
+      schema
+        .pointer(&format!("/{}/name", key))
+        .and_then(Value::as_str)
+        .map(String::from)
+    };
+    let types = schema
+      .get("types")
+      .and_then(Value::as_array)
+      .ok_or("Introspection response has no types")?
+      .iter()
+      .filter_map(|t| {
+        Some(GraphQLType {
+          name: t.get("name")?.as_str()?.to_string(),
+          kind: t.get("kind")?.as_str()?.to_string(),
+          fields: t
+            .get("fields")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(GraphQLField::from_introspection)
+            .collect(),
+        })
+      })
+      .collect();
+    Ok(GraphQLSchema {
+      query_type: root_name("queryType"),
+      mutation_type: root_name("mutationType"),
+      subscription_type: root_name("subscriptionType"),
+      types,
+    })
+  }
+
+  pub fn find_type(&self, name: &str) -> Option<&GraphQLType> {
+    self.types.iter().find(|t| t.name == name)
+  }
+
+  // The root type an operation keyword selects on, queries when there is no keyword
+  pub fn root_type(&self, operation: &str) -> Option<&str> {
+    match operation {
+      "mutation" => self.mutation_type.as_deref(),
+      "subscription" => self.subscription_type.as_deref(),
+      _ => self.query_type.as_deref(),
+    }
+  }
+}
+
+impl GraphQLField {
+  fn from_introspection(field: &Value) -> Option<GraphQLField> {
+    let type_ref = field.get("type")?;
+    let mut named = type_ref;
+    while named.get("name").is_none_or(Value::is_null) {
+      named = named.get("ofType")?;
+    }
+    Some(GraphQLField {
+      name: field.get("name")?.as_str()?.to_string(),
+      type_name: type_name(type_ref)?,
+      named_type: named.get("name")?.as_str()?.to_string(),
+      description: field
+        .get("description")
+        .and_then(Value::as_str)
+        .map(String::from),
+    })
+  }
+}
+
+fn type_name(type_ref: &Value) -> Option<String> {
+  match type_ref.get("kind")?.as_str()? {
+    "NON_NULL" => Some(format!("{}!", type_name(type_ref.get("ofType")?)?)),
+    "LIST" => Some(format!("[{}]", type_name(type_ref.get("ofType")?)?)),
+    _ => Some(type_ref.get("name")?.as_str()?.to_string()),
+  }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{environment::EnvironmentFile, header::Header, request::HttpRequest};
+
+#[derive(Debug, Clone)]
+pub struct GrpcRequest {
+  pub tab_id: Uuid,
+  pub id: Uuid,
+  pub name: Option<String>,
+  // grpc://host:port/package.Service/Method, grpcs:// connects with tls
+  pub url: String,
+  // sent as metadata
+  pub headers: Option<Vec<(String, String)>>,
+  // the request message as json
+  pub message: String,
+  // .proto files describing the service, the server's reflection service is asked when empty
+  pub proto_files: Vec<String>,
+  pub environment: EnvironmentFile,
+  pub variables: Vec<(String, String)>,
+}
+
+impl GrpcRequest {
+  // The gui builds grpc requests like http ones, the message is the tab's body
+  pub fn from_http_request(
+    request: HttpRequest,
+    message: String,
+    proto_files: Vec<String>,
+  ) -> Self {
+    GrpcRequest {
+      tab_id: request.tab_id,
+      id: request.id,
+      name: request.name,
+      url: request.url,
+      headers: request.headers,
+      message,
+      proto_files,
+      environment: request.environment,
+      variables: request.variables,
+    }
+  }
+
+  // Tabs with a grpc:// or grpcs:// url are grpc tabs
+  pub fn is_grpc_url(url: &str) -> bool {
+    let url = url.trim_start().to_lowercase();
+    url.starts_with("grpc://") || url.starts_with("grpcs://")
+  }
+}
+
+// A method a grpc tab can call
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GrpcMethod {
+  // fully qualified, e.g. helloworld.Greeter
+  pub service: String,
+  pub name: String,
+  pub client_streaming: bool,
+  pub server_streaming: bool,
+  // the input message with every field at its default, a starting point for the request
+  pub example: String,
+}
+
+impl GrpcMethod {
+  // What goes after the host in a grpc url
+  pub fn path(&self) -> String {
+    format!("{}/{}", self.service, self.name)
+  }
+
+  pub fn kind(&self) -> &'static str {
+    match (self.client_streaming, self.server_streaming) {
+      (false, false) => "unary",
+      (false, true) => "server streaming",
+      (true, false) => "client streaming",
+      (true, true) => "bidi streaming",
+    }
+  }
+}
+
+// The outcome of a call, a failed status still keeps the messages received before it
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrpcResponse {
+  // the grpc status code, 0 is OK
+  pub code: i32,
+  // e.g. Ok or NotFound
+  pub status: String,
+  pub status_message: String,
+  // response headers followed by the trailers
+  pub metadata: Vec<Header>,
+  pub messages: Vec<Value>,
+  pub server_streaming: bool,
+}
+
+impl GrpcResponse {
+  pub fn is_ok(&self) -> bool {
+    self.code == 0
+  }
+
+  // What is shown and saved as the response body. A unary call is its one message, a stream
+  // is the list of them
+  pub fn body(&self) -> Value {
+    if !self.is_ok() && self.messages.is_empty() {
+      return serde_json::json!({ "error": self.status_message, "status": self.status });
+    }
+    if self.server_streaming {
+      Value::Array(self.messages.clone())
+    } else {
+      self.messages.first().cloned().unwrap_or(Value::Null)
+    }
+  }
+}
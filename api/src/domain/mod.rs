@@ -1,8 +1,18 @@
+pub mod assertion;
+pub mod auth;
+pub mod client;
 pub mod collection;
+pub mod cookie;
 pub mod environment;
+pub mod extract;
+pub mod graphql;
+pub mod grpc;
 pub mod header;
 pub mod request;
 pub mod request_item;
 pub mod response;
+pub mod run;
+pub mod sse;
 pub mod tab;
 pub mod ui;
+pub mod websocket;
@@ -1,6 +1,6 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
-use crate::domain::{environment, header, request};
+use crate::domain::{assertion, auth, collection, environment, extract, header, request};
 
 use reqwest;
 use serde::{Deserialize, Serialize};
@@ -67,6 +67,7 @@ impl From<HttpMethod> for reqwest::Method {
   }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum PostieRequest {
   HTTP(HttpRequest),
@@ -83,26 +84,381 @@ pub struct HttpRequest {
   pub headers: Option<Vec<(String, String)>>,
   pub body: Option<RequestBody>,
   pub environment: environment::EnvironmentFile,
+  // Collection, folder and request variables in scope, lowest precedence first. All of
+  // them take precedence over the environment
+  pub variables: Vec<(String, String)>,
+  pub scripts: RequestScripts,
+  pub assertions: Vec<assertion::Assertion>,
+  pub extract: Vec<extract::ExtractRule>,
+  // overrides of the default timeouts, unset ones use the defaults
+  pub timeouts: RequestTimeouts,
+  pub auth: auth::RequestAuth,
 }
 
-#[derive(Clone, Debug)]
+// Limits on how long a request may take, in milliseconds. 0 means no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequestTimeouts {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub connect_ms: Option<u64>,
+  // waiting for the response to start, and for each chunk of its body after that
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub read_ms: Option<u64>,
+  // everything from connecting to reading the last byte of the body
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub total_ms: Option<u64>,
+}
+
+impl RequestTimeouts {
+  // Used until other defaults are saved
+  pub const DEFAULTS: RequestTimeouts = RequestTimeouts {
+    connect_ms: Some(10_000),
+    read_ms: Some(30_000),
+    total_ms: None,
+  };
+
+  pub fn is_empty(&self) -> bool {
+    *self == RequestTimeouts::default()
+  }
+
+  // Fills the ones not set here from `defaults`
+  pub fn or(&self, defaults: &RequestTimeouts) -> RequestTimeouts {
+    RequestTimeouts {
+      connect_ms: self.connect_ms.or(defaults.connect_ms),
+      read_ms: self.read_ms.or(defaults.read_ms),
+      total_ms: self.total_ms.or(defaults.total_ms),
+    }
+  }
+
+  pub fn connect(&self) -> Option<Duration> {
+    limit(self.connect_ms)
+  }
+
+  pub fn read(&self) -> Option<Duration> {
+    limit(self.read_ms)
+  }
+
+  pub fn total(&self) -> Option<Duration> {
+    limit(self.total_ms)
+  }
+}
+
+fn limit(ms: Option<u64>) -> Option<Duration> {
+  ms.filter(|ms| *ms > 0).map(Duration::from_millis)
+}
+
+// Rhai scripts run around a request, an empty string means there is no script
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequestScripts {
+  pub pre_request: String,
+  pub test: String,
+}
+
+impl Default for HttpRequest {
+  fn default() -> Self {
+    Self {
+      tab_id: Uuid::new_v4(),
+      id: Uuid::new_v4(),
+      name: None,
+      method: HttpMethod::GET,
+      url: "".into(),
+      headers: None,
+      body: None,
+      environment: environment::EnvironmentFile::default(),
+      variables: vec![],
+      scripts: RequestScripts::default(),
+      assertions: vec![],
+      extract: vec![],
+      timeouts: RequestTimeouts::default(),
+      auth: auth::RequestAuth::default(),
+    }
+  }
+}
+
+impl HttpRequest {
+  // Builds a request from a saved collection item, to be sent outside of any gui tab
+  pub fn from_collection_item(
+    collection: &collection::Collection,
+    item: &collection::CollectionItem,
+    environment: environment::EnvironmentFile,
+  ) -> Self {
+    let headers = item.request.header.as_ref().map(|headers| {
+      headers
+        .iter()
+        .map(|h| (h.key.clone(), h.value.clone()))
+        .collect()
+    });
+    let body = item.request.body.as_ref().and_then(|b| b.to_request_body());
+    HttpRequest {
+      tab_id: Uuid::new_v4(),
+      id: Uuid::new_v4(),
+      name: Some(item.name.clone()),
+      method: HttpMethod::from_str(&item.request.method).unwrap_or(HttpMethod::GET),
+      url: item.request.url.raw.clone(),
+      headers,
+      body,
+      environment,
+      variables: collection.variables_for(&item.id),
+      scripts: item.scripts(),
+      assertions: item.assertions.clone().unwrap_or_default(),
+      extract: item.extract.clone().unwrap_or_default(),
+      timeouts: item.timeouts.unwrap_or_default(),
+      auth: collection
+        .auth_for(&item.id)
+        .map(|(auth, _)| auth)
+        .unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum RequestBody {
   JSON(serde_json::Value),
-  FORM(String),
+  FORM(Vec<FormField>),
+  TEXT(String),
+  XML(String),
+  HTML(String),
+  JAVASCRIPT(String),
+  // path of a file whose bytes are sent as they are
+  BINARY(String),
+  MULTIPART(Vec<MultipartPart>),
+  GRAPHQL(GraphQLBody),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLBody {
+  pub query: String,
+  // kept as written so variables like {{id}} can be substituted before it is parsed
+  #[serde(default)]
+  pub variables: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub operation_name: Option<String>,
+}
+
+impl GraphQLBody {
+  // The json payload posted to the endpoint, empty variables and operation names are left out
+  pub fn payload(&self) -> Result<serde_json::Value, serde_json::Error> {
+    let mut payload = serde_json::json!({ "query": self.query });
+    if !self.variables.trim().is_empty() {
+      payload["variables"] = serde_json::from_str(&self.variables)?;
+    }
+    if let Some(name) = self.operation_name.as_ref().filter(|n| !n.is_empty()) {
+      payload["operationName"] = serde_json::Value::from(name.clone());
+    }
+    Ok(payload)
+  }
+}
+
+// One x-www-form-urlencoded pair, kept in the order it was written
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FormField {
+  pub key: String,
+  pub value: String,
+  pub enabled: bool,
+}
+
+impl FormField {
+  pub fn new(key: &str, value: &str) -> Self {
+    FormField {
+      key: key.into(),
+      value: value.into(),
+      enabled: true,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MultipartPart {
+  Text {
+    name: String,
+    value: String,
+    // disabled parts are kept with the body but not sent
+    #[serde(default = "part_enabled")]
+    enabled: bool,
+  },
+  File {
+    name: String,
+    path: String,
+    // defaults to the name of the file at `path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(default = "part_enabled")]
+    enabled: bool,
+  },
+}
+
+// parts saved before they could be disabled are all sent
+fn part_enabled() -> bool {
+  true
+}
+
+impl MultipartPart {
+  pub fn is_enabled(&self) -> bool {
+    match self {
+      MultipartPart::Text { enabled, .. } | MultipartPart::File { enabled, .. } => *enabled,
+    }
+  }
+}
+
+impl RequestBody {
+  // Builds a body from the text typed into a tab, an empty body isn't sent at all
+  pub fn from_text(body_type: &BodyType, text: &str) -> Option<RequestBody> {
+    if text.trim().is_empty() {
+      return None;
+    }
+    let text = text.to_string();
+    Some(match body_type {
+      // json with unquoted variables doesn't parse, it is still sent as written
+      BodyType::JSON => match serde_json::from_str(&text) {
+        Ok(json) => RequestBody::JSON(json),
+        Err(_) => RequestBody::TEXT(text),
+      },
+      // tabs keep form fields as json, older tabs and history entries have the encoded string
+      BodyType::FORM => RequestBody::FORM(serde_json::from_str(&text).unwrap_or_else(|_| {
+        url::form_urlencoded::parse(text.trim().as_bytes())
+          .map(|(key, value)| FormField::new(&key, &value))
+          .collect()
+      })),
+      BodyType::TEXT => RequestBody::TEXT(text),
+      BodyType::XML => RequestBody::XML(text),
+      BodyType::HTML => RequestBody::HTML(text),
+      BodyType::JAVASCRIPT => RequestBody::JAVASCRIPT(text),
+      BodyType::BINARY => RequestBody::BINARY(text),
+      // tabs keep multipart parts as json
+      BodyType::MULTIPART => {
+        RequestBody::MULTIPART(serde_json::from_str(&text).unwrap_or_default())
+      }
+      // tabs keep the query, variables and operation name as json, anything else is a query
+      BodyType::GRAPHQL => {
+        RequestBody::GRAPHQL(serde_json::from_str(&text).unwrap_or_else(|_| GraphQLBody {
+          query: text,
+          ..Default::default()
+        }))
+      }
+    })
+  }
+
+  pub fn body_type(&self) -> BodyType {
+    match self {
+      RequestBody::JSON(_) => BodyType::JSON,
+      RequestBody::FORM(_) => BodyType::FORM,
+      RequestBody::TEXT(_) => BodyType::TEXT,
+      RequestBody::XML(_) => BodyType::XML,
+      RequestBody::HTML(_) => BodyType::HTML,
+      RequestBody::JAVASCRIPT(_) => BodyType::JAVASCRIPT,
+      RequestBody::BINARY(_) => BodyType::BINARY,
+      RequestBody::MULTIPART(_) => BodyType::MULTIPART,
+      RequestBody::GRAPHQL(_) => BodyType::GRAPHQL,
+    }
+  }
+
+  // The body as it is shown in a tab or saved in the history, binary bodies show their path
+  pub fn to_text(&self) -> String {
+    match self {
+      RequestBody::JSON(j) => j.to_string(),
+      RequestBody::FORM(fields) => serde_json::to_string_pretty(fields).unwrap_or_default(),
+      RequestBody::TEXT(s)
+      | RequestBody::XML(s)
+      | RequestBody::HTML(s)
+      | RequestBody::JAVASCRIPT(s)
+      | RequestBody::BINARY(s) => s.clone(),
+      RequestBody::MULTIPART(parts) => serde_json::to_string_pretty(parts).unwrap_or_default(),
+      RequestBody::GRAPHQL(graphql) => serde_json::to_string_pretty(graphql).unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BodyType {
+  #[default]
+  JSON,
+  FORM,
+  TEXT,
+  XML,
+  HTML,
+  JAVASCRIPT,
+  BINARY,
+  MULTIPART,
+  GRAPHQL,
+}
+
+impl BodyType {
+  pub fn all() -> Vec<BodyType> {
+    vec![
+      BodyType::JSON,
+      BodyType::FORM,
+      BodyType::TEXT,
+      BodyType::XML,
+      BodyType::HTML,
+      BodyType::JAVASCRIPT,
+      BodyType::BINARY,
+      BodyType::MULTIPART,
+      BodyType::GRAPHQL,
+    ]
+  }
+
+  // Sent unless the request already has a Content-Type header. Multipart bodies always get
+  // reqwest's header, it has to carry the boundary
+  pub fn content_type(&self) -> &'static str {
+    match self {
+      BodyType::JSON => "application/json",
+      BodyType::FORM => "application/x-www-form-urlencoded",
+      BodyType::TEXT => "text/plain",
+      BodyType::XML => "application/xml",
+      BodyType::HTML => "text/html",
+      BodyType::JAVASCRIPT => "application/javascript",
+      BodyType::BINARY => "application/octet-stream",
+      BodyType::MULTIPART => "multipart/form-data",
+      BodyType::GRAPHQL => "application/json",
+    }
+  }
+}
+impl std::fmt::Display for BodyType {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{:?}", self)
+  }
+}
+
+#[derive(Debug)]
+pub struct BodyTypeParseError;
+impl FromStr for BodyType {
+  type Err = BodyTypeParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    BodyType::all()
+      .into_iter()
+      .find(|t| t.to_string() == s)
+      .ok_or(BodyTypeParseError)
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct OAuth2Request {
+  pub grant: auth::OAuth2Grant,
   pub access_token_url: String,
+  // where refresh tokens are sent, the access token url when empty
   pub refresh_url: String,
+  // authorization endpoint the browser is sent to for the authorization code grant
+  pub auth_url: String,
+  // e.g. http://127.0.0.1:8765/callback, a free port on 127.0.0.1 when empty
+  pub redirect_uri: String,
+  pub device_authorization_url: String,
   pub client_id: String,
   pub client_secret: String,
+  pub client_authentication: auth::OAuth2ClientAuth,
+  // of the password grant
+  pub username: String,
+  pub password: String,
   pub request: OAuthRequestBody,
+  // the selected environment, set before the config is used rather than saved with it
+  #[serde(skip)]
+  pub environment: environment::EnvironmentFile,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 pub struct OAuthRequestBody {
-  pub grant_type: String,
   pub scope: String,
   pub audience: String,
 }
@@ -1,3 +1,5 @@
+use crate::domain::response::TestResult;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct RequestHistoryItem {
   pub id: String,
@@ -5,4 +7,5 @@ pub struct RequestHistoryItem {
   pub response_id: String,
   pub sent_at: String,
   pub response_time: usize,
+  pub test_results: Vec<TestResult>,
 }
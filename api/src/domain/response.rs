@@ -1,7 +1,13 @@
-use serde::Deserialize;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
 use sqlx;
 
-use crate::domain::header;
+use crate::domain::{
+  cookie::Cookie,
+  environment::EnvironmentFile,
+  header::{self, Header},
+};
 
 #[derive(Debug, sqlx::FromRow, sqlx::Encode, sqlx::Decode)]
 pub struct DBResponse {
@@ -13,17 +19,59 @@ pub struct DBResponse {
   pub body: Option<String>,
 }
 
+// A token endpoint's answer, only the access token is required
 #[derive(Clone, Debug, Deserialize)]
 pub struct OAuthResponse {
   pub access_token: String,
-  pub expires_in: i32,
-  pub token_type: String,
+  pub expires_in: Option<i64>,
+  pub token_type: Option<String>,
+  pub refresh_token: Option<String>,
+  pub scope: Option<String>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Response {
   pub status: String,
+  pub status_code: u16,
   pub data: ResponseData,
+  pub response_time: u128,
+  pub headers: Vec<Header>,
+  pub test_results: Vec<TestResult>,
+  // Set when a script changed the environment, so callers can pick up the new values
+  pub environment: Option<EnvironmentFile>,
+  pub body: ResponseBody,
+  // the cookies its Set-Cookie headers set, expired ones deleted a cookie
+  pub cookies: Vec<Cookie>,
+}
+
+// What was received, the data of a response is only a preview of a large or binary body
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseBody {
+  // bytes received
+  pub size: u64,
+  pub content_type: String,
+  // not text, the data only describes it and the body is in the file
+  pub binary: bool,
+  // larger than the preview limit, or stopped before the whole body arrived
+  pub truncated: bool,
+  // the whole body when it wasn't all kept in memory, a temporary file to save it from. Only
+  // written when the api keeps response files, see PostieApi::keep_response_files
+  pub file: Option<PathBuf>,
+}
+
+// How much of a response body has been read, the total is the content-length if there was one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResponseProgress {
+  pub received: u64,
+  pub total: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestResult {
+  pub name: String,
+  pub passed: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub message: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use super::response::TestResult;
+
+// One row of a data file, its values are used as variables for one iteration
+pub type DataRow = Vec<(String, String)>;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+  // Defaults to one iteration per data row, or a single one without data
+  pub iterations: Option<usize>,
+  pub data: Vec<DataRow>,
+  pub delay_ms: u64,
+  pub stop_on_failure: bool,
+}
+
+impl RunOptions {
+  pub fn iteration_count(&self) -> usize {
+    self.iterations.unwrap_or(self.data.len().max(1))
+  }
+
+  // Postman reuses the last row when there are more iterations than rows
+  pub fn data_for(&self, iteration: usize) -> &[(String, String)] {
+    match self.data.get(iteration).or(self.data.last()) {
+      Some(row) => row,
+      None => &[],
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunResult {
+  // starts at 1
+  pub iteration: usize,
+  pub request_id: String,
+  pub name: String,
+  pub method: String,
+  pub url: String,
+  pub status: Option<String>,
+  pub status_code: Option<u16>,
+  pub response_time: Option<u128>,
+  pub test_results: Vec<TestResult>,
+  // set when the request couldn't be sent at all
+  pub error: Option<String>,
+}
+
+impl RunResult {
+  pub fn passed(&self) -> bool {
+    self.error.is_none()
+      && self.status_code.is_some_and(|s| s < 400)
+      && self.test_results.iter().all(|t| t.passed)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+  pub id: String,
+  pub collection_id: String,
+  pub collection_name: String,
+  pub environment_name: String,
+  pub started_at: String,
+  pub duration_ms: u128,
+  pub iterations: usize,
+  // true when stop on failure ended the run before every request was sent
+  pub stopped_early: bool,
+  pub results: Vec<RunResult>,
+}
+
+impl RunReport {
+  pub fn passed(&self) -> usize {
+    self.results.iter().filter(|r| r.passed()).count()
+  }
+
+  pub fn failed(&self) -> usize {
+    self.results.len() - self.passed()
+  }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+// One server-sent event, a stream's transcript is the list of them
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SseEvent {
+  // rfc3339
+  pub received_at: String,
+  // the last id the server sent, it carries over to events without one
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<String>,
+  // None for the default `message` type
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub event: Option<String>,
+  pub data: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub retry: Option<u64>,
+}
+
+impl SseEvent {
+  pub fn event_type(&self) -> &str {
+    self.event.as_deref().unwrap_or("message")
+  }
+}
@@ -1,7 +1,10 @@
 use uuid::Uuid;
 
+use super::assertion::Assertion;
+use super::auth::AuthSettings;
+use super::extract::ExtractRule;
 use super::header::Headers;
-use super::request::HttpMethod;
+use super::request::{BodyType, HttpMethod, RequestScripts, RequestTimeouts};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Tab {
@@ -9,10 +12,19 @@ pub struct Tab {
   pub method: HttpMethod,
   pub url: String,
   pub req_body: String,
+  pub body_type: BodyType,
   pub req_headers: Headers,
   pub res_status: Option<String>,
   pub res_body: String,
   pub res_headers: Headers,
+  // Set when the tab was opened from a collection, so its scoped variables can be found
+  pub collection_id: Option<String>,
+  pub request_id: Option<String>,
+  pub scripts: RequestScripts,
+  pub assertions: Vec<Assertion>,
+  pub extract: Vec<ExtractRule>,
+  pub timeouts: RequestTimeouts,
+  pub auth: AuthSettings,
 }
 impl Default for Tab {
   fn default() -> Self {
@@ -20,11 +32,19 @@ impl Default for Tab {
       id: Uuid::new_v4(),
       url: "".into(),
       req_body: "".into(),
+      body_type: BodyType::default(),
       req_headers: Headers(vec![]),
       method: HttpMethod::GET,
       res_status: None,
       res_body: "".into(),
       res_headers: Headers(vec![]),
+      collection_id: None,
+      request_id: None,
+      scripts: RequestScripts::default(),
+      assertions: vec![],
+      extract: vec![],
+      timeouts: RequestTimeouts::default(),
+      auth: AuthSettings::default(),
     }
   }
 }
@@ -12,6 +12,11 @@ pub enum ImportMode {
   ENVIRONMENT,
 }
 #[derive(Serialize, Deserialize)]
+pub enum ExportMode {
+  COLLECTION,
+  ENVIRONMENT,
+}
+#[derive(Serialize, Deserialize)]
 pub enum NewWindowMode {
   COLLECTION,
   ENVIRONMENT,
@@ -24,12 +29,37 @@ pub enum RequestWindowMode {
   HEADERS,
   BODY,
   ENVIRONMENT,
+  SCRIPTS,
+  ASSERTIONS,
+  EXTRACT,
+  SETTINGS,
+}
+
+// How the text typed into a websocket tab is sent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WebSocketFrameMode {
+  TEXT,
+  JSON,
+  // base64
+  BINARY,
+}
+impl std::fmt::Display for WebSocketFrameMode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{:?}", self)
+  }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum AuthMode {
+  // uses the auth of the collection request the tab was opened from, see Collection::inherited_auth_for
+  #[default]
+  INHERIT,
   APIKEY,
   BEARER,
+  BASIC,
+  DIGEST,
+  AWSSIGV4,
+  JWT,
   OAUTH2,
   NONE,
 }
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use chrono::{DateTime, Utc};
+
+use super::{environment::EnvironmentFile, header::Header, request::HttpRequest};
+
+#[derive(Debug, Clone)]
+pub struct WebSocketRequest {
+  pub tab_id: Uuid,
+  pub id: Uuid,
+  pub name: Option<String>,
+  pub url: String,
+  // sent with the opening handshake
+  pub headers: Option<Vec<(String, String)>>,
+  pub environment: EnvironmentFile,
+  pub variables: Vec<(String, String)>,
+}
+
+impl WebSocketRequest {
+  // The gui builds websocket requests like http ones, the body isn't used
+  pub fn from_http_request(request: HttpRequest) -> Self {
+    WebSocketRequest {
+      tab_id: request.tab_id,
+      id: request.id,
+      name: request.name,
+      url: request.url,
+      headers: request.headers,
+      environment: request.environment,
+      variables: request.variables,
+    }
+  }
+
+  // Tabs with a ws:// or wss:// url are websocket tabs
+  pub fn is_websocket_url(url: &str) -> bool {
+    let url = url.trim_start().to_lowercase();
+    url.starts_with("ws://") || url.starts_with("wss://")
+  }
+}
+
+// A frame to send, json is checked before it is sent as text
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebSocketFrame {
+  Text(String),
+  Json(String),
+  Binary(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketDirection {
+  Sent,
+  Received,
+  // opening, closing and errors
+  Event,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSocketMessageKind {
+  Text,
+  Binary,
+  Open,
+  Close,
+  Error,
+}
+
+// One line of a session's log, binary data is kept base64 encoded
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WebSocketMessage {
+  // rfc3339
+  pub timestamp: String,
+  pub direction: WebSocketDirection,
+  pub kind: WebSocketMessageKind,
+  pub data: String,
+}
+
+impl WebSocketMessage {
+  pub fn new(direction: WebSocketDirection, kind: WebSocketMessageKind, data: String) -> Self {
+    WebSocketMessage {
+      timestamp: Utc::now().to_rfc3339(),
+      direction,
+      kind,
+      data,
+    }
+  }
+}
+
+impl std::fmt::Display for WebSocketMessage {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let arrow = match self.direction {
+      WebSocketDirection::Sent => "->",
+      WebSocketDirection::Received => "<-",
+      WebSocketDirection::Event => "--",
+    };
+    match self.kind {
+      WebSocketMessageKind::Binary => {
+        write!(f, "{} {} [binary] {}", self.timestamp, arrow, self.data)
+      }
+      _ => write!(f, "{} {} {}", self.timestamp, arrow, self.data),
+    }
+  }
+}
+
+// Everything that happened on one connection, saved to the history once it is closed
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebSocketTranscript {
+  pub opened_at: DateTime<Utc>,
+  // of the opening handshake
+  pub response_headers: Vec<Header>,
+  pub messages: Vec<WebSocketMessage>,
+}
+
+// What the gui asks of an open session
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebSocketCommand {
+  Send(WebSocketFrame),
+  Close,
+}
@@ -2,127 +2,419 @@ pub mod db;
 pub mod domain;
 pub mod utilities;
 
-use base64::Engine as _;
 use chrono::prelude::*;
 use db::repository;
-use domain::environment::EnvironmentFile;
+use domain::environment::{EnvironmentFile, EnvironmentValue};
 use domain::header::Headers;
 use domain::{
-  collection::{
-    Collection, CollectionItem, CollectionItemOrFolder, CollectionRequest, CollectionRequestHeader,
-    CollectionUrl,
+  auth::{ApiKeyPlacement, AuthSettings, OAuth2Grant, OAuth2Prompt, OAuth2Token, RequestAuth},
+  client::ClientSettings,
+  collection::{Collection, CollectionItem, CollectionItemOrFolder},
+  cookie::Cookie,
+  graphql::{GraphQLSchema, INTROSPECTION_QUERY},
+  grpc::{GrpcMethod, GrpcRequest},
+  request::{
+    BodyType, GraphQLBody, HttpMethod, HttpRequest, MultipartPart, OAuth2Request, PostieRequest,
+    RequestBody, RequestTimeouts,
   },
-  request::{HttpRequest, PostieRequest, RequestBody},
-  response::{Response, ResponseData},
+  response::{Response, ResponseBody, ResponseData, ResponseProgress},
+  run::{RunOptions, RunReport, RunResult},
+  sse::SseEvent,
   tab::Tab,
+  websocket::{WebSocketRequest, WebSocketTranscript},
 };
+use log::{debug, error, warn};
+use prost_reflect::DescriptorPool;
 use reqwest::{
   header::{self, HeaderMap, HeaderName, HeaderValue},
-  Method,
+  multipart, Method,
+};
+use std::{borrow::Borrow, fs, future::Future, time::Duration};
+use tokio::{io::AsyncWriteExt, sync::watch};
+use utilities::{
+  client::ClientProfile,
+  cookies::{cookie_header, merge_cookie_header, parse_set_cookie, store_cookie},
+  sse::SseParser,
+  variables::VariableResolver,
+  websocket::WebSocketConnection,
 };
-use std::{borrow::Borrow, fs};
 use uuid::Uuid;
 
 use crate::domain::collection::CollectionFolder;
 use crate::domain::header::Header;
 use crate::domain::{request::DBRequest, request_item::RequestHistoryItem, response::DBResponse};
 
+const POSTMAN_COLLECTION_SCHEMA: &str =
+  "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+async fn read_body_file(path: &str) -> anyhow::Result<Vec<u8>> {
+  tokio::fs::read(path)
+    .await
+    .map_err(|e| anyhow::anyhow!("Could not read body file {}: {}", path, e))
+}
+
+// Waits on `future` for at most `limit`, when there is one
+async fn within<T>(
+  limit: Option<Duration>,
+  waiting_for: &str,
+  future: impl Future<Output = T>,
+) -> anyhow::Result<T> {
+  match limit {
+    Some(limit) => tokio::time::timeout(limit, future)
+      .await
+      .map_err(|_| anyhow::anyhow!("Timed out after {} ms {}", limit.as_millis(), waiting_for)),
+    None => Ok(future.await),
+  }
+}
+
+async fn read_event_stream(
+  mut res: reqwest::Response,
+  mut stop: Option<watch::Receiver<bool>>,
+  on_event: &mut (dyn FnMut(&SseEvent) + Send),
+) -> anyhow::Result<Vec<SseEvent>> {
+  let mut parser = SseParser::new();
+  let mut events = vec![];
+  loop {
+    let chunk = match stop.as_mut() {
+      Some(stop) => tokio::select! {
+        chunk = res.chunk() => chunk?,
+        // a dropped sender can't stop the stream anymore, the pattern disables this branch
+        Ok(_) = stop.wait_for(|stopped| *stopped) => None,
+      },
+      None => res.chunk().await?,
+    };
+    let new_events = match &chunk {
+      Some(chunk) => parser.push(chunk),
+      None => parser.finish(),
+    };
+    for event in new_events {
+      on_event(&event);
+      events.push(event);
+    }
+    if chunk.is_none() {
+      return Ok(events);
+    }
+  }
+}
+
+// Where bodies that aren't kept in memory are written while they are read
+fn response_file_path() -> anyhow::Result<std::path::PathBuf> {
+  let folder = std::env::temp_dir().join("postie");
+  fs::create_dir_all(&folder)?;
+  Ok(folder.join(format!("{}.body", Uuid::new_v4())))
+}
+
+// Reads a body chunk by chunk, passing on the progress. At most `limit` bytes of a text body are
+// kept in memory, anything larger and every binary body is written to a temporary file as it
+// arrives when `to_file` is set, and only counted otherwise. Returns the kept bytes, which are all
+// of them unless the body is truncated. `read` limits how long to wait for each chunk
+async fn read_body(
+  mut res: reqwest::Response,
+  limit: u64,
+  to_file: bool,
+  read: Option<Duration>,
+  mut stop: Option<watch::Receiver<bool>>,
+  on_progress: &mut (dyn FnMut(ResponseProgress) + Send),
+) -> anyhow::Result<(Vec<u8>, ResponseBody)> {
+  let content_type = res
+    .headers()
+    .get(header::CONTENT_TYPE)
+    .and_then(|t| t.to_str().ok())
+    .unwrap_or_default()
+    .to_string();
+  let mut body = ResponseBody {
+    binary: !utilities::response::is_text_content_type(&content_type),
+    content_type,
+    ..Default::default()
+  };
+  let mut progress = ResponseProgress {
+    received: 0,
+    total: res.content_length(),
+  };
+  on_progress(progress);
+  let mut kept: Vec<u8> = vec![];
+  let mut file: Option<tokio::fs::File> = None;
+  let mut stopped = false;
+  loop {
+    let next = within(
+      read,
+      "waiting for the rest of the response body",
+      res.chunk(),
+    );
+    let chunk = match stop.as_mut() {
+      Some(stop) => tokio::select! {
+        chunk = next => chunk??,
+        // stopping keeps what was read so far
+        Ok(_) = stop.wait_for(|stopped| *stopped) => {
+          stopped = true;
+          None
+        }
+      },
+      None => next.await??,
+    };
+    let Some(chunk) = chunk else {
+      break;
+    };
+    progress.received += chunk.len() as u64;
+    if to_file && file.is_none() && (body.binary || progress.received > limit) {
+      let path = response_file_path()?;
+      let mut created = tokio::fs::File::create(&path).await?;
+      created.write_all(&kept).await?;
+      body.file = Some(path);
+      file = Some(created);
+    }
+    if let Some(file) = file.as_mut() {
+      file.write_all(&chunk).await?;
+    }
+    if !body.binary {
+      let room = (limit as usize).saturating_sub(kept.len());
+      kept.extend_from_slice(&chunk[..room.min(chunk.len())]);
+    }
+    on_progress(progress);
+  }
+  if let Some(mut file) = file {
+    file.flush().await?;
+  }
+  body.size = progress.received;
+  body.truncated = stopped || body.size > kept.len() as u64;
+  // a text type whose body isn't utf-8, a cut off character at the end of a preview is fine
+  if !body.binary {
+    if let Err(e) = std::str::from_utf8(&kept) {
+      if e.error_len().is_some() || !body.truncated {
+        body.binary = true;
+        if to_file && body.file.is_none() {
+          let path = response_file_path()?;
+          tokio::fs::write(&path, &kept).await?;
+          body.file = Some(path);
+        }
+      }
+    }
+  }
+  // a binary body is whole in its file unless it was stopped
+  if body.binary {
+    body.truncated = stopped;
+    kept.clear();
+  }
+  Ok((kept, body))
+}
+
+async fn build_multipart_form(parts: Vec<MultipartPart>) -> anyhow::Result<multipart::Form> {
+  let mut form = multipart::Form::new();
+  for part in parts.into_iter().filter(MultipartPart::is_enabled) {
+    form = match part {
+      MultipartPart::Text { name, value, .. } => form.text(name, value),
+      MultipartPart::File {
+        name,
+        path,
+        filename,
+        content_type,
+        ..
+      } => {
+        let filename = filename.unwrap_or_else(|| {
+          std::path::Path::new(&path)
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default()
+        });
+        let mut file_part =
+          multipart::Part::bytes(read_body_file(&path).await?).file_name(filename);
+        if let Some(content_type) = content_type.filter(|c| !c.is_empty()) {
+          file_part = file_part.mime_str(&content_type)?;
+        }
+        form.part(name, file_part)
+      }
+    };
+  }
+  Ok(form)
+}
+
+// Scope and audience of token requests, when the config has them
+fn oauth2_scope(input: &OAuth2Request) -> Vec<(&'static str, String)> {
+  let mut params = vec![];
+  if !input.request.scope.is_empty() {
+    params.push(("scope", input.request.scope.clone()));
+  }
+  if !input.request.audience.is_empty() {
+    params.push(("audience", input.request.audience.clone()));
+  }
+  params
+}
+
+// Replaces a header of the request, whatever the case of its name, or adds it
+fn set_header(request: &mut HttpRequest, key: &str, value: String) {
+  let headers = request.headers.get_or_insert_with(Vec::new);
+  headers.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+  headers.push((key.to_string(), value));
+}
+
+// Sends the request, answering a digest challenge by sending it again with the response to it
+async fn send_with_auth(
+  client: &reqwest::Client,
+  req: reqwest::RequestBuilder,
+  auth: &RequestAuth,
+) -> reqwest::Result<reqwest::Response> {
+  let RequestAuth::DIGEST { username, password } = auth else {
+    return req.send().await;
+  };
+  let request = req.build()?;
+  // streamed bodies can't be sent twice
+  let Some(mut retry) = request.try_clone() else {
+    return client.execute(request).await;
+  };
+  let res = client.execute(request).await?;
+  if res.status() != reqwest::StatusCode::UNAUTHORIZED {
+    return Ok(res);
+  }
+  let url = retry.url();
+  let uri = match url.query() {
+    Some(query) => format!("{}?{}", url.path(), query),
+    None => url.path().to_string(),
+  };
+  let authorization = res
+    .headers()
+    .get_all(header::WWW_AUTHENTICATE)
+    .iter()
+    .filter_map(|h| h.to_str().ok())
+    .filter_map(utilities::auth::parse_digest_challenge)
+    .find_map(|challenge| {
+      utilities::auth::digest_authorization(
+        &challenge,
+        username,
+        password,
+        retry.method().as_str(),
+        &uri,
+        &Uuid::new_v4().simple().to_string(),
+      )
+    })
+    .and_then(|authorization| HeaderValue::from_str(&authorization).ok());
+  let Some(authorization) = authorization else {
+    return Ok(res);
+  };
+  retry
+    .headers_mut()
+    .insert(header::AUTHORIZATION, authorization);
+  client.execute(retry).await
+}
+
+// How long the authorization code grant waits for the user to sign in
+const OAUTH2_SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Text bodies up to this size are kept whole, larger ones are shown as a preview of this size
+pub const DEFAULT_RESPONSE_PREVIEW_LIMIT: u64 = 5 * 1024 * 1024;
+
 pub struct PostieApi {
   pub client: reqwest::Client,
   pub collection: Option<String>,
   pub environment: Option<String>,
   pub db: repository::PostieDb,
+  // bytes of a response body kept in memory, see read_body
+  pub response_preview_limit: u64,
+  // write bodies that aren't kept in memory to a temporary file, which the caller deletes when
+  // it's done with it. Off for headless callers that never look at the file
+  pub keep_response_files: bool,
+  // what `client` was last built from, see use_client_for
+  pub client_profile: Option<ClientProfile>,
 }
 
 impl PostieApi {
   pub async fn new() -> Self {
+    Self::with_db(repository::PostieDb::new().await)
+  }
+  pub fn with_db(db: repository::PostieDb) -> Self {
     PostieApi {
       client: reqwest::Client::new(),
       collection: None,
       environment: None,
-      db: repository::PostieDb::new().await,
+      db,
+      response_preview_limit: DEFAULT_RESPONSE_PREVIEW_LIMIT,
+      keep_response_files: false,
+      client_profile: None,
     }
   }
   pub fn parse_collection(&mut self, collection_json: &str) -> Collection {
-    println!("Parsing collection from json");
+    debug!("Parsing collection from json");
     serde_json::from_str(collection_json).expect("Failed to parse collection")
   }
   pub fn parse_environment(&mut self, environment_json: &str) -> EnvironmentFile {
-    println!("Parsing environment from json");
+    debug!("Parsing environment from json");
     serde_json::from_str(environment_json).expect("Failed to parse environment")
   }
   pub fn read_file(&mut self, path: &str) -> anyhow::Result<String> {
-    println!("Reading file: {}", path);
+    debug!("Reading file: {}", path);
     Ok(fs::read_to_string(path)?)
   }
   pub async fn import_collection(&mut self, path: &str) -> anyhow::Result<String> {
     let file_str = self.read_file(path).unwrap();
     let collection = self.parse_collection(&file_str);
-    println!("Successfully parsed postman collection!");
+    debug!("Successfully parsed postman collection!");
     match &self.db.save_collection(collection.clone()).await {
       Ok(_) => Ok(String::from("Import successful")),
       Err(_) => {
-        println!("Error saving collection");
+        error!("Error saving collection");
         Ok(String::from("Error saving collection"))
       }
     }
   }
+  // Postman v2.1 json for a collection, readable again by parse_collection
+  pub fn serialize_collection(&self, collection: &Collection) -> anyhow::Result<String> {
+    let mut collection_json = serde_json::to_value(collection)?;
+    collection_json["info"]["schema"] = serde_json::Value::from(POSTMAN_COLLECTION_SCHEMA);
+    Ok(serde_json::to_string_pretty(&collection_json)?)
+  }
+  pub async fn export_collection(&mut self, id: &str, path: &str) -> anyhow::Result<String> {
+    let collection = self
+      .db
+      .get_all_collections()
+      .await?
+      .into_iter()
+      .find(|c| c.info.id == id)
+      .ok_or_else(|| anyhow::anyhow!("no collection found with id {id}"))?;
+    let collection_json = self.serialize_collection(&collection)?;
+    debug!("Writing collection {} to {}", collection.info.name, path);
+    fs::write(path, collection_json)?;
+    Ok(String::from("Export successful"))
+  }
+  // Postman environment json, readable again by parse_environment
+  pub fn serialize_environment(&self, environment: &EnvironmentFile) -> anyhow::Result<String> {
+    let mut environment_json = serde_json::to_value(environment)?;
+    if environment_json["values"].is_null() {
+      environment_json["values"] = serde_json::json!([]);
+    }
+    environment_json["_postman_variable_scope"] = serde_json::Value::from("environment");
+    Ok(serde_json::to_string_pretty(&environment_json)?)
+  }
+  pub async fn export_environment(&mut self, id: &str, path: &str) -> anyhow::Result<String> {
+    let environment = self
+      .db
+      .get_all_environments()
+      .await?
+      .into_iter()
+      .find(|e| e.id == id)
+      .ok_or_else(|| anyhow::anyhow!("no environment found with id {id}"))?;
+    let environment_json = self.serialize_environment(&environment)?;
+    debug!("Writing environment {} to {}", environment.name, path);
+    fs::write(path, environment_json)?;
+    Ok(String::from("Export successful"))
+  }
   pub async fn add_request_to_collection(
     &mut self,
     id: &str,
     req: HttpRequest,
     folder_name: Option<String>,
   ) -> anyhow::Result<()> {
-    println!("finding collection {id} to update");
+    debug!("finding collection {id} to update");
     let collections = self.db.get_all_collections().await?;
     for mut collection in collections {
       if collection.info.id == id {
         if let Some(fol_name) = folder_name.clone() {
-          println!("adding request to {fol_name}");
+          debug!("adding request to {fol_name}");
           for item in &mut collection.item {
             if let CollectionItemOrFolder::Folder(ref mut folder) = item {
               if folder.name == fol_name {
-                println!("found matching folder name, updating collection");
-                let mut res: Vec<CollectionRequestHeader> = vec![];
-                let headers: Vec<CollectionRequestHeader> = req
-                  .headers
-                  .clone()
-                  .map(|headers| {
-                    for h in headers {
-                      res.push(CollectionRequestHeader {
-                        key: h.0,
-                        value: h.1,
-                        r#type: String::from(""),
-                      });
-                    }
-                    res
-                  })
-                  .unwrap_or(vec![]);
-                folder
-                  .item
-                  .push(CollectionItemOrFolder::Item(CollectionItem {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    name: if req.clone().name.is_some() {
-                      req.name.clone().unwrap()
-                    } else {
-                      req.url.clone()
-                    },
-                    request: CollectionRequest {
-                      auth: None,
-                      body: Some(domain::collection::RequestBody {
-                        mode: String::from(""),
-                        raw: None,
-                        options: None,
-                      }),
-                      header: Some(headers),
-                      method: req.method.to_string(),
-                      url: CollectionUrl {
-                        raw: req.clone().url,
-                        path: None,
-                        host: None,
-                      },
-                    },
-                  }));
+                debug!("found matching folder name, updating collection");
+                folder.item.push(CollectionItemOrFolder::Item(
+                  CollectionItem::from_http_request(&req),
+                ));
               }
             }
           }
@@ -131,53 +423,20 @@ impl PostieApi {
             info: collection.info,
             item: updated_items,
             auth: collection.auth,
+            variable: collection.variable,
           };
           self.db.save_collection(updated).await?;
         } else {
-          println!("saving to collection root");
+          debug!("saving to collection root");
           let mut col_items = collection.item.clone();
-          let mut res: Vec<CollectionRequestHeader> = vec![];
-          let headers: Vec<CollectionRequestHeader> = req
-            .headers
-            .clone()
-            .map(|headers| {
-              for h in headers {
-                res.push(CollectionRequestHeader {
-                  key: h.0,
-                  value: h.1,
-                  r#type: String::from(""),
-                });
-              }
-              res
-            })
-            .unwrap_or(vec![]);
-          col_items.push(CollectionItemOrFolder::Item(CollectionItem {
-            id: uuid::Uuid::new_v4().to_string(),
-            name: if req.name.is_some() {
-              req.name.clone().unwrap()
-            } else {
-              req.url.clone()
-            },
-            request: CollectionRequest {
-              method: req.method.to_string(),
-              url: CollectionUrl {
-                raw: req.url.clone(),
-                host: None,
-                path: None,
-              },
-              auth: None,
-              header: Some(headers),
-              body: Some(domain::collection::RequestBody {
-                mode: String::from(""),
-                raw: None,
-                options: None,
-              }),
-            },
-          }));
+          col_items.push(CollectionItemOrFolder::Item(
+            CollectionItem::from_http_request(&req),
+          ));
           let updated = Collection {
             info: collection.info,
             item: col_items,
             auth: collection.auth,
+            variable: collection.variable,
           };
           self.db.save_collection(updated).await?;
         }
@@ -195,7 +454,7 @@ impl PostieApi {
     let collections = self.db.get_all_collections().await?;
     if let Some(mut collection) = collections.into_iter().find(|c| c.info.id == col_id) {
       if let Some(target_folder) = sub_folder {
-        println!("adding new folder to {}", target_folder.name);
+        debug!("adding new folder to {}", target_folder.name);
         match self.add_folder_recursive(&mut collection.item, &target_folder.id, &folder) {
           true => {}
           false => anyhow::bail!("couldnt find matching subfolder to save new folder to"),
@@ -209,11 +468,11 @@ impl PostieApi {
   }
   fn add_folder_recursive(
     &mut self,
-    items: &mut Vec<CollectionItemOrFolder>,
+    items: &mut [CollectionItemOrFolder],
     target_folder_id: &str,
     new_folder: &CollectionFolder,
   ) -> bool {
-    println!("add folder rescursive");
+    debug!("add folder rescursive");
     for item in items.iter_mut() {
       if let CollectionItemOrFolder::Folder(ref mut current_folder) = item {
         if current_folder.id == target_folder_id {
@@ -235,11 +494,11 @@ impl PostieApi {
   pub async fn import_environment(&mut self, path: &str) -> anyhow::Result<String> {
     let file_str = self.read_file(path)?;
     let environment = self.parse_environment(&file_str);
-    println!("Successfully parsed postman environment!");
+    debug!("Successfully parsed postman environment!");
     match self.db.save_environment(environment).await {
       Ok(_) => Ok(String::from("Import successful")),
       Err(_) => {
-        println!("Error saving enviornment");
+        error!("Error saving enviornment");
         Ok(String::from("Error with importing"))
       }
     }
@@ -248,7 +507,7 @@ impl PostieApi {
     match self.db.save_environment(input).await {
       Ok(_) => Ok(()),
       Err(_) => {
-        println!("Error saving environment");
+        error!("Error saving environment");
         Ok(())
       }
     }
@@ -260,7 +519,7 @@ impl PostieApi {
     match self.db.save_collection(input).await {
       Ok(_) => Ok(()),
       Err(_) => {
-        println!("Error saving collection");
+        error!("Error saving collection");
         Ok(())
       }
     }
@@ -273,6 +532,353 @@ impl PostieApi {
     let collections = self.db.get_all_collections().await?;
     Ok(collections)
   }
+  pub async fn load_globals(&self) -> anyhow::Result<Vec<EnvironmentValue>> {
+    let globals = self.db.get_all_globals().await?;
+    Ok(globals)
+  }
+  pub async fn save_globals(&mut self, globals: &[EnvironmentValue]) -> anyhow::Result<()> {
+    self.db.save_globals(globals).await?;
+    Ok(())
+  }
+  // Timeouts used for requests that don't set their own
+  pub async fn load_default_timeouts(&self) -> anyhow::Result<RequestTimeouts> {
+    match self.db.get_setting("timeouts").await? {
+      Some(timeouts) => Ok(serde_json::from_str(&timeouts)?),
+      None => Ok(RequestTimeouts::DEFAULTS),
+    }
+  }
+  pub async fn save_default_timeouts(&mut self, timeouts: &RequestTimeouts) -> anyhow::Result<()> {
+    let timeouts = serde_json::to_string(timeouts)?;
+    self.db.save_setting("timeouts", &timeouts).await
+  }
+  pub async fn load_client_settings(&self) -> anyhow::Result<ClientSettings> {
+    match self.db.get_setting("client").await? {
+      Some(settings) => Ok(serde_json::from_str(&settings)?),
+      None => Ok(ClientSettings::default()),
+    }
+  }
+  pub async fn save_client_settings(&mut self, settings: &ClientSettings) -> anyhow::Result<()> {
+    let settings = serde_json::to_string(settings)?;
+    self.db.save_setting("client", &settings).await
+  }
+  // Each saved environment has its own cookies, requests sent without one share the workspace's
+  async fn cookie_scope(&self, environment: &EnvironmentFile) -> anyhow::Result<String> {
+    let saved = self.db.get_all_environments().await?;
+    Ok(match saved.iter().any(|e| e.id == environment.id) {
+      true => environment.id.clone(),
+      false => "".into(),
+    })
+  }
+  pub async fn load_cookies(&self, environment: &EnvironmentFile) -> anyhow::Result<Vec<Cookie>> {
+    let scope = self.cookie_scope(environment).await?;
+    let now = Utc::now();
+    let mut cookies = self.db.get_cookies(&scope).await?;
+    cookies.retain(|c| !c.is_expired(now));
+    Ok(cookies)
+  }
+  // Replaces the environment's cookies, clearing them is saving none
+  pub async fn save_cookies(
+    &mut self,
+    environment: &EnvironmentFile,
+    cookies: &[Cookie],
+  ) -> anyhow::Result<()> {
+    let scope = self.cookie_scope(environment).await?;
+    self.db.save_cookies(&scope, cookies).await
+  }
+  // Adds what the request's auth sends to the resolved request. Digest and AWS auth are added
+  // while sending, see sign_request and send_with_auth
+  async fn apply_auth(&mut self, resolved: &mut HttpRequest) -> anyhow::Result<()> {
+    match resolved.auth.clone() {
+      RequestAuth::APIKEY {
+        key,
+        value,
+        placement: ApiKeyPlacement::QUERY,
+      } => {
+        let mut url = reqwest::Url::parse(&resolved.url)
+          .map_err(|e| anyhow::anyhow!("{} is not a valid url: {}", resolved.url, e))?;
+        url.query_pairs_mut().append_pair(&key, &value);
+        resolved.url = url.to_string();
+      }
+      RequestAuth::JWT(config) => {
+        let token = utilities::auth::jwt_token(&config)?;
+        let value = format!("{} {}", config.header_prefix.trim(), token);
+        set_header(resolved, "Authorization", value.trim_start().to_string());
+      }
+      RequestAuth::OAUTH2(config) => {
+        let config = OAuth2Request {
+          environment: resolved.environment.clone(),
+          ..config
+        };
+        let token = self.oauth2_token_for_sending(&config).await?;
+        set_header(resolved, "Authorization", token.header_value());
+      }
+      auth => {
+        if let Some((key, value)) = auth.header() {
+          set_header(resolved, &key, value);
+        }
+      }
+    }
+    Ok(())
+  }
+  // Signs the built request for AWS, after which nothing about it may change
+  fn sign_request(
+    &self,
+    req: reqwest::RequestBuilder,
+    auth: &RequestAuth,
+  ) -> anyhow::Result<reqwest::RequestBuilder> {
+    let RequestAuth::AWSSIGV4(config) = auth else {
+      return Ok(req);
+    };
+    let mut request = req.build()?;
+    let headers: Vec<(String, String)> = request
+      .headers()
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
+      .collect();
+    let payload = match request.body() {
+      Some(body) => body.as_bytes(),
+      None => Some(&[][..]),
+    };
+    let signed = utilities::auth::aws_sigv4_headers(
+      config,
+      request.method().as_str(),
+      request.url(),
+      &headers,
+      payload,
+      Utc::now(),
+    );
+    for (key, value) in signed {
+      request.headers_mut().insert(
+        HeaderName::from_bytes(key.as_bytes())?,
+        HeaderValue::from_str(&value)?,
+      );
+    }
+    Ok(reqwest::RequestBuilder::from_parts(
+      self.client.clone(),
+      request,
+    ))
+  }
+  // Saved tokens are found by what they were given for, so each environment's client has its own
+  fn oauth2_token_key(input: &OAuth2Request) -> String {
+    format!(
+      "{} {} {}",
+      input.access_token_url, input.client_id, input.request.scope
+    )
+  }
+  async fn resolve_oauth2_request(&self, input: &OAuth2Request) -> anyhow::Result<OAuth2Request> {
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver = VariableResolver::with_globals(&globals, &input.environment);
+    let input = resolver.resolve_oauth2_request(input);
+    resolver.check_unresolved()?;
+    Ok(input)
+  }
+  // The saved token for the config, None before one was requested
+  pub async fn load_oauth2_token(
+    &self,
+    input: &OAuth2Request,
+  ) -> anyhow::Result<Option<OAuth2Token>> {
+    let input = self.resolve_oauth2_request(input).await?;
+    self
+      .db
+      .get_oauth_token(&Self::oauth2_token_key(&input))
+      .await
+  }
+  // Gets a new token with the config's grant and saves it. Interactive grants tell `on_prompt`
+  // what the user has to do and wait for them
+  pub async fn request_oauth2_token(
+    &mut self,
+    input: &OAuth2Request,
+    mut on_prompt: impl FnMut(OAuth2Prompt) + Send,
+  ) -> anyhow::Result<OAuth2Token> {
+    debug!("requesting an oauth 2 token with the {} grant", input.grant);
+    let input = self.resolve_oauth2_request(input).await?;
+    let mut form = vec![("grant_type", input.grant.grant_type().to_string())];
+    let token = match input.grant {
+      OAuth2Grant::CLIENTCREDENTIALS => {
+        form.extend(oauth2_scope(&input));
+        self
+          .post_oauth2_token_form(&input, &input.access_token_url, form)
+          .await?
+      }
+      OAuth2Grant::PASSWORD => {
+        form.push(("username", input.username.clone()));
+        form.push(("password", input.password.clone()));
+        form.extend(oauth2_scope(&input));
+        self
+          .post_oauth2_token_form(&input, &input.access_token_url, form)
+          .await?
+      }
+      OAuth2Grant::AUTHORIZATIONCODE => {
+        let (listener, redirect_uri) =
+          utilities::oauth2::bind_redirect_listener(&input.redirect_uri).await?;
+        let (verifier, challenge) = utilities::oauth2::pkce_pair();
+        let state = Uuid::new_v4().simple().to_string();
+        on_prompt(OAuth2Prompt::OpenBrowser(utilities::oauth2::authorize_url(
+          &input,
+          &redirect_uri,
+          &state,
+          &challenge,
+        )?));
+        let code = within(
+          Some(OAUTH2_SIGN_IN_TIMEOUT),
+          "waiting for the browser to sign in",
+          utilities::oauth2::wait_for_code(&listener, &redirect_uri, &state),
+        )
+        .await??;
+        form.push(("code", code));
+        form.push(("redirect_uri", redirect_uri));
+        form.push(("code_verifier", verifier));
+        self
+          .post_oauth2_token_form(&input, &input.access_token_url, form)
+          .await?
+      }
+      OAuth2Grant::DEVICECODE => self.poll_device_token(&input, &mut on_prompt).await?,
+    };
+    self
+      .db
+      .save_oauth_token(&Self::oauth2_token_key(&input), &token)
+      .await?;
+    Ok(token)
+  }
+  // Trades the saved refresh token for a new token. The refresh token is kept when the server
+  // doesn't send a new one
+  pub async fn refresh_oauth2_token(
+    &mut self,
+    input: &OAuth2Request,
+  ) -> anyhow::Result<OAuth2Token> {
+    let input = self.resolve_oauth2_request(input).await?;
+    let key = Self::oauth2_token_key(&input);
+    let refresh_token = self
+      .db
+      .get_oauth_token(&key)
+      .await?
+      .and_then(|token| token.refresh_token)
+      .ok_or_else(|| anyhow::anyhow!("There is no refresh token, get a new token instead"))?;
+    let url = if input.refresh_url.is_empty() {
+      input.access_token_url.clone()
+    } else {
+      input.refresh_url.clone()
+    };
+    let mut form = vec![
+      ("grant_type", "refresh_token".to_string()),
+      ("refresh_token", refresh_token.clone()),
+    ];
+    form.extend(oauth2_scope(&input));
+    let mut token = self.post_oauth2_token_form(&input, &url, form).await?;
+    token.refresh_token.get_or_insert(refresh_token);
+    self.db.save_oauth_token(&key, &token).await?;
+    Ok(token)
+  }
+  // The token to send a request with, refreshed when it's about to expire. Grants that don't
+  // need the user get a new one when there is none
+  async fn oauth2_token_for_sending(
+    &mut self,
+    input: &OAuth2Request,
+  ) -> anyhow::Result<OAuth2Token> {
+    let token = self.load_oauth2_token(input).await?;
+    if let Some(token) = token.as_ref().filter(|t| !t.needs_refresh(Utc::now())) {
+      return Ok(token.clone());
+    }
+    let refreshed = match token.as_ref().and_then(|t| t.refresh_token.as_ref()) {
+      Some(_) => Some(self.refresh_oauth2_token(input).await),
+      None => None,
+    };
+    match refreshed {
+      Some(Ok(token)) => Ok(token),
+      _ if !input.grant.is_interactive() => self.request_oauth2_token(input, |_| {}).await,
+      Some(Err(e)) => Err(e),
+      None if token.is_some() => {
+        anyhow::bail!("The OAuth 2.0 token expired, get a new one from the Auth tab")
+      }
+      None => anyhow::bail!("There is no OAuth 2.0 token yet, get one from the Auth tab"),
+    }
+  }
+  async fn post_oauth2_form(
+    &mut self,
+    input: &OAuth2Request,
+    url: &str,
+    form: Vec<(&'static str, String)>,
+  ) -> anyhow::Result<(reqwest::StatusCode, String)> {
+    let timeouts = self.load_default_timeouts().await?;
+    self.use_client_for(url, timeouts.connect()).await?;
+    let req = utilities::oauth2::token_request(self.client.post(url), input, form);
+    let res = within(
+      timeouts.read(),
+      "waiting for the token endpoint",
+      req.send(),
+    )
+    .await??;
+    let status = res.status();
+    Ok((status, res.text().await?))
+  }
+  async fn post_oauth2_token_form(
+    &mut self,
+    input: &OAuth2Request,
+    url: &str,
+    form: Vec<(&'static str, String)>,
+  ) -> anyhow::Result<OAuth2Token> {
+    let (status, text) = self.post_oauth2_form(input, url, form).await?;
+    utilities::oauth2::parse_token_response(status, &text, Utc::now())
+  }
+  // Asks for a device code, shows the user where to enter it, then polls the token endpoint
+  // until they did
+  async fn poll_device_token(
+    &mut self,
+    input: &OAuth2Request,
+    on_prompt: &mut (dyn FnMut(OAuth2Prompt) + Send),
+  ) -> anyhow::Result<OAuth2Token> {
+    let (status, text) = self
+      .post_oauth2_form(input, &input.device_authorization_url, oauth2_scope(input))
+      .await?;
+    if !status.is_success() {
+      return Err(utilities::oauth2::token_error(status, &text));
+    }
+    let device: utilities::oauth2::DeviceAuthorization = serde_json::from_str(&text)
+      .map_err(|e| anyhow::anyhow!("The device authorization response is not valid: {}", e))?;
+    on_prompt(OAuth2Prompt::EnterCode {
+      user_code: device.user_code.clone(),
+      verification_uri: device.verification_uri.clone(),
+      verification_uri_complete: device.verification_uri_complete.clone(),
+    });
+    let mut interval = device.interval.unwrap_or(5);
+    let expires_in = device.expires_in.unwrap_or(600).max(0) as u64;
+    let expires_at = std::time::Instant::now() + Duration::from_secs(expires_in);
+    loop {
+      tokio::time::sleep(Duration::from_secs(interval)).await;
+      if std::time::Instant::now() > expires_at {
+        anyhow::bail!("The device code expired before it was entered");
+      }
+      let form = vec![
+        ("grant_type", input.grant.grant_type().to_string()),
+        ("device_code", device.device_code.clone()),
+      ];
+      let (status, text) = self
+        .post_oauth2_form(input, &input.access_token_url, form)
+        .await?;
+      if !status.is_success() {
+        match utilities::oauth2::error_code(&text).as_deref() {
+          Some("authorization_pending") => continue,
+          Some("slow_down") => {
+            interval += 5;
+            continue;
+          }
+          _ => {}
+        }
+      }
+      return utilities::oauth2::parse_token_response(status, &text, Utc::now());
+    }
+  }
+  // Makes `client` the one for sending to `url`. reqwest sets timeouts, proxies and certificates
+  // on a whole client, so it is rebuilt whenever they change for the request being sent
+  async fn use_client_for(&mut self, url: &str, connect: Option<Duration>) -> anyhow::Result<()> {
+    let settings = self.load_client_settings().await?;
+    let profile = ClientProfile::for_url(&settings, url, connect);
+    if self.client_profile.as_ref() != Some(&profile) {
+      self.client = profile.build()?;
+      self.client_profile = Some(profile);
+    }
+    Ok(())
+  }
   pub async fn load_tabs(&mut self) -> anyhow::Result<Vec<Tab>> {
     let tabs = self.db.get_all_tabs().await?;
     Ok(tabs)
@@ -299,12 +905,12 @@ impl PostieApi {
   ) -> anyhow::Result<()> {
     let collections = self.db.get_all_collections().await?;
     if let Some(mut collection) = collections.into_iter().find(|c| c.info.id == id) {
-      println!("removing folder {} from colleciton {}", folder_id, id);
+      debug!("removing folder {} from colleciton {}", folder_id, id);
       let found = self.delete_folder_recursive(&mut collection.item, &folder_id);
       if found {
         self.db.save_collection(collection).await?;
       } else {
-        println!("no matching folder found");
+        warn!("no matching folder found");
       }
     }
     Ok(())
@@ -349,16 +955,10 @@ impl PostieApi {
     let collections = self.db.get_all_collections().await?;
     for mut col in collections {
       if col.info.id == id {
-        println!("matching collection found, looking for request to remove");
+        debug!("matching collection found, looking for request to remove");
 
         col.item.retain(|item| match item {
-          CollectionItemOrFolder::Item(collection_item) => {
-            if collection_item.id == request_id {
-              false
-            } else {
-              true
-            }
-          }
+          CollectionItemOrFolder::Item(collection_item) => collection_item.id != request_id,
           CollectionItemOrFolder::Folder(_) => true,
         });
 
@@ -377,7 +977,7 @@ impl PostieApi {
     let collections = self.db.get_all_collections().await?;
     for mut col in collections {
       if col.info.id == id {
-        println!("matching collection found, looking for request to remove");
+        debug!("matching collection found, looking for request to remove");
         let mut collection_items: Vec<CollectionItemOrFolder> = vec![];
         for (index, item) in &mut col.item.iter().enumerate() {
           match item.clone() {
@@ -402,152 +1002,655 @@ impl PostieApi {
     }
     Ok(())
   }
-  pub fn substitute_variables_in_url(
+  pub fn substitute_variables_in_url(environment: &EnvironmentFile, raw_url: String) -> String {
+    debug!("substituting env vars");
+    let url = VariableResolver::new(environment).resolve(&raw_url);
+    debug!("final url: {}", url);
+    url
+  }
+  // Builds what goes over the wire from a request whose variables are already resolved
+  async fn build_request(
+    &self,
+    method: Method,
+    resolved: &HttpRequest,
+  ) -> anyhow::Result<reqwest::RequestBuilder> {
+    let mut headers = HeaderMap::new();
+    if let Some(h) = resolved.headers.clone() {
+      for (key, value) in h {
+        let header_name = HeaderName::from_bytes(key.as_bytes())?;
+        let header_value = HeaderValue::from_str(&value)?;
+        headers.insert(header_name, header_value);
+      }
+    };
+
+    let mut req = self
+      .client
+      .request(method, resolved.url.clone())
+      .headers(headers.clone());
+    if let Some(ref request_body) = resolved.body {
+      let is_multipart = request_body.body_type() == BodyType::MULTIPART;
+      if !is_multipart && !headers.contains_key(header::CONTENT_TYPE) {
+        req = req.header(
+          header::CONTENT_TYPE,
+          request_body.body_type().content_type(),
+        );
+      }
+      req = match request_body.clone() {
+        RequestBody::JSON(j) => req.json(&j),
+        RequestBody::FORM(fields) => {
+          let pairs: Vec<(String, String)> = fields
+            .into_iter()
+            .filter(|f| f.enabled)
+            .map(|f| (f.key, f.value))
+            .collect();
+          req.form(&pairs)
+        }
+        RequestBody::GRAPHQL(graphql) => req.json(
+          &graphql
+            .payload()
+            .map_err(|e| anyhow::anyhow!("GraphQL variables are not valid json: {}", e))?,
+        ),
+        RequestBody::BINARY(path) => req.body(read_body_file(&path).await?),
+        RequestBody::MULTIPART(parts) => req.multipart(build_multipart_form(parts).await?),
+        // everything else is sent as written
+        other => req.body(other.to_text()),
+      };
+    }
+    Ok(req)
+  }
+  // Sends an http request and saves it to the request history, without touching any tabs.
+  // Used directly by headless callers like the cli, and by make_request for the gui.
+  pub async fn send_http_request(&mut self, input: HttpRequest) -> anyhow::Result<Response> {
+    self
+      .send_http_request_streaming(input, None, |_| {}, |_| {})
+      .await
+  }
+  // Same as send_http_request, but text/event-stream bodies are read as they arrive and every
+  // event is passed to `on_event`. Setting `stop` to true ends the stream early. Either way the
+  // events received become the response, as a json list. Other bodies are downloaded with their
+  // progress passed to `on_progress`, stopping keeps what was read as a truncated preview.
+  // Stopping before the response arrives cancels the request
+  pub async fn send_http_request_streaming(
     &mut self,
-    environment: &EnvironmentFile,
-    raw_url: String,
-  ) -> String {
-    println!("substituting env vars");
-    if let Some(values) = environment.clone().values {
-      let url = values.iter().fold(raw_url, |acc, env_value| {
-        acc.replace(&format!("{{{{{}}}}}", env_value.key), &env_value.value)
-      });
-      println!("final url: {}", url);
-      url
+    mut input: HttpRequest,
+    stop: Option<watch::Receiver<bool>>,
+    mut on_event: impl FnMut(&SseEvent) + Send,
+    mut on_progress: impl FnMut(ResponseProgress) + Send,
+  ) -> anyhow::Result<Response> {
+    debug!("Submitting http request: {:?}", input);
+    let original_environment = input.environment.clone();
+    if !input.scripts.pre_request.trim().is_empty() {
+      let script = input.scripts.pre_request.clone();
+      utilities::scripting::run_pre_request_script(&script, &mut input)?;
+    }
+    let method = reqwest::Method::from(input.method.clone());
+
+    // history keeps the request as written, only what goes over the wire is substituted
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver =
+      VariableResolver::with_globals(&globals, &input.environment).with_scope(&input.variables);
+    let mut resolved = resolver.resolve_http_request(&input);
+    resolver.check_unresolved()?;
+    self.apply_auth(&mut resolved).await?;
+
+    let cookie_scope = self.cookie_scope(&input.environment).await?;
+    let mut cookies = self.db.get_cookies(&cookie_scope).await?;
+    let url = reqwest::Url::parse(&resolved.url).ok();
+    if let Some(jar) = url
+      .as_ref()
+      .and_then(|url| cookie_header(&cookies, url, Utc::now()))
+    {
+      let headers = resolved.headers.get_or_insert_with(Vec::new);
+      match headers
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case("cookie"))
+      {
+        Some((_, value)) => *value = merge_cookie_header(value, &jar),
+        None => headers.push(("Cookie".into(), jar)),
+      }
+    }
+
+    let timeouts = input.timeouts.or(&self.load_default_timeouts().await?);
+    self
+      .use_client_for(&resolved.url, timeouts.connect())
+      .await?;
+    let req = self.build_request(method, &resolved).await?;
+    let req = self.sign_request(req, &resolved.auth)?;
+    let client = self.client.clone();
+
+    let now: DateTime<Utc> = Utc::now();
+    let sent_at = std::time::Instant::now();
+    let preview_limit = self.response_preview_limit;
+    let to_file = self.keep_response_files;
+    let exchange = async {
+      let mut stop = stop;
+      let send = within(
+        timeouts.read(),
+        "waiting for a response",
+        send_with_auth(&client, req, &resolved.auth),
+      );
+      let res = match stop.as_mut() {
+        Some(stop) => tokio::select! {
+          res = send => res?,
+          // nothing was received yet, so there is no response to keep
+          Ok(_) = stop.wait_for(|stopped| *stopped) => anyhow::bail!("Request was cancelled"),
+        },
+        None => send.await?,
+      }
+      .map_err(|e| match timeouts.connect_ms {
+        Some(connect) if e.is_connect() && e.is_timeout() => {
+          anyhow::anyhow!(
+            "Timed out after {} ms connecting to {}",
+            connect,
+            resolved.url
+          )
+        }
+        _ => anyhow::Error::new(e),
+      })?;
+      let final_url = res.url().clone();
+      let res_headers = res.headers().clone();
+      let res_status = res.status();
+      let res_type = res_headers
+        .get("content-type")
+        .and_then(|t| t.to_str().ok())
+        .unwrap_or_default();
+      // streams can be quiet for a while, only the total limit and stopping end them
+      if res_type.starts_with("text/event-stream") {
+        let events = read_event_stream(res, stop, &mut on_event).await?;
+        let text = serde_json::to_string_pretty(&events)?;
+        let body = ResponseBody {
+          size: text.len() as u64,
+          content_type: res_type.to_string(),
+          ..Default::default()
+        };
+        Ok((final_url, res_headers, res_status, text, body, true))
+      } else {
+        let (kept, body) = read_body(
+          res,
+          preview_limit,
+          to_file,
+          timeouts.read(),
+          stop,
+          &mut on_progress,
+        )
+        .await?;
+        let text = String::from_utf8_lossy(&kept).into_owned();
+        Ok((final_url, res_headers, res_status, text, body, false))
+      }
+    };
+    let (final_url, res_headers, res_status, res_text, res_body, is_event_stream) =
+      within(timeouts.total(), "in total", exchange).await??;
+    let res_type = if is_event_stream {
+      "application/json"
+    } else {
+      res_headers
+        .get("content-type")
+        .and_then(|t| t.to_str().ok())
+        .unwrap_or_default()
+    };
+    let response_time = sent_at.elapsed().as_millis();
+
+    // only the final response's cookies are seen, reqwest follows redirects without showing the
+    // responses along the way. They belong to wherever the redirects ended up
+    let mut set_cookies = vec![];
+    for header in res_headers.get_all(header::SET_COOKIE) {
+      if let Some(cookie) = header
+        .to_str()
+        .ok()
+        .and_then(|header| parse_set_cookie(header, &final_url, Utc::now()))
+      {
+        store_cookie(&mut cookies, cookie.clone(), Utc::now());
+        set_cookies.push(cookie);
+      }
+    }
+    if !set_cookies.is_empty() {
+      self.db.save_cookies(&cookie_scope, &cookies).await?;
+    }
+
+    let request_headers = input
+      .headers
+      .clone()
+      .unwrap_or_default()
+      .into_iter()
+      .map(|(key, value)| Header { key, value })
+      .collect();
+    let body = input.body.as_ref().map(RequestBody::to_text);
+    let db_request = DBRequest {
+      id: input.id.to_string(),
+      body,
+      name: input.name.clone(),
+      method: input.method.to_string(),
+      url: input.url.clone(),
+      headers: request_headers,
+    };
+    self.db.save_request_history(&db_request).await?;
+    let response_headers: Vec<Header> = res_headers
+      .borrow()
+      .into_iter()
+      .map(|(key, value)| Header {
+        key: String::from(HeaderName::as_str(key)),
+        value: String::from(value.to_str().unwrap_or_default()),
+      })
+      .collect();
+    let db_response = DBResponse {
+      id: Uuid::new_v4().to_string(),
+      status_code: res_status.as_u16(),
+      name: input.name.clone(),
+      headers: response_headers,
+      // the history keeps the preview of a large body, and nothing of a binary one
+      body: (!res_body.binary).then(|| res_text.clone()),
+    };
+    self.db.save_response(&db_response).await?;
+    let mut response = if res_body.binary || res_body.truncated {
+      utilities::response::build_preview(res_status, res_body, res_text.clone(), response_time)
     } else {
-      println!("env doesnt have values, returning original");
-      raw_url
+      let mut response =
+        utilities::response::build_response(res_type, res_status, res_text.clone(), response_time)?;
+      response.body = res_body;
+      response
+    };
+    response.headers = db_response.headers.clone();
+    response.cookies = set_cookies;
+    if !input.scripts.test.trim().is_empty() {
+      response.test_results = utilities::scripting::run_test_script(
+        &input.scripts.test,
+        &response,
+        &mut input.environment,
+      );
+    }
+    response
+      .test_results
+      .extend(utilities::assertions::evaluate_assertions(
+        &input.assertions,
+        &response,
+        &res_text,
+      ));
+    if res_status.is_success() && !input.extract.is_empty() {
+      let failures = utilities::extract::apply_extract_rules(
+        &input.extract,
+        &response,
+        &res_text,
+        &mut input.environment,
+      );
+      response.test_results.extend(failures);
+    }
+    if input.environment != original_environment {
+      // only environments the user saved are written back, not the default used when none
+      // is selected
+      let saved = self.db.get_all_environments().await?;
+      if saved.iter().any(|e| e.id == input.environment.id) {
+        self.db.save_environment(input.environment.clone()).await?;
+      }
+      response.environment = Some(input.environment.clone());
     }
+    self
+      .db
+      .save_request_response_item(
+        &db_request,
+        &db_response,
+        &now,
+        &response_time,
+        &response.test_results,
+      )
+      .await?;
+    Ok(response)
   }
-  pub async fn make_request(&mut self, input: PostieRequest) -> anyhow::Result<Response> {
-    match input {
-      // request and save http request
-      PostieRequest::HTTP(input) => {
-        println!("Submitting http request: {:?}", input);
-        let method = reqwest::Method::from(input.method.clone());
-
-        let mut headers = HeaderMap::new();
-        if let Some(h) = input.headers.clone() {
-          for (key, value) in h {
-            let header_name = HeaderName::from_bytes(key.as_bytes()).unwrap();
-            let header_value = HeaderValue::from_str(&value).unwrap();
-            headers.insert(header_name, header_value);
-          }
-        };
+  // Asks the request's endpoint for its schema, with the request's url and headers. Nothing is
+  // saved to the history
+  pub async fn introspect_graphql_schema(
+    &mut self,
+    mut input: HttpRequest,
+  ) -> anyhow::Result<GraphQLSchema> {
+    input.body = Some(RequestBody::GRAPHQL(GraphQLBody {
+      query: INTROSPECTION_QUERY.into(),
+      ..Default::default()
+    }));
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver =
+      VariableResolver::with_globals(&globals, &input.environment).with_scope(&input.variables);
+    let mut resolved = resolver.resolve_http_request(&input);
+    resolver.check_unresolved()?;
+    self.apply_auth(&mut resolved).await?;
+    let timeouts = self.load_default_timeouts().await?;
+    self
+      .use_client_for(&resolved.url, timeouts.connect())
+      .await?;
+    let req = self.build_request(Method::POST, &resolved).await?;
+    let req = self.sign_request(req, &resolved.auth)?;
+    let res = send_with_auth(&self.client, req, &resolved.auth).await?;
+    let status = res.status();
+    if !status.is_success() {
+      anyhow::bail!("Introspection failed with status {}", status);
+    }
+    let json: serde_json::Value = res.json().await?;
+    GraphQLSchema::from_introspection(&json).map_err(|e| anyhow::anyhow!(e))
+  }
+  // Opens a websocket with the request's headers, variables are substituted like they are for
+  // http requests. The connection doesn't hold on to the api, see save_websocket_session
+  pub async fn connect_websocket(
+    &self,
+    input: &WebSocketRequest,
+  ) -> anyhow::Result<WebSocketConnection> {
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver =
+      VariableResolver::with_globals(&globals, &input.environment).with_scope(&input.variables);
+    let url = resolver.resolve(&input.url);
+    let headers: Vec<(String, String)> = input
+      .headers
+      .iter()
+      .flatten()
+      .map(|(key, value)| (resolver.resolve(key), resolver.resolve(value)))
+      .collect();
+    resolver.check_unresolved()?;
+    utilities::websocket::connect(&url, &headers).await
+  }
+  // Saves a finished websocket session to the history, the transcript is kept as the body of
+  // the response
+  pub async fn save_websocket_session(
+    &mut self,
+    input: &WebSocketRequest,
+    transcript: &WebSocketTranscript,
+  ) -> anyhow::Result<()> {
+    let db_request = DBRequest {
+      id: input.id.to_string(),
+      body: None,
+      name: input.name.clone(),
+      // the opening handshake is a GET
+      method: HttpMethod::GET.to_string(),
+      url: input.url.clone(),
+      headers: input
+        .headers
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| Header { key, value })
+        .collect(),
+    };
+    self.db.save_request_history(&db_request).await?;
+    let db_response = DBResponse {
+      id: Uuid::new_v4().to_string(),
+      status_code: 101,
+      name: input.name.clone(),
+      headers: transcript.response_headers.clone(),
+      body: Some(serde_json::to_string_pretty(&transcript.messages)?),
+    };
+    self.db.save_response(&db_response).await?;
+    let duration = (Utc::now() - transcript.opened_at)
+      .num_milliseconds()
+      .max(0) as u128;
+    self
+      .db
+      .save_request_response_item(
+        &db_request,
+        &db_response,
+        &transcript.opened_at,
+        &duration,
+        &[],
+      )
+      .await?;
+    Ok(())
+  }
+  // Lists the methods a grpc tab can call, from its proto files or, when it has none, the
+  // server's reflection service
+  pub async fn load_grpc_methods(&self, input: &GrpcRequest) -> anyhow::Result<Vec<GrpcMethod>> {
+    let pool = self.grpc_descriptors(input).await?;
+    Ok(utilities::grpc::list_methods(&pool))
+  }
+  async fn grpc_descriptors(&self, input: &GrpcRequest) -> anyhow::Result<DescriptorPool> {
+    if !input.proto_files.is_empty() {
+      return utilities::grpc::load_proto_files(&input.proto_files);
+    }
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver =
+      VariableResolver::with_globals(&globals, &input.environment).with_scope(&input.variables);
+    let url = resolver.resolve(&input.url);
+    resolver.check_unresolved()?;
+    let (endpoint, _) = utilities::grpc::parse_url(&url)?;
+    let channel = utilities::grpc::connect(&endpoint).await?;
+    utilities::grpc::reflect_descriptors(channel).await
+  }
+  // Calls the method the request's url points to and saves it to the history. The grpc status
+  // is kept in the response headers, the message or stream of messages is the body
+  pub async fn send_grpc_request(&mut self, input: GrpcRequest) -> anyhow::Result<Response> {
+    debug!("Submitting grpc request: {:?}", input);
+    let pool = self.grpc_descriptors(&input).await?;
+    let globals = self.db.get_all_globals().await?;
+    let mut resolver =
+      VariableResolver::with_globals(&globals, &input.environment).with_scope(&input.variables);
+    let url = resolver.resolve(&input.url);
+    let message = resolver.resolve(&input.message);
+    let metadata: Vec<(String, String)> = input
+      .headers
+      .iter()
+      .flatten()
+      .map(|(key, value)| (resolver.resolve(key), resolver.resolve(value)))
+      .collect();
+    resolver.check_unresolved()?;
+    let (endpoint, path) = utilities::grpc::parse_url(&url)?;
+    let method = utilities::grpc::find_method(&pool, &path)?;
 
-        let url = self.substitute_variables_in_url(&input.environment.clone(), input.url.clone());
-        let mut req = self.client.request(method, url).headers(headers.clone());
-        if let Some(ref request_body) = input.body {
-          req = match request_body.clone() {
-            RequestBody::JSON(j) => req.json(&j.clone()),
-            RequestBody::FORM(f) => req.form(&f.clone()),
-          };
-        }
+    let now: DateTime<Utc> = Utc::now();
+    let sent_at = std::time::Instant::now();
+    let channel = utilities::grpc::connect(&endpoint).await?;
+    let res = utilities::grpc::call(channel, &method, &message, &metadata).await?;
+    let response_time = sent_at.elapsed().as_millis();
 
-        let now: DateTime<Utc> = Utc::now();
-        let sent_at = std::time::Instant::now();
-        let res = req.send().await?;
-        let response_time = sent_at.elapsed().as_millis();
-        let res_headers = res.headers().clone();
-        let res_status = res.status();
-        let res_type = res_headers.get("content-type").unwrap().to_str().unwrap();
-        let res_text = res.text().await?;
-
-        let request_headers = input
-          .headers
-          .clone()
-          .unwrap()
-          .into_iter()
-          .map(|(key, value)| Header { key, value })
-          .collect();
-        let body = if let Some(req_body) = input.body {
-          match req_body {
-            RequestBody::JSON(j) => Some(j.to_string()),
-            RequestBody::FORM(f) => Some(f),
-          }
-        } else {
-          None
-        };
-        let db_request = DBRequest {
-          id: input.id.to_string(),
-          body,
-          name: input.name.clone(),
+    let db_request = DBRequest {
+      id: input.id.to_string(),
+      body: Some(input.message.clone()),
+      name: input.name.clone(),
+      // every grpc call is a POST to /Service/Method
+      method: HttpMethod::POST.to_string(),
+      url: input.url.clone(),
+      headers: input
+        .headers
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| Header { key, value })
+        .collect(),
+    };
+    self.db.save_request_history(&db_request).await?;
+    let mut headers = res.metadata.clone();
+    headers.push(Header {
+      key: "grpc-status".into(),
+      value: res.code.to_string(),
+    });
+    if !res.status_message.is_empty() {
+      headers.push(Header {
+        key: "grpc-message".into(),
+        value: res.status_message.clone(),
+      });
+    }
+    let body = res.body();
+    let db_response = DBResponse {
+      id: Uuid::new_v4().to_string(),
+      status_code: 200,
+      name: input.name.clone(),
+      headers: headers.clone(),
+      body: Some(serde_json::to_string_pretty(&body)?),
+    };
+    self.db.save_response(&db_response).await?;
+    self
+      .db
+      .save_request_response_item(&db_request, &db_response, &now, &response_time, &[])
+      .await?;
+    Ok(Response {
+      status: format!("{} {}", res.code, res.status),
+      status_code: 200,
+      data: ResponseData::JSON(body),
+      response_time,
+      headers,
+      test_results: vec![],
+      environment: None,
+      body: ResponseBody::default(),
+      cookies: vec![],
+    })
+  }
+  // Sends every item in order, once per iteration, and saves the report. Environment changes
+  // made by one request carry over to the next. `on_result` is called as each request
+  // finishes, so callers can show progress while the run is going
+  pub async fn run_collection(
+    &mut self,
+    collection: &Collection,
+    items: &[&CollectionItem],
+    mut environment: EnvironmentFile,
+    options: &RunOptions,
+    mut on_result: impl FnMut(&RunResult),
+  ) -> anyhow::Result<RunReport> {
+    let started_at: DateTime<Utc> = Utc::now();
+    let timer = std::time::Instant::now();
+    let mut report = RunReport {
+      id: Uuid::new_v4().to_string(),
+      collection_id: collection.info.id.clone(),
+      collection_name: collection.info.name.clone(),
+      environment_name: environment.name.clone(),
+      started_at: started_at.to_rfc3339(),
+      duration_ms: 0,
+      iterations: options.iteration_count(),
+      stopped_early: false,
+      results: vec![],
+    };
+    'iterations: for iteration in 0..report.iterations {
+      for item in items {
+        if !report.results.is_empty() && options.delay_ms > 0 {
+          tokio::time::sleep(std::time::Duration::from_millis(options.delay_ms)).await;
+        }
+        let mut input = HttpRequest::from_collection_item(collection, item, environment.clone());
+        // data file values take precedence over every other variable
+        input
+          .variables
+          .extend_from_slice(options.data_for(iteration));
+        let mut result = RunResult {
+          iteration: iteration + 1,
+          request_id: item.id.clone(),
+          name: item.name.clone(),
           method: input.method.to_string(),
           url: input.url.clone(),
-          headers: request_headers,
-        };
-        self.db.save_request_history(&db_request).await?;
-        let response_headers: Vec<Header> = res_headers
-          .borrow()
-          .into_iter()
-          .map(|(key, value)| Header {
-            key: String::from(HeaderName::as_str(key)),
-            value: String::from(HeaderValue::to_str(value).unwrap()),
-          })
-          .collect();
-        let db_response = DBResponse {
-          id: Uuid::new_v4().to_string(),
-          status_code: res_status.as_u16(),
-          name: input.name.clone(),
-          headers: response_headers,
-          body: Some(res_text.clone()),
+          status: None,
+          status_code: None,
+          response_time: None,
+          test_results: vec![],
+          error: None,
         };
-        self.db.save_response(&db_response).await?;
+        match self.send_http_request(input).await {
+          Ok(res) => {
+            result.status = Some(res.status);
+            result.status_code = Some(res.status_code);
+            result.response_time = Some(res.response_time);
+            result.test_results = res.test_results;
+            if let Some(updated) = res.environment {
+              environment = updated;
+            }
+          }
+          Err(e) => result.error = Some(format!("{:#}", e)),
+        }
+        on_result(&result);
+        let failed = !result.passed();
+        report.results.push(result);
+        if failed && options.stop_on_failure {
+          report.stopped_early = true;
+          break 'iterations;
+        }
+      }
+    }
+    report.duration_ms = timer.elapsed().as_millis();
+    self.db.save_run_report(&report).await?;
+    Ok(report)
+  }
+  pub async fn load_run_reports(&self) -> anyhow::Result<Vec<RunReport>> {
+    self.db.get_all_run_reports().await
+  }
+  // Sends a request from a gui tab and saves the response to the tab, event stream bodies and
+  // download progress are passed on as in send_http_request_streaming. The tab is saved with its
+  // own auth settings rather than the auth it was sent with, which may have been inherited
+  pub async fn make_streaming_request(
+    &mut self,
+    input: HttpRequest,
+    auth: AuthSettings,
+    stop: Option<watch::Receiver<bool>>,
+    on_event: impl FnMut(&SseEvent) + Send,
+    on_progress: impl FnMut(ResponseProgress) + Send,
+  ) -> anyhow::Result<Response> {
+    let response = self
+      .send_http_request_streaming(input.clone(), stop, on_event, on_progress)
+      .await?;
+    let res_body = match &response.data {
+      ResponseData::JSON(j) => j.to_string(),
+      ResponseData::TEXT(t) => t.to_string(),
+      ResponseData::XML(x) => x.to_string(),
+      ResponseData::UNKNOWN(t) => t.to_string(),
+    };
+    let updated_tab = Tab {
+      id: input.tab_id,
+      method: input.method.clone(),
+      url: input.url.clone(),
+      req_body: input
+        .body
+        .as_ref()
+        .map(RequestBody::to_text)
+        .unwrap_or_default(),
+      body_type: input
+        .body
+        .as_ref()
+        .map(RequestBody::body_type)
+        .unwrap_or_default(),
+      req_headers: Headers(vec![]),
+      res_status: Some(response.status.clone()),
+      res_body,
+      res_headers: Headers(vec![]),
+      // left as is in the db
+      collection_id: None,
+      request_id: None,
+      scripts: input.scripts.clone(),
+      assertions: input.assertions.clone(),
+      extract: input.extract.clone(),
+      timeouts: input.timeouts,
+      auth,
+    };
+    self.db.save_tab(&updated_tab).await?;
+    Ok(response)
+  }
+  pub async fn make_request(&mut self, input: PostieRequest) -> anyhow::Result<Response> {
+    match input {
+      // request and save http request
+      PostieRequest::HTTP(input) => {
+        let auth = AuthSettings::from_request_auth(&input.auth);
         self
-          .db
-          .save_request_response_item(&db_request, &db_response, &now, &response_time)
-          .await?;
-        let response = utilities::response::build_response(res_type, res_status, res_text)?;
-        let res_body = match &response.data {
-          ResponseData::JSON(j) => j.to_string(),
-          ResponseData::TEXT(t) => t.to_string(),
-          ResponseData::XML(x) => x.to_string(),
-          ResponseData::UNKNOWN(t) => t.to_string(),
-        };
-        let updated_tab = Tab {
-          id: input.tab_id,
-          method: input.method.clone(),
-          url: input.url.clone(),
-          req_body: "".into(),
-          req_headers: Headers(vec![]),
-          res_status: Some(res_status.to_string()),
-          res_body,
-          res_headers: Headers(vec![]),
-        };
-        self.db.save_tab(&updated_tab).await?;
-        Ok(response)
+          .make_streaming_request(input, auth, None, |_| {}, |_| {})
+          .await
       }
       // if making an oauth token request, dont save to db
       PostieRequest::OAUTH(input) => {
-        println!("making ouath request");
-        let auth_header_value = base64::engine::general_purpose::STANDARD
-          .encode(format!("{}:{}", &input.client_id, &input.client_secret));
-        let mut header_map = HeaderMap::new();
-        let header_value = &format!("Basic {:?}", &auth_header_value);
-        println!("auth header: {}", &header_value);
-        header_map.insert(
-          header::AUTHORIZATION,
-          HeaderValue::from_str(header_value).unwrap(),
-        );
-        header_map.insert(
-          header::CONTENT_TYPE,
-          HeaderValue::from_str("application/x-www-form-urlencoded").unwrap(),
-        );
-        let mut req = self
-          .client
-          .request(Method::POST, input.access_token_url)
-          .headers(header_map);
-        req = req.form(&input.request);
-        let res = req.send().await?;
-        println!("{:?}", res);
-        let status = res.status().to_string();
+        debug!("making ouath request");
+        let sent_at = std::time::Instant::now();
+        let token = self
+          .request_oauth2_token(&input, |prompt| debug!("oauth 2 prompt: {:?}", prompt))
+          .await?;
         Ok(Response {
-          data: ResponseData::JSON(res.json().await.unwrap()),
-          status,
+          data: ResponseData::JSON(serde_json::json!({
+            "access_token": token.access_token,
+            "token_type": token.token_type,
+            "refresh_token": token.refresh_token,
+            "expires_at": token.expires_at.map(|e| e.to_rfc3339()),
+            "scope": token.scope,
+          })),
+          status: reqwest::StatusCode::OK.to_string(),
+          status_code: reqwest::StatusCode::OK.as_u16(),
+          response_time: sent_at.elapsed().as_millis(),
+          headers: vec![],
+          test_results: vec![],
+          environment: None,
+          body: ResponseBody::default(),
+          cookies: vec![],
         })
       }
     }
   }
+  pub async fn save_tab(&mut self, tab: &Tab) -> anyhow::Result<()> {
+    self.db.save_tab(tab).await?;
+    Ok(())
+  }
   pub async fn delete_tab(&mut self, tab_id: Uuid) -> anyhow::Result<()> {
     self.db.delete_tab(tab_id).await?;
     Ok(())
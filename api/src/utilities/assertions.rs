@@ -0,0 +1,84 @@
+use regex::Regex;
+use serde_json_path::JsonPath;
+
+use crate::domain::{
+  assertion::Assertion,
+  response::{Response, TestResult},
+};
+
+// `body` is the response text as received, before any pretty printing
+pub fn evaluate_assertions(
+  assertions: &[Assertion],
+  response: &Response,
+  body: &str,
+) -> Vec<TestResult> {
+  assertions
+    .iter()
+    .map(|assertion| {
+      let outcome = evaluate(assertion, response, body);
+      TestResult {
+        name: assertion.to_string(),
+        passed: outcome.is_ok(),
+        message: outcome.err(),
+      }
+    })
+    .collect()
+}
+
+fn evaluate(assertion: &Assertion, response: &Response, body: &str) -> Result<(), String> {
+  match assertion {
+    Assertion::StatusEquals { status } => check(
+      response.status_code == *status,
+      format!("got {}", response.status_code),
+    ),
+    Assertion::StatusInRange { min, max } => check(
+      (*min..=*max).contains(&response.status_code),
+      format!("got {}", response.status_code),
+    ),
+    Assertion::HeaderPresent { name } => header_value(response, name).map(|_| ()),
+    Assertion::HeaderMatches { name, pattern } => {
+      let value = header_value(response, name)?;
+      let regex = Regex::new(pattern).map_err(|e| format!("invalid pattern: {}", e))?;
+      check(regex.is_match(value), format!("got {:?}", value))
+    }
+    Assertion::JsonPathEquals { path, value } => {
+      let found = json_path_value(body, path)?;
+      check(&found == value, format!("got {}", found))
+    }
+    Assertion::BodyContains { text } => {
+      check(body.contains(text.as_str()), String::from("not found"))
+    }
+    Assertion::ResponseTimeUnder { ms } => check(
+      response.response_time < u128::from(*ms),
+      format!("took {} ms", response.response_time),
+    ),
+  }
+}
+
+fn check(passed: bool, failure: String) -> Result<(), String> {
+  if passed {
+    Ok(())
+  } else {
+    Err(failure)
+  }
+}
+
+pub(crate) fn json_path_value(body: &str, path: &str) -> Result<serde_json::Value, String> {
+  let json_path = JsonPath::parse(path).map_err(|e| format!("invalid path: {}", e))?;
+  let json: serde_json::Value =
+    serde_json::from_str(body).map_err(|_| String::from("response is not json"))?;
+  json_path
+    .query(&json)
+    .first()
+    .cloned()
+    .ok_or_else(|| String::from("nothing found at path"))
+}
+
+pub(crate) fn header_value<'a>(response: &'a Response, name: &str) -> Result<&'a str, String> {
+  response
+    .headers
+    .iter()
+    .find(|h| h.key.eq_ignore_ascii_case(name))
+    .map(|h| h.value.as_str())
+    .ok_or_else(|| format!("header {} is missing", name))
+}
@@ -0,0 +1,270 @@
+use std::{collections::HashMap, str::FromStr};
+
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{Algorithm, EncodingKey};
+use md5::Md5;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use crate::domain::auth::{AwsSigV4Auth, JwtAuth};
+
+// The parameters of a `WWW-Authenticate: Digest ...` challenge, None for other schemes
+pub fn parse_digest_challenge(header: &str) -> Option<HashMap<String, String>> {
+  let (scheme, rest) = header.trim().split_once(' ')?;
+  if !scheme.eq_ignore_ascii_case("digest") {
+    return None;
+  }
+  let mut params = HashMap::new();
+  let mut rest = rest.trim();
+  while !rest.is_empty() {
+    let (key, after) = rest.split_once('=')?;
+    let key = key.trim().trim_start_matches(',').trim().to_lowercase();
+    let after = after.trim_start();
+    let (value, after) = match after.strip_prefix('"') {
+      Some(quoted) => {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+      }
+      None => match after.find(',') {
+        Some(end) => (&after[..end], &after[end..]),
+        None => (after, ""),
+      },
+    };
+    params.insert(key, value.trim().to_string());
+    rest = after.trim_start_matches([',', ' ']);
+  }
+  Some(params)
+}
+
+// The Authorization header answering a digest challenge, None when the challenge asks for
+// something that isn't supported. `uri` is the request's path and query
+pub fn digest_authorization(
+  challenge: &HashMap<String, String>,
+  username: &str,
+  password: &str,
+  method: &str,
+  uri: &str,
+  cnonce: &str,
+) -> Option<String> {
+  let realm = challenge
+    .get("realm")
+    .map(String::as_str)
+    .unwrap_or_default();
+  let nonce = challenge.get("nonce")?;
+  let algorithm = challenge
+    .get("algorithm")
+    .map(String::as_str)
+    .unwrap_or("MD5");
+  let hash = |data: String| -> Option<String> {
+    match algorithm.trim_end_matches("-sess").to_uppercase().as_str() {
+      "MD5" => Some(hex::encode(Md5::digest(data.as_bytes()))),
+      "SHA-256" => Some(hex::encode(Sha256::digest(data.as_bytes()))),
+      _ => None,
+    }
+  };
+  let mut ha1 = hash(format!("{}:{}:{}", username, realm, password))?;
+  if algorithm.to_lowercase().ends_with("-sess") {
+    ha1 = hash(format!("{}:{}:{}", ha1, nonce, cnonce))?;
+  }
+  let ha2 = hash(format!("{}:{}", method, uri))?;
+  // only auth is supported, auth-int would need the body
+  let qop = challenge
+    .get("qop")
+    .map(|qop| qop.split(',').any(|q| q.trim() == "auth"));
+  let nc = "00000001";
+  let response = match qop {
+    Some(true) => hash(format!("{}:{}:{}:{}:auth:{}", ha1, nonce, nc, cnonce, ha2))?,
+    Some(false) => return None,
+    None => hash(format!("{}:{}:{}", ha1, nonce, ha2))?,
+  };
+  let mut header = format!(
+    "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+    username, realm, nonce, uri, algorithm, response
+  );
+  if qop.is_some() {
+    header.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", nc, cnonce));
+  }
+  if let Some(opaque) = challenge.get("opaque") {
+    header.push_str(&format!(", opaque=\"{}\"", opaque));
+  }
+  Some(header)
+}
+
+// The headers that sign a request with AWS Signature Version 4. `headers` are the ones already
+// on the request, they are all signed along with the host. Bodies that can't be read up front
+// are sent unsigned
+pub fn aws_sigv4_headers(
+  config: &AwsSigV4Auth,
+  method: &str,
+  url: &Url,
+  headers: &[(String, String)],
+  payload: Option<&[u8]>,
+  now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+  let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+  let date = now.format("%Y%m%d").to_string();
+  let payload_hash = match payload {
+    Some(payload) => hex::encode(Sha256::digest(payload)),
+    None => "UNSIGNED-PAYLOAD".to_string(),
+  };
+  let mut added = vec![("x-amz-date".to_string(), amz_date.clone())];
+  if !config.session_token.is_empty() {
+    added.push((
+      "x-amz-security-token".to_string(),
+      config.session_token.clone(),
+    ));
+  }
+  // s3 wants to know the hash, other services don't
+  if config.service == "s3" {
+    added.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+  }
+
+  let host = match url.port() {
+    Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+    None => url.host_str().unwrap_or_default().to_string(),
+  };
+  let mut signed: Vec<(String, String)> = headers
+    .iter()
+    .chain(added.iter())
+    .map(|(k, v)| {
+      (
+        k.to_lowercase(),
+        v.split_whitespace().collect::<Vec<_>>().join(" "),
+      )
+    })
+    .filter(|(k, _)| k != "host" && k != "authorization")
+    .chain(std::iter::once(("host".to_string(), host)))
+    .collect();
+  signed.sort();
+  let canonical_headers: String = signed
+    .iter()
+    .map(|(k, v)| format!("{}:{}\n", k, v))
+    .collect();
+  let signed_headers = signed
+    .iter()
+    .map(|(k, _)| k.as_str())
+    .collect::<Vec<_>>()
+    .join(";");
+
+  // s3 paths are encoded once, other services encode the already encoded path again
+  let path = if url.path().is_empty() {
+    "/"
+  } else {
+    url.path()
+  };
+  let canonical_uri = if config.service == "s3" {
+    path.to_string()
+  } else {
+    aws_encode(path, false)
+  };
+  // query_pairs would read `+` as a space, aws signs it as the plus it is
+  let mut query: Vec<(String, String)> = url
+    .query()
+    .unwrap_or_default()
+    .split('&')
+    .filter(|pair| !pair.is_empty())
+    .map(|pair| {
+      let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+      (
+        aws_encode(&aws_decode(k), true),
+        aws_encode(&aws_decode(v), true),
+      )
+    })
+    .collect();
+  query.sort();
+  let canonical_query = query
+    .iter()
+    .map(|(k, v)| format!("{}={}", k, v))
+    .collect::<Vec<_>>()
+    .join("&");
+
+  let canonical_request = format!(
+    "{}\n{}\n{}\n{}\n{}\n{}",
+    method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_hash
+  );
+  let scope = format!("{}/{}/{}/aws4_request", date, config.region, config.service);
+  let string_to_sign = format!(
+    "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+    amz_date,
+    scope,
+    hex::encode(Sha256::digest(canonical_request.as_bytes()))
+  );
+  let mut key = format!("AWS4{}", config.secret_key).into_bytes();
+  for part in [
+    date.as_str(),
+    config.region.as_str(),
+    config.service.as_str(),
+    "aws4_request",
+  ] {
+    key = hmac_sha256(&key, part.as_bytes());
+  }
+  let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+  added.push((
+    "Authorization".to_string(),
+    format!(
+      "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+      config.access_key, scope, signed_headers, signature
+    ),
+  ));
+  added
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac takes keys of any size");
+  mac.update(data);
+  mac.finalize().into_bytes().to_vec()
+}
+
+// Percent encodes everything but unreserved characters, and slashes unless `slash` is set
+fn aws_encode(value: &str, slash: bool) -> String {
+  let mut encoded = String::new();
+  for byte in value.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        encoded.push(byte as char)
+      }
+      b'/' if !slash => encoded.push('/'),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+fn aws_decode(value: &str) -> String {
+  percent_encoding::percent_decode_str(value)
+    .decode_utf8_lossy()
+    .into_owned()
+}
+
+// Signs the config's claims into a token
+pub fn jwt_token(config: &JwtAuth) -> anyhow::Result<String> {
+  let algorithm = Algorithm::from_str(&config.algorithm)
+    .map_err(|_| anyhow::anyhow!("{} is not a supported JWT algorithm", config.algorithm))?;
+  let claims: serde_json::Value = serde_json::from_str(&config.payload)
+    .map_err(|e| anyhow::anyhow!("The JWT payload is not valid json: {}", e))?;
+  let secret = if config.secret_base64 {
+    base64::engine::general_purpose::STANDARD
+      .decode(config.secret.trim())
+      .map_err(|e| anyhow::anyhow!("The JWT secret is not valid base64: {}", e))?
+  } else {
+    config.secret.clone().into_bytes()
+  };
+  let key = match algorithm {
+    Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => EncodingKey::from_secret(&secret),
+    Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(&secret)?,
+    Algorithm::RS256
+    | Algorithm::RS384
+    | Algorithm::RS512
+    | Algorithm::PS256
+    | Algorithm::PS384
+    | Algorithm::PS512 => EncodingKey::from_rsa_pem(&secret)?,
+    Algorithm::EdDSA => EncodingKey::from_ed_pem(&secret)?,
+  };
+  Ok(jsonwebtoken::encode(
+    &jsonwebtoken::Header::new(algorithm),
+    &claims,
+    &key,
+  )?)
+}
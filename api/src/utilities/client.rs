@@ -0,0 +1,142 @@
+use std::{path::Path, time::Duration};
+
+use reqwest::{redirect::Policy, Certificate, Identity, NoProxy, Proxy};
+
+use crate::domain::client::{
+  host_matches, ClientCertificate, ClientSettings, HttpVersion, RedirectPolicy,
+};
+
+// What a client is built from for one request: the settings, the parts of them that apply to the
+// request's host and the connect timeout. A client can be reused while this stays the same
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientProfile {
+  pub settings: ClientSettings,
+  pub insecure: bool,
+  pub certificate: Option<ClientCertificate>,
+  pub connect: Option<Duration>,
+}
+
+impl ClientProfile {
+  pub fn for_url(settings: &ClientSettings, url: &str, connect: Option<Duration>) -> Self {
+    let host = reqwest::Url::parse(url)
+      .map(|url| host_of(&url))
+      .unwrap_or_default();
+    let (insecure, certificate) = for_host(settings, &host);
+    ClientProfile {
+      settings: settings.clone(),
+      insecure,
+      certificate,
+      connect,
+    }
+  }
+
+  pub fn build(&self) -> anyhow::Result<reqwest::Client> {
+    let settings = &self.settings;
+    let mut builder = reqwest::Client::builder();
+    if let Some(connect) = self.connect {
+      builder = builder.connect_timeout(connect);
+    }
+    let no_proxy = NoProxy::from_string(&settings.no_proxy);
+    if !settings.http_proxy.trim().is_empty() {
+      let proxy = Proxy::http(settings.http_proxy.trim())
+        .map_err(|e| anyhow::anyhow!("{} is not a valid proxy: {}", settings.http_proxy, e))?;
+      builder = builder.proxy(proxy.no_proxy(no_proxy.clone()));
+    }
+    if !settings.https_proxy.trim().is_empty() {
+      let proxy = Proxy::https(settings.https_proxy.trim())
+        .map_err(|e| anyhow::anyhow!("{} is not a valid proxy: {}", settings.https_proxy, e))?;
+      builder = builder.proxy(proxy.no_proxy(no_proxy));
+    }
+    for path in &settings.ca_certificates {
+      let pem = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Could not read CA certificate {}: {}", path, e))?;
+      let certificates = Certificate::from_pem_bundle(&pem)
+        .map_err(|e| anyhow::anyhow!("{} is not a pem certificate: {}", path, e))?;
+      for certificate in certificates {
+        builder = builder.add_root_certificate(certificate);
+      }
+    }
+    if self.insecure {
+      builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(certificate) = &self.certificate {
+      builder = builder.identity(read_identity(certificate)?);
+    }
+    builder = builder.redirect(self.redirect_policy());
+    builder = match settings.http_version {
+      HttpVersion::AUTO => builder,
+      HttpVersion::HTTP1 => builder.http1_only(),
+      HttpVersion::HTTP2 => builder.http2_prior_knowledge(),
+    };
+    Ok(builder.build()?)
+  }
+
+  // The insecure setting and client certificate are only meant for the host they were set for, so
+  // a redirect to a host that doesn't share them is returned as is rather than followed
+  fn redirect_policy(&self) -> Policy {
+    let limit = match self.settings.redirects {
+      // reqwest's default
+      RedirectPolicy::FOLLOW => 10,
+      RedirectPolicy::LIMIT(limit) => limit,
+      RedirectPolicy::OFF => return Policy::none(),
+    };
+    if !self.insecure && self.certificate.is_none() {
+      return Policy::limited(limit);
+    }
+    let profile = self.clone();
+    Policy::custom(move |attempt| {
+      let (insecure, certificate) = for_host(&profile.settings, &host_of(attempt.url()));
+      if insecure != profile.insecure || certificate != profile.certificate {
+        attempt.stop()
+      } else if attempt.previous().len() > limit {
+        attempt.error("too many redirects")
+      } else {
+        attempt.follow()
+      }
+    })
+  }
+}
+
+fn host_of(url: &reqwest::Url) -> String {
+  url
+    .host_str()
+    .map(|host| host.trim_matches(['[', ']']).to_string())
+    .unwrap_or_default()
+}
+
+// Whether certificates aren't verified for the host, and the client certificate presented to it
+fn for_host(settings: &ClientSettings, host: &str) -> (bool, Option<ClientCertificate>) {
+  let insecure = settings
+    .insecure_hosts
+    .iter()
+    .any(|pattern| host_matches(pattern, host));
+  let certificate = settings
+    .client_certificates
+    .iter()
+    .find(|cert| host_matches(&cert.host, host))
+    .cloned();
+  (insecure, certificate)
+}
+
+fn read_identity(certificate: &ClientCertificate) -> anyhow::Result<Identity> {
+  let read = |path: &str| {
+    std::fs::read(path)
+      .map_err(|e| anyhow::anyhow!("Could not read client certificate {}: {}", path, e))
+  };
+  let is_pkcs12 = Path::new(&certificate.certificate)
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| ext.eq_ignore_ascii_case("p12") || ext.eq_ignore_ascii_case("pfx"));
+  let identity = if is_pkcs12 {
+    Identity::from_pkcs12_der(&read(&certificate.certificate)?, &certificate.password)
+  } else {
+    Identity::from_pkcs8_pem(&read(&certificate.certificate)?, &read(&certificate.key)?)
+  };
+  identity.map_err(|e| {
+    anyhow::anyhow!(
+      "Client certificate for {} could not be used: {}",
+      certificate.host,
+      e
+    )
+  })
+}
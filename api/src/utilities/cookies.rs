@@ -0,0 +1,145 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+
+use crate::domain::cookie::Cookie;
+
+// Reads a Set-Cookie header sent in answer to `url`. None when it isn't a valid cookie or sets a
+// domain `url` isn't part of. A cookie with a Max-Age of 0 or an Expires in the past comes back
+// expired, storing it deletes the cookie
+pub fn parse_set_cookie(header: &str, url: &Url, now: DateTime<Utc>) -> Option<Cookie> {
+  let host = url.host_str()?.to_lowercase();
+  let mut parts = header.split(';');
+  let (name, value) = parts.next()?.split_once('=')?;
+  let name = name.trim();
+  if name.is_empty() {
+    return None;
+  }
+  let mut cookie = Cookie {
+    name: name.to_string(),
+    value: value.trim().trim_matches('"').to_string(),
+    domain: host.clone(),
+    host_only: true,
+    path: default_path(url),
+    ..Default::default()
+  };
+  let mut max_age = None;
+  for attribute in parts {
+    let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+    let value = value.trim();
+    match key.trim().to_lowercase().as_str() {
+      "domain" => {
+        let domain = value.trim_start_matches('.').to_lowercase();
+        if domain.is_empty() {
+          continue;
+        }
+        if !domain_matches(&domain, &host) {
+          return None;
+        }
+        cookie.domain = domain;
+        cookie.host_only = false;
+      }
+      "path" if value.starts_with('/') => cookie.path = value.to_string(),
+      "expires" => cookie.expires = parse_date(value).or(cookie.expires),
+      "max-age" => max_age = value.parse::<i64>().ok(),
+      "secure" => cookie.secure = true,
+      "httponly" => cookie.http_only = true,
+      "samesite" if !value.is_empty() => cookie.same_site = Some(value.to_string()),
+      _ => {}
+    }
+  }
+  // Max-Age wins over Expires
+  if let Some(seconds) = max_age {
+    cookie.expires = Some(if seconds <= 0 {
+      DateTime::<Utc>::MIN_UTC
+    } else {
+      now + Duration::seconds(seconds.min(i32::MAX as i64))
+    });
+  }
+  Some(cookie)
+}
+
+// Adds the cookie to the jar or replaces the one it's the same as, an expired cookie removes it
+pub fn store_cookie(jar: &mut Vec<Cookie>, cookie: Cookie, now: DateTime<Utc>) {
+  jar.retain(|c| !c.same_as(&cookie));
+  if !cookie.is_expired(now) {
+    jar.push(cookie);
+  }
+}
+
+// The value of a Cookie header with every cookie in the jar that goes to `url`, the ones with
+// longer paths first
+pub fn cookie_header(jar: &[Cookie], url: &Url, now: DateTime<Utc>) -> Option<String> {
+  let host = url.host_str()?.to_lowercase();
+  let mut cookies: Vec<&Cookie> = jar
+    .iter()
+    .filter(|c| !c.is_expired(now))
+    .filter(|c| !c.secure || url.scheme() == "https")
+    .filter(|c| {
+      if c.host_only {
+        c.domain == host
+      } else {
+        domain_matches(&c.domain, &host)
+      }
+    })
+    .filter(|c| path_matches(&c.path, url.path()))
+    .collect();
+  if cookies.is_empty() {
+    return None;
+  }
+  cookies.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+  Some(
+    cookies
+      .iter()
+      .map(|c| format!("{}={}", c.name, c.value))
+      .collect::<Vec<_>>()
+      .join("; "),
+  )
+}
+
+// Adds the jar's cookies to a Cookie header the request already has, keeping its values when
+// both have a cookie with the same name
+pub fn merge_cookie_header(existing: &str, jar: &str) -> String {
+  let names: Vec<&str> = existing
+    .split(';')
+    .filter_map(|c| c.split_once('=').map(|(name, _)| name.trim()))
+    .collect();
+  let mut merged = vec![existing.trim().trim_end_matches(';').to_string()];
+  merged.extend(
+    jar
+      .split("; ")
+      .filter(|c| {
+        let name = c.split_once('=').map(|(name, _)| name).unwrap_or(c);
+        !names.contains(&name)
+      })
+      .map(str::to_string),
+  );
+  merged.retain(|c| !c.is_empty());
+  merged.join("; ")
+}
+
+fn domain_matches(domain: &str, host: &str) -> bool {
+  host == domain
+    || (host.ends_with(&format!(".{}", domain)) && host.parse::<std::net::IpAddr>().is_err())
+}
+
+fn path_matches(cookie_path: &str, path: &str) -> bool {
+  cookie_path == path
+    || (path.starts_with(cookie_path)
+      && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+// The folder of the request's path, used when a cookie doesn't set its own
+fn default_path(url: &Url) -> String {
+  match url.path().rfind('/') {
+    Some(0) | None => "/".into(),
+    Some(end) => url.path()[..end].to_string(),
+  }
+}
+
+// Expires dates look like `Wed, 21 Oct 2015 07:28:00 GMT`, some servers put dashes in the date
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+  DateTime::parse_from_rfc2822(value)
+    .or_else(|_| DateTime::parse_from_rfc2822(&value.replace('-', " ")))
+    .map(|date| date.with_timezone(&Utc))
+    .ok()
+}
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use crate::domain::run::DataRow;
+
+// Reads runner iteration data from a csv file with a header row, or a json array of objects
+pub fn parse_data_file(path: &str) -> anyhow::Result<Vec<DataRow>> {
+  let contents = std::fs::read_to_string(path)?;
+  let is_csv = Path::new(path)
+    .extension()
+    .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+  if is_csv {
+    parse_csv(&contents)
+  } else {
+    parse_json(&contents)
+  }
+}
+
+pub fn parse_csv(contents: &str) -> anyhow::Result<Vec<DataRow>> {
+  let mut reader = csv::Reader::from_reader(contents.as_bytes());
+  let headers = reader.headers()?.clone();
+  reader
+    .records()
+    .map(|record| {
+      let record = record?;
+      Ok(
+        headers
+          .iter()
+          .zip(record.iter())
+          .map(|(key, value)| (key.to_string(), value.to_string()))
+          .collect(),
+      )
+    })
+    .collect()
+}
+
+pub fn parse_json(contents: &str) -> anyhow::Result<Vec<DataRow>> {
+  let rows: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_str(contents)
+    .map_err(|e| anyhow::anyhow!("Data file should be an array of objects: {}", e))?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        row
+          .into_iter()
+          .map(|(key, value)| match value {
+            serde_json::Value::String(s) => (key, s),
+            other => (key, other.to_string()),
+          })
+          .collect()
+      })
+      .collect(),
+  )
+}
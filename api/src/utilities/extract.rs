@@ -0,0 +1,69 @@
+use log::warn;
+use regex::Regex;
+
+use crate::domain::{
+  environment::{EnvironmentFile, EnvironmentValue},
+  extract::{ExtractRule, ExtractSource},
+  response::{Response, TestResult},
+};
+
+use super::assertions::{header_value, json_path_value};
+
+// Writes every value found into the environment, adding the variable if it doesn't exist yet.
+// Returns a failed result for each rule that found nothing, those variables are left unchanged
+pub fn apply_extract_rules(
+  rules: &[ExtractRule],
+  response: &Response,
+  body: &str,
+  environment: &mut EnvironmentFile,
+) -> Vec<TestResult> {
+  let mut failures = vec![];
+  for rule in rules.iter().filter(|r| !r.variable.is_empty()) {
+    match extract(&rule.source, response, body) {
+      Ok(value) => set_variable(environment, &rule.variable, value),
+      Err(e) => {
+        warn!("Could not extract {}: {}", rule, e);
+        failures.push(TestResult {
+          name: format!("Extract {}", rule),
+          passed: false,
+          message: Some(e),
+        });
+      }
+    }
+  }
+  failures
+}
+
+fn extract(source: &ExtractSource, response: &Response, body: &str) -> Result<String, String> {
+  match source {
+    ExtractSource::JsonPath { path } => match json_path_value(body, path)? {
+      serde_json::Value::String(s) => Ok(s),
+      other => Ok(other.to_string()),
+    },
+    ExtractSource::Header { name } => header_value(response, name).map(String::from),
+    ExtractSource::Regex { pattern } => {
+      let regex = Regex::new(pattern).map_err(|e| format!("invalid pattern: {}", e))?;
+      let captures = regex
+        .captures(body)
+        .ok_or_else(|| String::from("no match"))?;
+      let found = captures.get(1).or_else(|| captures.get(0)).unwrap();
+      Ok(found.as_str().to_string())
+    }
+  }
+}
+
+fn set_variable(environment: &mut EnvironmentFile, key: &str, value: String) {
+  let values = environment.values.get_or_insert_with(Vec::new);
+  match values.iter_mut().find(|v| v.key == key) {
+    Some(existing) => {
+      existing.value = value;
+      existing.enabled = true;
+    }
+    None => values.push(EnvironmentValue {
+      key: key.to_string(),
+      value,
+      r#type: "default".into(),
+      enabled: true,
+    }),
+  }
+}
@@ -0,0 +1,121 @@
+use crate::domain::graphql::{GraphQLField, GraphQLSchema};
+
+/*
+ * Suggests the fields that can be selected where the cursor is in a query. The text before the
+ * cursor is walked to find the type of the selection set it is in: the root type of the
+ * operation at the first `{`, the type of the last field before every nested `{`, or the type
+ * named by `on` for fragments. Arguments, strings and comments are skipped. Only fields
+ * starting with the word being typed are returned. `cursor` counts chars, not bytes.
+ */
+pub fn complete<'a>(
+  schema: &'a GraphQLSchema,
+  query: &str,
+  cursor: usize,
+) -> Vec<&'a GraphQLField> {
+  let before: Vec<char> = query.chars().take(cursor).collect();
+  let mut stack: Vec<Option<String>> = vec![];
+  let mut operation = String::new();
+  let mut last_word = String::new();
+  let mut previous_word = String::new();
+  let mut paren_depth = 0;
+  let mut index = 0;
+  while index < before.len() {
+    let c = before[index];
+    match c {
+      '#' => {
+        while index < before.len() && before[index] != '\n' {
+          index += 1;
+        }
+      }
+      '"' => {
+        index += 1;
+        while index < before.len() && before[index] != '"' {
+          if before[index] == '\\' {
+            index += 1;
+          }
+          index += 1;
+        }
+      }
+      '(' => paren_depth += 1,
+      ')' => paren_depth -= 1,
+      '{' if paren_depth == 0 => {
+        let selected = if previous_word == "on" {
+          Some(last_word.clone())
+        } else if stack.is_empty() {
+          schema.root_type(&operation).map(String::from)
+        } else {
+          stack
+            .last()
+            .cloned()
+            .flatten()
+            .and_then(|parent| field_type(schema, &parent, &last_word))
+        };
+        stack.push(selected);
+        last_word.clear();
+        previous_word.clear();
+      }
+      '}' if paren_depth == 0 => {
+        stack.pop();
+        if stack.is_empty() {
+          operation.clear();
+        }
+        last_word.clear();
+        previous_word.clear();
+      }
+      c if paren_depth == 0 && is_name_start(c) => {
+        let start = index;
+        while index + 1 < before.len() && is_name_char(before[index + 1]) {
+          index += 1;
+        }
+        let word: String = before[start..=index].iter().collect();
+        let is_directive = start > 0 && before[start - 1] == '@';
+        if stack.is_empty() && operation.is_empty() {
+          operation = word.clone();
+        }
+        if !is_directive {
+          previous_word = std::mem::replace(&mut last_word, word);
+        }
+      }
+      _ => {}
+    }
+    index += 1;
+  }
+
+  let typing: String = before
+    .iter()
+    .rev()
+    .take_while(|c| is_name_char(**c))
+    .collect::<Vec<_>>()
+    .into_iter()
+    .rev()
+    .collect();
+  let Some(Some(type_name)) = stack.last() else {
+    return vec![];
+  };
+  schema
+    .find_type(type_name)
+    .map(|t| {
+      t.fields
+        .iter()
+        .filter(|f| f.name.starts_with(&typing))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn field_type(schema: &GraphQLSchema, parent: &str, field: &str) -> Option<String> {
+  schema
+    .find_type(parent)?
+    .fields
+    .iter()
+    .find(|f| f.name == field)
+    .map(|f| f.named_type.clone())
+}
+
+fn is_name_start(c: char) -> bool {
+  c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
@@ -0,0 +1,350 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+};
+
+use prost::Message as _;
+use prost_reflect::{
+  prost_types::FileDescriptorProto, DescriptorPool, DynamicMessage, MessageDescriptor,
+  MethodDescriptor, SerializeOptions,
+};
+use serde_json::Value;
+use tonic::{
+  codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+  codegen::http::uri::PathAndQuery,
+  metadata::{AsciiMetadataKey, AsciiMetadataValue, MetadataMap},
+  transport::{Channel, ClientTlsConfig, Endpoint},
+  Status,
+};
+use tonic_reflection::pb::v1::{
+  server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+  server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
+
+use crate::domain::{
+  grpc::{GrpcMethod, GrpcResponse},
+  header::Header,
+};
+
+// Splits a resolved grpc url into the endpoint to connect to and the Service/Method path
+pub fn parse_url(url: &str) -> anyhow::Result<(String, String)> {
+  let url = url.trim();
+  let (scheme, rest) = url
+    .split_once("://")
+    .ok_or_else(|| anyhow::anyhow!("{} is not a grpc url", url))?;
+  let scheme = match scheme.to_lowercase().as_str() {
+    "grpc" => "http",
+    "grpcs" => "https",
+    _ => anyhow::bail!("{} is not a grpc url", url),
+  };
+  let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+  if authority.is_empty() {
+    anyhow::bail!("{} has no host", url);
+  }
+  Ok((
+    format!("{}://{}", scheme, authority),
+    path.trim_matches('/').to_string(),
+  ))
+}
+
+pub async fn connect(endpoint: &str) -> anyhow::Result<Channel> {
+  let mut channel = Endpoint::from_shared(endpoint.to_string())?;
+  if endpoint.starts_with("https://") {
+    channel = channel.tls_config(ClientTlsConfig::new().with_native_roots())?;
+  }
+  channel
+    .connect()
+    .await
+    .map_err(|e| anyhow::Error::new(e).context(format!("Could not connect to {}", endpoint)))
+}
+
+// Reads .proto files and everything they import. Each file's folder is searched for imports,
+// the well known google/protobuf types are built in
+pub fn load_proto_files(paths: &[String]) -> anyhow::Result<DescriptorPool> {
+  let mut includes: Vec<PathBuf> = vec![];
+  for path in paths {
+    let folder = match Path::new(path).parent() {
+      Some(folder) if !folder.as_os_str().is_empty() => folder.to_path_buf(),
+      _ => PathBuf::from("."),
+    };
+    if !includes.contains(&folder) {
+      includes.push(folder);
+    }
+  }
+  let parsed = protobuf_parse::Parser::new()
+    .pure()
+    .includes(&includes)
+    .inputs(paths)
+    .parse_and_typecheck()
+    .map_err(|e| anyhow::anyhow!("Could not read proto files: {:#}", e))?;
+  let mut set = protobuf::descriptor::FileDescriptorSet::new();
+  set.file = parsed.file_descriptors;
+  let bytes = protobuf::Message::write_to_bytes(&set)?;
+  Ok(DescriptorPool::decode(bytes.as_slice())?)
+}
+
+// Asks the server's reflection service for every service it has and the files describing them
+pub async fn reflect_descriptors(channel: Channel) -> anyhow::Result<DescriptorPool> {
+  let mut client = ServerReflectionClient::new(channel);
+  let mut requests = vec![];
+  for response in ask_reflection(&mut client, vec![MessageRequest::ListServices("".into())]).await?
+  {
+    if let MessageResponse::ListServicesResponse(list) = response {
+      requests.extend(
+        list
+          .service
+          .into_iter()
+          .filter(|s| !s.name.starts_with("grpc.reflection."))
+          .map(|s| MessageRequest::FileContainingSymbol(s.name)),
+      );
+    }
+  }
+  // the server can leave out files it already sent, so ask again for any missing imports
+  let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+  while !requests.is_empty() {
+    for response in ask_reflection(&mut client, requests).await? {
+      if let MessageResponse::FileDescriptorResponse(response) = response {
+        for bytes in response.file_descriptor_proto {
+          let file = FileDescriptorProto::decode(bytes.as_slice())?;
+          files.insert(file.name().to_string(), file);
+        }
+      }
+    }
+    let mut missing: Vec<String> = files
+      .values()
+      .flat_map(|f| f.dependency.iter())
+      .filter(|d| !files.contains_key(*d))
+      .cloned()
+      .collect();
+    missing.sort();
+    missing.dedup();
+    requests = missing
+      .into_iter()
+      .map(MessageRequest::FileByFilename)
+      .collect();
+  }
+  let mut pool = DescriptorPool::new();
+  pool.add_file_descriptor_protos(files.into_values())?;
+  Ok(pool)
+}
+
+async fn ask_reflection(
+  client: &mut ServerReflectionClient<Channel>,
+  requests: Vec<MessageRequest>,
+) -> anyhow::Result<Vec<MessageResponse>> {
+  let requests: Vec<ServerReflectionRequest> = requests
+    .into_iter()
+    .map(|r| ServerReflectionRequest {
+      host: "".into(),
+      message_request: Some(r),
+    })
+    .collect();
+  let mut stream = client
+    .server_reflection_info(futures_util::stream::iter(requests))
+    .await
+    .map_err(|s| anyhow::anyhow!("Server reflection failed: {}", s.message()))?
+    .into_inner();
+  let mut responses = vec![];
+  while let Some(response) = stream
+    .message()
+    .await
+    .map_err(|s| anyhow::anyhow!("Server reflection failed: {}", s.message()))?
+  {
+    match response.message_response {
+      Some(MessageResponse::ErrorResponse(e)) => {
+        anyhow::bail!("Server reflection failed: {}", e.error_message)
+      }
+      Some(response) => responses.push(response),
+      None => {}
+    }
+  }
+  Ok(responses)
+}
+
+pub fn list_methods(pool: &DescriptorPool) -> Vec<GrpcMethod> {
+  let example_options = SerializeOptions::new().skip_default_fields(false);
+  let mut methods: Vec<GrpcMethod> = pool
+    .services()
+    .flat_map(|service| service.methods().collect::<Vec<_>>())
+    .map(|method| {
+      let example = DynamicMessage::new(method.input())
+        .serialize_with_options(serde_json::value::Serializer, &example_options)
+        .and_then(|json| serde_json::to_string_pretty(&json))
+        .unwrap_or_default();
+      GrpcMethod {
+        service: method.parent_service().full_name().to_string(),
+        name: method.name().to_string(),
+        client_streaming: method.is_client_streaming(),
+        server_streaming: method.is_server_streaming(),
+        example,
+      }
+    })
+    .collect();
+  methods.sort_by_key(GrpcMethod::path);
+  methods
+}
+
+// Looks up a Service/Method path, the service is fully qualified
+pub fn find_method(pool: &DescriptorPool, path: &str) -> anyhow::Result<MethodDescriptor> {
+  let (service, method) = path
+    .rsplit_once('/')
+    .ok_or_else(|| anyhow::anyhow!("Pick a method to call, the url ends with Service/Method"))?;
+  pool
+    .get_service_by_name(service)
+    .ok_or_else(|| anyhow::anyhow!("Service {} was not found", service))?
+    .methods()
+    .find(|m| m.name() == method)
+    .ok_or_else(|| anyhow::anyhow!("Method {} was not found in {}", method, service))
+}
+
+// Sends the json message to a unary or server streaming method. A status other than OK is part
+// of the response, errors are for calls that could not be made at all
+pub async fn call(
+  channel: Channel,
+  method: &MethodDescriptor,
+  message: &str,
+  metadata: &[(String, String)],
+) -> anyhow::Result<GrpcResponse> {
+  if method.is_client_streaming() {
+    anyhow::bail!(
+      "{} is a client streaming method, only unary and server streaming calls are supported",
+      method.name()
+    );
+  }
+  let message = if message.trim().is_empty() {
+    "{}"
+  } else {
+    message
+  };
+  let mut deserializer = serde_json::Deserializer::from_str(message);
+  let request = DynamicMessage::deserialize(method.input(), &mut deserializer)
+    .and_then(|request| deserializer.end().map(|_| request))
+    .map_err(|e| {
+      anyhow::anyhow!(
+        "Message is not a valid {}: {}",
+        method.input().full_name(),
+        e
+      )
+    })?;
+  let mut request = tonic::Request::new(request);
+  for (key, value) in metadata {
+    request.metadata_mut().insert(
+      AsciiMetadataKey::from_bytes(key.as_bytes())?,
+      AsciiMetadataValue::try_from(value.as_str())?,
+    );
+  }
+  let path = PathAndQuery::try_from(format!(
+    "/{}/{}",
+    method.parent_service().full_name(),
+    method.name()
+  ))?;
+  let codec = DynamicCodec(method.output());
+  let mut grpc = tonic::client::Grpc::new(channel);
+  grpc.ready().await?;
+
+  let mut response = GrpcResponse {
+    code: tonic::Code::Ok as i32,
+    status: format!("{:?}", tonic::Code::Ok),
+    status_message: "".into(),
+    metadata: vec![],
+    messages: vec![],
+    server_streaming: method.is_server_streaming(),
+  };
+  if method.is_server_streaming() {
+    match grpc.server_streaming(request, path, codec).await {
+      Ok(res) => {
+        response.metadata = metadata_headers(res.metadata());
+        let mut stream = res.into_inner();
+        loop {
+          match stream.message().await {
+            Ok(Some(message)) => response.messages.push(message_json(&message)?),
+            Ok(None) => break,
+            Err(status) => {
+              set_status(&mut response, &status);
+              break;
+            }
+          }
+        }
+        if let Ok(Some(trailers)) = stream.trailers().await {
+          response.metadata.extend(metadata_headers(&trailers));
+        }
+      }
+      Err(status) => set_status(&mut response, &status),
+    }
+  } else {
+    match grpc.unary(request, path, codec).await {
+      Ok(res) => {
+        response.metadata = metadata_headers(res.metadata());
+        response.messages.push(message_json(res.get_ref())?);
+      }
+      Err(status) => set_status(&mut response, &status),
+    }
+  }
+  Ok(response)
+}
+
+fn set_status(response: &mut GrpcResponse, status: &Status) {
+  response.code = status.code() as i32;
+  response.status = format!("{:?}", status.code());
+  response.status_message = status.message().to_string();
+  response
+    .metadata
+    .extend(metadata_headers(status.metadata()));
+}
+
+fn message_json(message: &DynamicMessage) -> anyhow::Result<Value> {
+  Ok(serde_json::to_value(message)?)
+}
+
+fn metadata_headers(metadata: &MetadataMap) -> Vec<Header> {
+  metadata
+    .clone()
+    .into_headers()
+    .iter()
+    .map(|(key, value)| Header {
+      key: key.to_string(),
+      value: value.to_str().unwrap_or_default().to_string(),
+    })
+    .collect()
+}
+
+// Encodes and decodes messages from their descriptors instead of generated types
+#[derive(Clone)]
+struct DynamicCodec(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+  type Encode = DynamicMessage;
+  type Decode = DynamicMessage;
+  type Encoder = DynamicCodec;
+  type Decoder = DynamicCodec;
+
+  fn encoder(&mut self) -> Self::Encoder {
+    self.clone()
+  }
+
+  fn decoder(&mut self) -> Self::Decoder {
+    self.clone()
+  }
+}
+
+impl Encoder for DynamicCodec {
+  type Item = DynamicMessage;
+  type Error = Status;
+
+  fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+    item
+      .encode(dst)
+      .map_err(|e| Status::internal(e.to_string()))
+  }
+}
+
+impl Decoder for DynamicCodec {
+  type Item = DynamicMessage;
+  type Error = Status;
+
+  fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+    DynamicMessage::decode(self.0.clone(), src)
+      .map(Some)
+      .map_err(|e| Status::internal(e.to_string()))
+  }
+}
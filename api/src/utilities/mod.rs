@@ -1 +1,14 @@
+pub mod assertions;
+pub mod auth;
+pub mod client;
+pub mod cookies;
+pub mod data_file;
+pub mod extract;
+pub mod graphql;
+pub mod grpc;
+pub mod oauth2;
 pub mod response;
+pub mod scripting;
+pub mod sse;
+pub mod variables;
+pub mod websocket;
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use base64::Engine as _;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{header, RequestBuilder, StatusCode, Url};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::TcpListener,
+};
+use uuid::Uuid;
+
+use crate::domain::{
+  auth::{OAuth2ClientAuth, OAuth2Token},
+  request::OAuth2Request,
+  response::OAuthResponse,
+};
+
+// A device authorization endpoint's answer
+#[derive(Clone, Debug, Deserialize)]
+pub struct DeviceAuthorization {
+  pub device_code: String,
+  pub user_code: String,
+  // some servers call it verification_url
+  #[serde(alias = "verification_url")]
+  pub verification_uri: String,
+  pub verification_uri_complete: Option<String>,
+  pub expires_in: Option<i64>,
+  // seconds to wait between polls
+  pub interval: Option<u64>,
+}
+
+// A random PKCE code verifier and its S256 challenge
+pub fn pkce_pair() -> (String, String) {
+  let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+  let challenge =
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+  (verifier, challenge)
+}
+
+// Where the browser is sent to sign in for the authorization code grant
+pub fn authorize_url(
+  config: &OAuth2Request,
+  redirect_uri: &str,
+  state: &str,
+  challenge: &str,
+) -> anyhow::Result<String> {
+  let mut url = Url::parse(&config.auth_url)
+    .map_err(|e| anyhow::anyhow!("{} is not a valid auth url: {}", config.auth_url, e))?;
+  {
+    let mut query = url.query_pairs_mut();
+    query
+      .append_pair("response_type", "code")
+      .append_pair("client_id", &config.client_id)
+      .append_pair("redirect_uri", redirect_uri)
+      .append_pair("state", state)
+      .append_pair("code_challenge", challenge)
+      .append_pair("code_challenge_method", "S256");
+    if !config.request.scope.is_empty() {
+      query.append_pair("scope", &config.request.scope);
+    }
+    if !config.request.audience.is_empty() {
+      query.append_pair("audience", &config.request.audience);
+    }
+  }
+  Ok(url.to_string())
+}
+
+// Listens where the browser is redirected to after signing in. Returns the listener and the
+// redirect uri to send, which has the port picked when the config doesn't set one
+pub async fn bind_redirect_listener(redirect_uri: &str) -> anyhow::Result<(TcpListener, String)> {
+  if redirect_uri.trim().is_empty() {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    return Ok((listener, format!("http://127.0.0.1:{}/callback", port)));
+  }
+  let url = Url::parse(redirect_uri)
+    .map_err(|e| anyhow::anyhow!("{} is not a valid redirect uri: {}", redirect_uri, e))?;
+  let host = url.host_str().unwrap_or("127.0.0.1");
+  let port = url.port_or_known_default().unwrap_or(80);
+  let listener = TcpListener::bind((host, port)).await.map_err(|e| {
+    anyhow::anyhow!(
+      "Could not listen for the redirect to {}: {}",
+      redirect_uri,
+      e
+    )
+  })?;
+  Ok((listener, redirect_uri.to_string()))
+}
+
+// Answers the browser's redirect and returns the authorization code in it. Requests for other
+// paths, like a favicon, are turned away
+pub async fn wait_for_code(
+  listener: &TcpListener,
+  redirect_uri: &str,
+  state: &str,
+) -> anyhow::Result<String> {
+  let path = Url::parse(redirect_uri)?.path().to_string();
+  loop {
+    let (mut socket, _) = listener.accept().await?;
+    let mut request = vec![];
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 16 * 1024 {
+      let read = socket.read(&mut buf).await?;
+      if read == 0 {
+        break;
+      }
+      request.extend_from_slice(&buf[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let target = request
+      .lines()
+      .next()
+      .and_then(|line| line.split_whitespace().nth(1))
+      .unwrap_or("/");
+    let url = Url::parse(&format!("http://localhost{}", target))?;
+    if url.path() != path {
+      let _ = socket
+        .write_all(b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+        .await;
+      continue;
+    }
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let result = if let Some(error) = params.get("error") {
+      Err(anyhow::anyhow!(
+        "Authorization failed: {}{}",
+        error,
+        params
+          .get("error_description")
+          .map(|d| format!(", {}", d))
+          .unwrap_or_default()
+      ))
+    } else if params.get("state").map(String::as_str) != Some(state) {
+      Err(anyhow::anyhow!(
+        "Authorization failed: the redirect is not for this sign in"
+      ))
+    } else {
+      params
+        .get("code")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Authorization failed: the redirect has no code"))
+    };
+    let page = match &result {
+      Ok(_) => "Signed in, you can close this window and go back to Postie".to_string(),
+      Err(e) => e.to_string(),
+    };
+    let _ = socket
+      .write_all(
+        format!(
+          "HTTP/1.1 200 OK\r\ncontent-type: text/plain; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+          page.len(),
+          page
+        )
+        .as_bytes(),
+      )
+      .await;
+    return result;
+  }
+}
+
+// Sends the form with the client's credentials, in a Basic header unless the config puts them
+// in the body. Public clients without a secret only send their id in the body
+pub fn token_request(
+  req: RequestBuilder,
+  config: &OAuth2Request,
+  mut form: Vec<(&'static str, String)>,
+) -> RequestBuilder {
+  let mut req = req.header(header::ACCEPT, "application/json");
+  match config.client_authentication {
+    OAuth2ClientAuth::HEADER if !config.client_secret.is_empty() => {
+      let credentials = base64::engine::general_purpose::STANDARD
+        .encode(format!("{}:{}", config.client_id, config.client_secret));
+      req = req.header(header::AUTHORIZATION, format!("Basic {}", credentials));
+    }
+    _ => {
+      form.push(("client_id", config.client_id.clone()));
+      if !config.client_secret.is_empty() {
+        form.push(("client_secret", config.client_secret.clone()));
+      }
+    }
+  }
+  req.form(&form)
+}
+
+// Reads a token endpoint's answer into the token to keep
+pub fn parse_token_response(
+  status: StatusCode,
+  text: &str,
+  now: DateTime<Utc>,
+) -> anyhow::Result<OAuth2Token> {
+  if !status.is_success() {
+    return Err(token_error(status, text));
+  }
+  let res: OAuthResponse = serde_json::from_str(text)
+    .map_err(|e| anyhow::anyhow!("The token response is not valid: {}", e))?;
+  Ok(OAuth2Token {
+    access_token: res.access_token,
+    token_type: res.token_type.unwrap_or_else(|| "Bearer".into()),
+    refresh_token: res.refresh_token,
+    expires_at: res
+      .expires_in
+      .map(|seconds| now + Duration::seconds(seconds)),
+    scope: res.scope,
+  })
+}
+
+// The `error` of an OAuth error response, e.g. authorization_pending
+pub fn error_code(text: &str) -> Option<String> {
+  serde_json::from_str::<serde_json::Value>(text)
+    .ok()?
+    .get("error")?
+    .as_str()
+    .map(str::to_string)
+}
+
+pub fn token_error(status: StatusCode, text: &str) -> anyhow::Error {
+  let description = serde_json::from_str::<serde_json::Value>(text)
+    .ok()
+    .and_then(|json| json.get("error_description")?.as_str().map(str::to_string));
+  match (error_code(text), description) {
+    (Some(error), Some(description)) => {
+      anyhow::anyhow!("Token request failed: {}, {}", error, description)
+    }
+    (Some(error), None) => anyhow::anyhow!("Token request failed: {}", error),
+    _ => anyhow::anyhow!("Token request failed with {}: {}", status, text),
+  }
+}
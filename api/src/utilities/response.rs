@@ -1,5 +1,6 @@
-use crate::domain::response::{Response, ResponseData};
+use crate::domain::response::{Response, ResponseBody, ResponseData};
 use anyhow;
+use log::warn;
 use reqwest::StatusCode;
 use serde_json::json;
 
@@ -7,6 +8,7 @@ pub fn build_response(
   res_type: &str,
   res_status: StatusCode,
   res_text: String,
+  response_time: u128,
 ) -> anyhow::Result<Response> {
   if !res_type.starts_with("application/json")
     && !res_type.starts_with("text/plain")
@@ -15,16 +17,25 @@ pub fn build_response(
     && !res_type.starts_with("text/xml")
   {
     // I couldn't figure out how to safely throw an error so I'm just returning this for now
-    println!(
+    warn!(
       "expected application/json, application/xml, text/xml, text/html, or text/plain, got {}",
       res_type
     );
     return Ok(Response {
-      data: ResponseData::JSON(json!({"err": println!("unsupported response type {}!", res_type)})),
+      data: ResponseData::JSON(json!({"err": format!("unsupported response type {}!", res_type)})),
       status: res_status.to_string(),
+      status_code: res_status.as_u16(),
+      response_time,
+      headers: vec![],
+      test_results: vec![],
+      environment: None,
+      body: ResponseBody::default(),
+      cookies: vec![],
     });
   }
-  let res_data = match res_type {
+  // ignore parameters like charset, scripts need the body of `application/json; charset=utf-8` too
+  let mime = res_type.split(';').next().unwrap_or_default().trim();
+  let res_data = match mime {
     "application/json" => {
       let res_json = serde_json::from_str(&res_text)?;
       ResponseData::JSON(res_json)
@@ -46,5 +57,75 @@ pub fn build_response(
   Ok(Response {
     data: res_data,
     status: res_status.to_string(),
+    status_code: res_status.as_u16(),
+    response_time,
+    headers: vec![],
+    test_results: vec![],
+    environment: None,
+    body: ResponseBody::default(),
+    cookies: vec![],
   })
 }
+
+// Bodies of these types are read as text, anything else is kept as bytes. A missing content
+// type is treated as text until the body turns out not to be utf-8
+pub fn is_text_content_type(content_type: &str) -> bool {
+  let mime = content_type
+    .split(';')
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .to_lowercase();
+  mime.is_empty()
+    || mime.starts_with("text/")
+    || mime.ends_with("/json")
+    || mime.ends_with("+json")
+    || mime.ends_with("/xml")
+    || mime.ends_with("+xml")
+    || mime == "application/javascript"
+    || mime == "application/graphql"
+    || mime == "application/x-www-form-urlencoded"
+}
+
+// A binary or cut short body can't be parsed, binary ones are described and the rest of the
+// preview is shown as text
+pub fn build_preview(
+  res_status: StatusCode,
+  body: ResponseBody,
+  preview: String,
+  response_time: u128,
+) -> Response {
+  let data = if body.binary {
+    ResponseData::UNKNOWN(format!(
+      "Binary response, {} bytes of {}",
+      body.size,
+      if body.content_type.is_empty() {
+        "unknown type"
+      } else {
+        &body.content_type
+      }
+    ))
+  } else {
+    ResponseData::TEXT(preview)
+  };
+  Response {
+    data,
+    status: res_status.to_string(),
+    status_code: res_status.as_u16(),
+    response_time,
+    headers: vec![],
+    test_results: vec![],
+    environment: None,
+    body,
+    cookies: vec![],
+  }
+}
+
+// Saves a whole response body, from its temporary file when it has one
+pub async fn save_body(body: &ResponseBody, data: &ResponseData, path: &str) -> anyhow::Result<()> {
+  match &body.file {
+    Some(file) => tokio::fs::copy(file, path).await.map(|_| ()),
+    None => tokio::fs::write(path, data.to_raw_string()).await,
+  }
+  .map_err(|e| anyhow::anyhow!("Could not save the response to {}: {}", path, e))
+}
@@ -0,0 +1,224 @@
+use std::{cell::RefCell, rc::Rc, str::FromStr};
+
+use log::info;
+use rhai::{Dynamic, Engine, Map, Scope};
+
+use crate::domain::{
+  environment::{EnvironmentFile, EnvironmentValue},
+  request::{BodyType, HttpMethod, HttpRequest, RequestBody},
+  response::{Response, ResponseData, TestResult},
+};
+
+// Keeps a runaway script from hanging the request worker
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/*
+ * Scripts are written in rhai (https://rhai.rs) and see these variables:
+ * - `env`: map of the enabled environment values, changes are written back to the environment
+ * - `request` (pre-request only): map with `method`, `url`, `headers` and `body` (a map for json
+ *   bodies, an array of `#{key, value, enabled}` for urlencoded forms, an array of
+ *   `#{type, name, ...}` parts for multipart bodies, `#{query, variables, operation_name}` for
+ *   graphql and a string for every other kind), changes are applied to the request before it
+ *   is sent
+ * - `response` (test only): map with `status`, `status_text`, `response_time`, `headers`,
+ *   `body` and `json` (the parsed body, or () if it wasn't json)
+ * Test scripts record assertions with `test("name", condition)`.
+ */
+fn new_engine() -> Engine {
+  let mut engine = Engine::new();
+  engine.set_max_operations(MAX_OPERATIONS);
+  engine.on_print(|s| info!("script: {}", s));
+  engine.on_debug(|s, _, pos| info!("script {}: {}", pos, s));
+  engine
+}
+
+// Runs before variables are substituted, so scripts can set values the request then uses
+pub fn run_pre_request_script(script: &str, request: &mut HttpRequest) -> anyhow::Result<()> {
+  let engine = new_engine();
+  let mut scope = Scope::new();
+  scope.push("env", env_to_map(&request.environment));
+  scope.push("request", request_to_map(request)?);
+
+  engine
+    .run_with_scope(&mut scope, script)
+    .map_err(|e| anyhow::anyhow!("Pre-request script failed: {}", e))?;
+
+  if let Some(env) = scope.get_value::<Map>("env") {
+    apply_env_map(&mut request.environment, env);
+  }
+  if let Some(map) = scope.get_value::<Map>("request") {
+    apply_request_map(request, map)?;
+  }
+  Ok(())
+}
+
+// Script errors are reported as a failed result rather than failing the request, the
+// response has already been received at this point
+pub fn run_test_script(
+  script: &str,
+  response: &Response,
+  environment: &mut EnvironmentFile,
+) -> Vec<TestResult> {
+  let results = Rc::new(RefCell::new(vec![]));
+  let mut engine = new_engine();
+  let results_for_script = Rc::clone(&results);
+  engine.register_fn("test", move |name: &str, passed: bool| {
+    results_for_script.borrow_mut().push(TestResult {
+      name: name.to_string(),
+      passed,
+      message: None,
+    });
+  });
+  let mut scope = Scope::new();
+  scope.push("env", env_to_map(environment));
+  scope.push("response", response_to_map(response));
+
+  let outcome = engine.run_with_scope(&mut scope, script);
+
+  if let Some(env) = scope.get_value::<Map>("env") {
+    apply_env_map(environment, env);
+  }
+  let mut results = results.take();
+  if let Err(e) = outcome {
+    results.push(TestResult {
+      name: "Test script".into(),
+      passed: false,
+      message: Some(e.to_string()),
+    });
+  }
+  results
+}
+
+fn env_to_map(environment: &EnvironmentFile) -> Map {
+  environment
+    .values
+    .iter()
+    .flatten()
+    .filter(|v| v.enabled)
+    .map(|v| (v.key.as_str().into(), v.value.clone().into()))
+    .collect()
+}
+
+// Keys the script set are added or updated, enabled keys it removed are dropped. Disabled
+// values are never visible to scripts so they are left alone
+fn apply_env_map(environment: &mut EnvironmentFile, env: Map) {
+  let values = environment.values.get_or_insert_with(Vec::new);
+  values.retain(|v| !v.enabled || env.contains_key(v.key.as_str()));
+  for (key, value) in env {
+    let value = dynamic_to_string(value);
+    match values.iter_mut().find(|v| v.key == key.as_str()) {
+      Some(existing) => {
+        existing.value = value;
+        existing.enabled = true;
+      }
+      None => values.push(EnvironmentValue {
+        key: key.to_string(),
+        value,
+        r#type: "default".into(),
+        enabled: true,
+      }),
+    }
+  }
+}
+
+fn request_to_map(request: &HttpRequest) -> anyhow::Result<Map> {
+  let headers: Map = request
+    .headers
+    .iter()
+    .flatten()
+    .map(|(key, value)| (key.as_str().into(), value.clone().into()))
+    .collect();
+  let body = match &request.body {
+    Some(RequestBody::JSON(j)) => rhai::serde::to_dynamic(j).map_err(script_error)?,
+    Some(RequestBody::FORM(fields)) => rhai::serde::to_dynamic(fields).map_err(script_error)?,
+    Some(RequestBody::GRAPHQL(graphql)) => {
+      rhai::serde::to_dynamic(graphql).map_err(script_error)?
+    }
+    Some(RequestBody::MULTIPART(parts)) => rhai::serde::to_dynamic(parts).map_err(script_error)?,
+    Some(other) => other.to_text().into(),
+    None => Dynamic::UNIT,
+  };
+  let mut map = Map::new();
+  map.insert("method".into(), request.method.to_string().into());
+  map.insert("url".into(), request.url.clone().into());
+  map.insert("headers".into(), headers.into());
+  map.insert("body".into(), body);
+  Ok(map)
+}
+
+fn apply_request_map(request: &mut HttpRequest, mut map: Map) -> anyhow::Result<()> {
+  if let Some(method) = map.remove("method") {
+    let method = dynamic_to_string(method);
+    request.method = HttpMethod::from_str(&method)
+      .map_err(|_| anyhow::anyhow!("Pre-request script set an unknown method {}", method))?;
+  }
+  if let Some(url) = map.remove("url") {
+    request.url = dynamic_to_string(url);
+  }
+  if let Some(headers) = map.remove("headers").and_then(|h| h.try_cast::<Map>()) {
+    request.headers = Some(
+      headers
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), dynamic_to_string(value)))
+        .collect(),
+    );
+  }
+  if let Some(body) = map.remove("body") {
+    let body_type = request.body.as_ref().map(RequestBody::body_type);
+    request.body = match body_type {
+      _ if body.is_unit() => None,
+      Some(BodyType::FORM) => Some(RequestBody::FORM(
+        rhai::serde::from_dynamic(&body).map_err(script_error)?,
+      )),
+      Some(BodyType::GRAPHQL) => Some(RequestBody::GRAPHQL(
+        rhai::serde::from_dynamic(&body).map_err(script_error)?,
+      )),
+      Some(BodyType::MULTIPART) => Some(RequestBody::MULTIPART(
+        rhai::serde::from_dynamic(&body).map_err(script_error)?,
+      )),
+      Some(body_type) if body_type != BodyType::JSON && body.is_string() => {
+        RequestBody::from_text(&body_type, &dynamic_to_string(body))
+      }
+      _ => Some(RequestBody::JSON(
+        rhai::serde::from_dynamic(&body).map_err(script_error)?,
+      )),
+    };
+  }
+  Ok(())
+}
+
+fn response_to_map(response: &Response) -> Map {
+  let json = match &response.data {
+    ResponseData::JSON(j) => rhai::serde::to_dynamic(j).unwrap_or(Dynamic::UNIT),
+    _ => Dynamic::UNIT,
+  };
+  let headers: Map = response
+    .headers
+    .iter()
+    .map(|h| (h.key.as_str().into(), h.value.clone().into()))
+    .collect();
+  let mut map = Map::new();
+  map.insert("status".into(), (response.status_code as i64).into());
+  map.insert("status_text".into(), response.status.clone().into());
+  map.insert(
+    "response_time".into(),
+    (response.response_time as i64).into(),
+  );
+  map.insert("headers".into(), headers.into());
+  map.insert("body".into(), response.data.to_raw_string().into());
+  map.insert("json".into(), json);
+  map
+}
+
+// rhai errors aren't Send, so they can't be passed on as they are
+fn script_error(e: Box<rhai::EvalAltResult>) -> anyhow::Error {
+  anyhow::anyhow!("{}", e)
+}
+
+fn dynamic_to_string(value: Dynamic) -> String {
+  if value.is_string() {
+    value.into_string().unwrap_or_default()
+  } else {
+    value.to_string()
+  }
+}
@@ -0,0 +1,98 @@
+use crate::domain::sse::SseEvent;
+
+/*
+ * Parses a text/event-stream body as it arrives. Chunks can end anywhere, even inside a
+ * utf-8 character, so only complete lines are read and the rest waits for the next chunk.
+ * Follows the html spec: `field: value` lines build up an event that is dispatched on a blank
+ * line, multiple data lines are joined with newlines, lines starting with `:` are comments and
+ * the last id carries over to the events after it.
+ */
+#[derive(Default)]
+pub struct SseParser {
+  buffer: Vec<u8>,
+  last_id: Option<String>,
+  event: Option<String>,
+  data: Vec<String>,
+  retry: Option<u64>,
+}
+
+impl SseParser {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // Returns the events completed by this chunk
+  pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+    self.buffer.extend_from_slice(chunk);
+    let mut events = vec![];
+    while let Some(end) = self.buffer.iter().position(|b| *b == b'\n' || *b == b'\r') {
+      // a \r\n pair is one line ending, wait for the next chunk when it could be split
+      let ending = if self.buffer[end] == b'\r' {
+        match self.buffer.get(end + 1) {
+          Some(b'\n') => 2,
+          Some(_) => 1,
+          None => break,
+        }
+      } else {
+        1
+      };
+      let line: Vec<u8> = self.buffer.drain(..end + ending).take(end).collect();
+      if let Some(event) = self.read_line(&String::from_utf8_lossy(&line)) {
+        events.push(event);
+      }
+    }
+    events
+  }
+
+  // Called when the stream ends, a lone \r at the very end still finishes its line. An event
+  // without the blank line after it is dropped, like browsers do
+  pub fn finish(&mut self) -> Vec<SseEvent> {
+    if self.buffer.last() == Some(&b'\r') {
+      self.buffer.pop();
+      let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).to_string();
+      return self.read_line(&line).into_iter().collect();
+    }
+    vec![]
+  }
+
+  fn read_line(&mut self, line: &str) -> Option<SseEvent> {
+    if line.is_empty() {
+      return self.dispatch();
+    }
+    if line.starts_with(':') {
+      return None;
+    }
+    let (field, value) = match line.split_once(':') {
+      Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+      None => (line, ""),
+    };
+    match field {
+      "event" => self.event = Some(value.to_string()),
+      "data" => self.data.push(value.to_string()),
+      // ids with a null character are ignored by the spec
+      "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+      "retry" => {
+        if let Ok(retry) = value.parse() {
+          self.retry = Some(retry);
+        }
+      }
+      _ => {}
+    }
+    None
+  }
+
+  fn dispatch(&mut self) -> Option<SseEvent> {
+    let event = self.event.take();
+    let retry = self.retry.take();
+    if self.data.is_empty() {
+      return None;
+    }
+    Some(SseEvent {
+      received_at: chrono::Utc::now().to_rfc3339(),
+      id: self.last_id.clone().filter(|id| !id.is_empty()),
+      event: event.filter(|e| !e.is_empty()),
+      data: std::mem::take(&mut self.data).join("\n"),
+      retry,
+    })
+  }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::domain::{
+  auth::{AwsSigV4Auth, JwtAuth, RequestAuth},
+  environment::{EnvironmentFile, EnvironmentValue},
+  request::{FormField, GraphQLBody, HttpRequest, MultipartPart, OAuth2Request, RequestBody},
+};
+
+// Resolves {{name}} references using the enabled globals and environment values, any scoped
+// collection variables layered over them and a few of postman's dynamic variables
+// ({{$guid}}, {{$timestamp}}, ...). Anything that can't be resolved is left as is and
+// remembered so callers can refuse to send it.
+pub struct VariableResolver {
+  values: HashMap<String, String>,
+  unresolved: BTreeSet<String>,
+}
+
+impl VariableResolver {
+  pub fn new(environment: &EnvironmentFile) -> Self {
+    Self::with_globals(&[], environment)
+  }
+
+  // Globals are the lowest precedence layer, the environment overrides them
+  pub fn with_globals(globals: &[EnvironmentValue], environment: &EnvironmentFile) -> Self {
+    let values = globals
+      .iter()
+      .chain(environment.values.iter().flatten())
+      .filter(|v| v.enabled)
+      .map(|v| (v.key.clone(), v.value.clone()))
+      .collect();
+    VariableResolver {
+      values,
+      unresolved: BTreeSet::new(),
+    }
+  }
+
+  // Layers more specific variables over the environment, later entries win
+  pub fn with_scope(mut self, variables: &[(String, String)]) -> Self {
+    self.values.extend(variables.iter().cloned());
+    self
+  }
+
+  pub fn resolve(&mut self, text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+      let after_open = &rest[start + 2..];
+      let Some(end) = after_open.find("}}") else {
+        break;
+      };
+      let name = &after_open[..end];
+      result.push_str(&rest[..start]);
+      match self.lookup(name) {
+        Some(value) => result.push_str(&value),
+        None => {
+          if !name.is_empty() {
+            self.unresolved.insert(name.to_string());
+          }
+          result.push_str(&rest[start..start + 2 + end + 2]);
+        }
+      }
+      rest = &after_open[end + 2..];
+    }
+    result.push_str(rest);
+    result
+  }
+
+  // Only substitutes inside keys and string values, so a value can't break the json structure
+  pub fn resolve_json(&mut self, value: &Value) -> Value {
+    match value {
+      Value::String(s) => Value::String(self.resolve(s)),
+      Value::Array(a) => Value::Array(a.iter().map(|v| self.resolve_json(v)).collect()),
+      Value::Object(o) => Value::Object(
+        o.iter()
+          .map(|(k, v)| (self.resolve(k), self.resolve_json(v)))
+          .collect(),
+      ),
+      other => other.clone(),
+    }
+  }
+
+  pub fn resolve_http_request(&mut self, input: &HttpRequest) -> HttpRequest {
+    let mut resolved = input.clone();
+    resolved.url = self.resolve(&input.url);
+    resolved.headers = input.headers.as_ref().map(|headers| {
+      headers
+        .iter()
+        .map(|(key, value)| (self.resolve(key), self.resolve(value)))
+        .collect()
+    });
+    resolved.body = input.body.as_ref().and_then(|body| match body {
+      RequestBody::JSON(j) => Some(RequestBody::JSON(self.resolve_json(j))),
+      RequestBody::FORM(fields) => Some(RequestBody::FORM(
+        fields
+          .iter()
+          .map(|field| FormField {
+            key: self.resolve(&field.key),
+            value: self.resolve(&field.value),
+            enabled: field.enabled,
+          })
+          .collect(),
+      )),
+      RequestBody::MULTIPART(parts) => Some(RequestBody::MULTIPART(
+        parts
+          .iter()
+          .map(|part| self.resolve_multipart_part(part))
+          .collect(),
+      )),
+      RequestBody::GRAPHQL(graphql) => Some(RequestBody::GRAPHQL(GraphQLBody {
+        query: self.resolve(&graphql.query),
+        variables: self.resolve(&graphql.variables),
+        operation_name: graphql.operation_name.as_ref().map(|n| self.resolve(n)),
+      })),
+      other => RequestBody::from_text(&other.body_type(), &self.resolve(&other.to_text())),
+    });
+    resolved.auth = self.resolve_auth(&input.auth);
+    resolved
+  }
+
+  fn resolve_auth(&mut self, auth: &RequestAuth) -> RequestAuth {
+    match auth {
+      RequestAuth::BEARER(token) => RequestAuth::BEARER(self.resolve(token)),
+      RequestAuth::APIKEY {
+        key,
+        value,
+        placement,
+      } => RequestAuth::APIKEY {
+        key: self.resolve(key),
+        value: self.resolve(value),
+        placement: *placement,
+      },
+      RequestAuth::BASIC { username, password } => RequestAuth::BASIC {
+        username: self.resolve(username),
+        password: self.resolve(password),
+      },
+      RequestAuth::DIGEST { username, password } => RequestAuth::DIGEST {
+        username: self.resolve(username),
+        password: self.resolve(password),
+      },
+      RequestAuth::AWSSIGV4(config) => RequestAuth::AWSSIGV4(AwsSigV4Auth {
+        access_key: self.resolve(&config.access_key),
+        secret_key: self.resolve(&config.secret_key),
+        session_token: self.resolve(&config.session_token),
+        region: self.resolve(&config.region),
+        service: self.resolve(&config.service),
+      }),
+      RequestAuth::JWT(config) => RequestAuth::JWT(JwtAuth {
+        secret: self.resolve(&config.secret),
+        payload: self.resolve(&config.payload),
+        ..config.clone()
+      }),
+      RequestAuth::OAUTH2(config) => RequestAuth::OAUTH2(self.resolve_oauth2_request(config)),
+      other => other.clone(),
+    }
+  }
+
+  fn resolve_multipart_part(&mut self, part: &MultipartPart) -> MultipartPart {
+    match part {
+      MultipartPart::Text {
+        name,
+        value,
+        enabled,
+      } => MultipartPart::Text {
+        name: self.resolve(name),
+        value: self.resolve(value),
+        enabled: *enabled,
+      },
+      MultipartPart::File {
+        name,
+        path,
+        filename,
+        content_type,
+        enabled,
+      } => MultipartPart::File {
+        name: self.resolve(name),
+        path: self.resolve(path),
+        filename: filename.as_ref().map(|f| self.resolve(f)),
+        content_type: content_type.as_ref().map(|c| self.resolve(c)),
+        enabled: *enabled,
+      },
+    }
+  }
+
+  pub fn resolve_oauth2_request(&mut self, input: &OAuth2Request) -> OAuth2Request {
+    let mut resolved = input.clone();
+    resolved.access_token_url = self.resolve(&input.access_token_url);
+    resolved.refresh_url = self.resolve(&input.refresh_url);
+    resolved.auth_url = self.resolve(&input.auth_url);
+    resolved.redirect_uri = self.resolve(&input.redirect_uri);
+    resolved.device_authorization_url = self.resolve(&input.device_authorization_url);
+    resolved.client_id = self.resolve(&input.client_id);
+    resolved.client_secret = self.resolve(&input.client_secret);
+    resolved.username = self.resolve(&input.username);
+    resolved.password = self.resolve(&input.password);
+    resolved.request.scope = self.resolve(&input.request.scope);
+    resolved.request.audience = self.resolve(&input.request.audience);
+    resolved
+  }
+
+  pub fn unresolved(&self) -> Vec<String> {
+    self.unresolved.iter().cloned().collect()
+  }
+
+  // Errors listing every variable that couldn't be resolved so far
+  pub fn check_unresolved(&self) -> anyhow::Result<()> {
+    if self.unresolved.is_empty() {
+      Ok(())
+    } else {
+      anyhow::bail!("Unresolved variables: {}", self.unresolved().join(", "))
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Option<String> {
+    if let Some(value) = self.values.get(name) {
+      return Some(value.clone());
+    }
+    match name {
+      "$guid" | "$randomUUID" => Some(uuid::Uuid::new_v4().to_string()),
+      "$timestamp" => Some(Utc::now().timestamp().to_string()),
+      "$isoTimestamp" => Some(Utc::now().to_rfc3339()),
+      "$randomInt" => Some((uuid::Uuid::new_v4().as_u128() % 1001).to_string()),
+      _ => None,
+    }
+  }
+}
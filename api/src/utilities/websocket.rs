@@ -0,0 +1,204 @@
+use base64::Engine as _;
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{net::TcpStream, sync::mpsc};
+use tokio_tungstenite::{
+  tungstenite::{client::IntoClientRequest, http::HeaderName, http::HeaderValue, Message},
+  MaybeTlsStream, WebSocketStream,
+};
+
+use crate::domain::{
+  header::Header,
+  websocket::{
+    WebSocketCommand, WebSocketDirection, WebSocketFrame, WebSocketMessage, WebSocketMessageKind,
+    WebSocketTranscript,
+  },
+};
+
+// An open connection and everything sent or received on it so far
+pub struct WebSocketConnection {
+  stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+  pub transcript: WebSocketTranscript,
+}
+
+// Opens a connection to a url whose variables are already resolved
+pub async fn connect(
+  url: &str,
+  headers: &[(String, String)],
+) -> anyhow::Result<WebSocketConnection> {
+  let mut request = url.into_client_request()?;
+  for (key, value) in headers {
+    request.headers_mut().insert(
+      HeaderName::from_bytes(key.as_bytes())?,
+      HeaderValue::from_str(value)?,
+    );
+  }
+  let (stream, response) = tokio_tungstenite::connect_async(request)
+    .await
+    .map_err(|e| anyhow::anyhow!("Could not connect to {}: {}", url, e))?;
+  let response_headers = response
+    .headers()
+    .iter()
+    .map(|(key, value)| Header {
+      key: key.to_string(),
+      value: value.to_str().unwrap_or_default().to_string(),
+    })
+    .collect();
+  let mut connection = WebSocketConnection {
+    stream,
+    transcript: WebSocketTranscript {
+      opened_at: Utc::now(),
+      response_headers,
+      messages: vec![],
+    },
+  };
+  connection.log(
+    WebSocketDirection::Event,
+    WebSocketMessageKind::Open,
+    format!("Connected to {}", url),
+  );
+  Ok(connection)
+}
+
+impl WebSocketConnection {
+  fn log(
+    &mut self,
+    direction: WebSocketDirection,
+    kind: WebSocketMessageKind,
+    data: String,
+  ) -> WebSocketMessage {
+    let message = WebSocketMessage::new(direction, kind, data);
+    self.transcript.messages.push(message.clone());
+    message
+  }
+
+  pub async fn send(&mut self, frame: WebSocketFrame) -> anyhow::Result<WebSocketMessage> {
+    let (message, kind, data) = match frame {
+      WebSocketFrame::Text(text) => (
+        Message::Text(text.clone()),
+        WebSocketMessageKind::Text,
+        text,
+      ),
+      WebSocketFrame::Json(json) => {
+        let value: serde_json::Value = serde_json::from_str(&json)
+          .map_err(|e| anyhow::anyhow!("Message is not valid json: {}", e))?;
+        let text = value.to_string();
+        (
+          Message::Text(text.clone()),
+          WebSocketMessageKind::Text,
+          text,
+        )
+      }
+      WebSocketFrame::Binary(bytes) => {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        (
+          Message::Binary(bytes),
+          WebSocketMessageKind::Binary,
+          encoded,
+        )
+      }
+    };
+    self.stream.send(message).await?;
+    Ok(self.log(WebSocketDirection::Sent, kind, data))
+  }
+
+  // Waits for the next text or binary message, None once the connection is closed. Pings are
+  // answered by the stream and aren't logged
+  pub async fn next_message(&mut self) -> Option<WebSocketMessage> {
+    loop {
+      let message = match self.stream.next().await? {
+        Ok(message) => message,
+        Err(e) => {
+          self.log(
+            WebSocketDirection::Event,
+            WebSocketMessageKind::Error,
+            e.to_string(),
+          );
+          return None;
+        }
+      };
+      return Some(match message {
+        Message::Text(text) => self.log(
+          WebSocketDirection::Received,
+          WebSocketMessageKind::Text,
+          text,
+        ),
+        Message::Binary(bytes) => self.log(
+          WebSocketDirection::Received,
+          WebSocketMessageKind::Binary,
+          base64::engine::general_purpose::STANDARD.encode(bytes),
+        ),
+        Message::Close(frame) => self.log(
+          WebSocketDirection::Event,
+          WebSocketMessageKind::Close,
+          match frame {
+            Some(frame) if frame.reason.is_empty() => format!("Closed ({})", frame.code),
+            Some(frame) => format!("Closed ({}): {}", frame.code, frame.reason),
+            None => String::from("Closed"),
+          },
+        ),
+        Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+      });
+    }
+  }
+
+  pub async fn close(&mut self) -> anyhow::Result<()> {
+    self.stream.close(None).await?;
+    Ok(())
+  }
+
+  // Sends what the commands ask for and reads incoming messages until either side closes the
+  // connection. `on_message` is called with every line as it is added to the transcript,
+  // which is returned at the end
+  pub async fn run(
+    mut self,
+    mut commands: mpsc::Receiver<WebSocketCommand>,
+    mut on_message: impl FnMut(&WebSocketMessage),
+  ) -> WebSocketTranscript {
+    if let Some(opened) = self.transcript.messages.last() {
+      on_message(opened);
+    }
+    let mut closing = false;
+    loop {
+      tokio::select! {
+        command = commands.recv(), if !closing => match command {
+          Some(WebSocketCommand::Send(frame)) => match self.send(frame).await {
+            Ok(sent) => on_message(&sent),
+            Err(e) => on_message(&self.log(
+              WebSocketDirection::Event,
+              WebSocketMessageKind::Error,
+              e.to_string(),
+            )),
+          },
+          // a dropped sender closes the connection too, the server's close frame ends the loop
+          Some(WebSocketCommand::Close) | None => {
+            closing = true;
+            if self.close().await.is_err() {
+              break;
+            }
+          }
+        },
+        message = self.next_message() => match message {
+          Some(message) => {
+            on_message(&message);
+            if message.kind == WebSocketMessageKind::Close {
+              break;
+            }
+          }
+          None => {
+            if let Some(error) = self
+              .transcript
+              .messages
+              .last()
+              .filter(|m| m.kind == WebSocketMessageKind::Error)
+            {
+              on_message(error);
+            }
+            break;
+          }
+        },
+      }
+    }
+    self.transcript
+  }
+}
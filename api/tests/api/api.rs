@@ -1,9 +1,20 @@
 use api::{
-  domain::environment::{EnvironmentFile, EnvironmentValue},
+  domain::{
+    auth::RequestAuth,
+    environment::{EnvironmentFile, EnvironmentValue},
+    request::{HttpRequest, RequestBody, RequestScripts, RequestTimeouts},
+  },
   PostieApi,
 };
 use reqwest::Url;
-use wiremock::Match;
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::{
+  matchers::{body_json, header, method},
+  Match, Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
 
 // wiremock matches dont include a raw url match? i think
 // i only saw path mainly. this is used to validate that a Url
@@ -11,11 +22,9 @@ use wiremock::Match;
 pub struct MockUrlMatcher(String);
 impl Match for MockUrlMatcher {
   fn matches(&self, request: &wiremock::Request) -> bool {
-    if request.url == Url::parse(&self.0).unwrap() {
-      true
-    } else {
-      false
-    }
+    // the mock server reports its host as localhost, so only compare what the client controls
+    let expected = Url::parse(&self.0).unwrap();
+    request.url.path() == expected.path() && request.url.query() == expected.query()
   }
 }
 
@@ -48,39 +57,167 @@ fn returns_base_url_if_env_vars_dont_exist() {
   assert_eq!(converted_url, "{{BOGUS}}/json");
 }
 
-// TODO - figure out how to make this test work to validate the substitution when it is a private
-// method. Goal is to validate the url the reqest client is using.
-//#[tokio::test]
-//async fn it_substitutes_env_vars_into_the_url_and_returns_200() {
-//    let test_app = spawn_test_app().await;
-//    let environment = EnvironmentFile {
-//        id: String::from("id"),
-//        name: String::from("some environment"),
-//        values: Some(vec![EnvironmentValue {
-//            key: String::from("HOST_URL"),
-//            value: String::from("https://httpbin.org"),
-//            r#type: String::from("default"),
-//            enabled: true,
-//        }]),
-//    };
-//    let raw_url = String::from("{{HOST_URL}}/json");
-//    let input = api::HttpRequest {
-//        id: Uuid::new_v4(),
-//        name: None,
-//        method: api::HttpMethod::GET,
-//        url: raw_url,
-//        headers: None,
-//        body: None,
-//        environment: Some(environment),
-//    };
-//    // Given a request to the given url, return a mock 200. This should only work if substitution
-//    // works correctly
-//    Mock::given(method("GET"))
-//        .and(MockUrlMatcher(String::from("https://httpbin.org/json").try_into().unwrap()))
-//        .respond_with(ResponseTemplate::new(200))
-//        .expect(1)
-//        .mount(&test_app.test_server)
-//        .await;
-//
-//    let _ = PostieApi::make_request(&test_app.app,input);
-//}
+fn env_value(key: &str, value: &str, enabled: bool) -> EnvironmentValue {
+  EnvironmentValue {
+    key: String::from(key),
+    value: String::from(value),
+    r#type: String::from("default"),
+    enabled,
+  }
+}
+
+#[test]
+fn disabled_env_vars_are_not_substituted() {
+  let environment = EnvironmentFile {
+    id: String::from("id"),
+    name: String::from("some environment"),
+    values: Some(vec![env_value("HOST_URL", "https://httpbin.org", false)]),
+  };
+  let raw_url = String::from("{{HOST_URL}}/json");
+  let converted_url = PostieApi::substitute_variables_in_url(&environment, raw_url);
+  assert_eq!(converted_url, "{{HOST_URL}}/json");
+}
+
+#[tokio::test]
+async fn it_substitutes_env_vars_into_the_url_headers_and_body() {
+  let mut test_app = spawn_test_app().await;
+  let environment = EnvironmentFile {
+    id: String::from("id"),
+    name: String::from("some environment"),
+    values: Some(vec![
+      env_value("HOST_URL", &test_app._test_server.uri(), true),
+      env_value("TOKEN", "secret-token", true),
+      env_value("USER", "postie", true),
+    ]),
+  };
+  let input = HttpRequest {
+    tab_id: Uuid::new_v4(),
+    id: Uuid::new_v4(),
+    name: None,
+    method: api::domain::request::HttpMethod::POST,
+    url: String::from("{{HOST_URL}}/users?name={{USER}}"),
+    headers: Some(vec![(
+      String::from("Authorization"),
+      String::from("Bearer {{TOKEN}}"),
+    )]),
+    body: Some(RequestBody::JSON(json!({"name": "{{USER}}", "age": 3}))),
+    environment,
+    variables: vec![],
+    scripts: RequestScripts::default(),
+    assertions: vec![],
+    extract: vec![],
+    timeouts: RequestTimeouts::default(),
+    auth: RequestAuth::NONE,
+  };
+  // Only matches if every part of the request was substituted
+  Mock::given(method("POST"))
+    .and(MockUrlMatcher(format!(
+      "{}/users?name=postie",
+      test_app._test_server.uri()
+    )))
+    .and(header("Authorization", "Bearer secret-token"))
+    .and(body_json(json!({"name": "postie", "age": 3})))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true})))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.status_code, 200);
+}
+
+#[tokio::test]
+async fn it_refuses_to_send_requests_with_unresolved_variables() {
+  let mut test_app = spawn_test_app().await;
+  let environment = EnvironmentFile {
+    id: String::from("id"),
+    name: String::from("some environment"),
+    values: Some(vec![
+      env_value("HOST_URL", &test_app._test_server.uri(), true),
+      env_value("TOKEN", "secret-token", false),
+    ]),
+  };
+  let input = HttpRequest {
+    tab_id: Uuid::new_v4(),
+    id: Uuid::new_v4(),
+    name: None,
+    method: api::domain::request::HttpMethod::GET,
+    url: String::from("{{HOST_URL}}/json"),
+    headers: Some(vec![(
+      String::from("Authorization"),
+      String::from("Bearer {{TOKEN}}"),
+    )]),
+    body: Some(RequestBody::JSON(json!({"id": "{{MISSING}}"}))),
+    environment,
+    variables: vec![],
+    scripts: RequestScripts::default(),
+    assertions: vec![],
+    extract: vec![],
+    timeouts: RequestTimeouts::default(),
+    auth: RequestAuth::NONE,
+  };
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(0)
+    .mount(&test_app._test_server)
+    .await;
+
+  let err = test_app.app.send_http_request(input).await.unwrap_err();
+
+  assert_eq!(err.to_string(), "Unresolved variables: MISSING, TOKEN");
+  assert!(test_app
+    .app
+    .load_request_response_items()
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[tokio::test]
+async fn globals_are_used_when_the_environment_doesnt_define_a_variable() {
+  let mut test_app = spawn_test_app().await;
+  test_app
+    .app
+    .save_globals(&[
+      env_value("HOST_URL", &test_app._test_server.uri(), true),
+      env_value("resource", "global", true),
+      env_value("id", "global", true),
+    ])
+    .await
+    .unwrap();
+  let environment = EnvironmentFile {
+    id: String::from("id"),
+    name: String::from("some environment"),
+    values: Some(vec![env_value("id", "environment", true)]),
+  };
+  let input = HttpRequest {
+    tab_id: Uuid::new_v4(),
+    id: Uuid::new_v4(),
+    name: None,
+    method: api::domain::request::HttpMethod::GET,
+    url: String::from("{{HOST_URL}}/{{resource}}/{{id}}"),
+    headers: None,
+    body: None,
+    environment,
+    variables: vec![],
+    scripts: RequestScripts::default(),
+    assertions: vec![],
+    extract: vec![],
+    timeouts: RequestTimeouts::default(),
+    auth: RequestAuth::NONE,
+  };
+  Mock::given(method("GET"))
+    .and(MockUrlMatcher(format!(
+      "{}/global/environment",
+      test_app._test_server.uri()
+    )))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.status_code, 200);
+}
@@ -0,0 +1,99 @@
+use api::domain::{collection::Collection, environment::EnvironmentFile, request::HttpRequest};
+use serde_json::json;
+use wiremock::{
+  matchers::{method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn collection_with_assertions(host: &str) -> Collection {
+  serde_json::from_value(json!({
+    "info": { "_postman_id": "assertions", "name": "assertions" },
+    "item": [
+      {
+        "id": "req",
+        "name": "req",
+        "request": { "method": "GET", "url": { "raw": format!("{host}/users/7") } },
+        "assertions": [
+          { "type": "status_equals", "status": 200 },
+          { "type": "status_in_range", "min": 400, "max": 499 },
+          { "type": "header_present", "name": "x-request-id" },
+          { "type": "header_matches", "name": "Content-Type", "pattern": "^application/json" },
+          { "type": "json_path_equals", "path": "$.user.id", "value": 7 },
+          { "type": "json_path_equals", "path": "$.user.name", "value": "someone else" },
+          { "type": "body_contains", "text": "postie" },
+          { "type": "response_time_under", "ms": 60000 }
+        ]
+      }
+    ]
+  }))
+  .unwrap()
+}
+
+#[tokio::test]
+async fn assertions_are_evaluated_against_the_response() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/users/7"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_json(json!({"user": {"id": 7, "name": "postie"}}))
+        .insert_header("content-type", "application/json; charset=utf-8"),
+    )
+    .mount(&test_app._test_server)
+    .await;
+  let collection = collection_with_assertions(&test_app._test_server.uri());
+  let input = HttpRequest::from_collection_item(
+    &collection,
+    collection.requests()[0],
+    EnvironmentFile::default(),
+  );
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  let results: Vec<(&str, bool, Option<&str>)> = res
+    .test_results
+    .iter()
+    .map(|t| (t.name.as_str(), t.passed, t.message.as_deref()))
+    .collect();
+  assert_eq!(
+    results,
+    vec![
+      ("Status is 200", true, None),
+      ("Status is between 400 and 499", false, Some("got 200")),
+      (
+        "Header x-request-id is present",
+        false,
+        Some("header x-request-id is missing")
+      ),
+      ("Header Content-Type matches ^application/json", true, None),
+      ("$.user.id equals 7", true, None),
+      (
+        "$.user.name equals \"someone else\"",
+        false,
+        Some("got \"postie\"")
+      ),
+      ("Body contains \"postie\"", true, None),
+      ("Response time is under 60000 ms", true, None),
+    ]
+  );
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  assert_eq!(history[0].test_results, res.test_results);
+}
+
+#[tokio::test]
+async fn assertions_are_saved_with_the_collection() {
+  let mut test_app = spawn_test_app().await;
+  let collection = collection_with_assertions("https://example.com");
+
+  test_app
+    .app
+    .save_collection(collection.clone())
+    .await
+    .unwrap();
+
+  let saved = test_app.app.load_collections().await.unwrap()[0].clone();
+  assert_eq!(saved, collection);
+  assert_eq!(saved.requests()[0].assertions.as_ref().unwrap().len(), 8);
+}
@@ -0,0 +1,413 @@
+use api::domain::{
+  collection::{Collection, RequestBody as CollectionBody},
+  environment::EnvironmentFile,
+  request::{BodyType, FormField, HttpMethod, HttpRequest, MultipartPart, RequestBody},
+  tab::Tab,
+};
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::{
+  matchers::{body_bytes, body_string, header, method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn post(url: String, headers: Vec<(String, String)>, body: RequestBody) -> HttpRequest {
+  HttpRequest {
+    method: HttpMethod::POST,
+    url,
+    headers: Some(headers),
+    body: Some(body),
+    variables: vec![("name".into(), "postie".into())],
+    ..Default::default()
+  }
+}
+
+#[tokio::test]
+async fn raw_bodies_are_sent_verbatim_with_their_content_type() {
+  let mut test_app = spawn_test_app().await;
+  let cases = [
+    (
+      RequestBody::TEXT("hello {{name}}".into()),
+      "text/plain",
+      "hello postie",
+    ),
+    (
+      RequestBody::XML("<user>{{name}}</user>".into()),
+      "application/xml",
+      "<user>postie</user>",
+    ),
+    (
+      RequestBody::HTML("<p>{{name}}</p>".into()),
+      "text/html",
+      "<p>postie</p>",
+    ),
+    (
+      RequestBody::JAVASCRIPT("let name = '{{name}}';".into()),
+      "application/javascript",
+      "let name = 'postie';",
+    ),
+  ];
+  for (index, (_, content_type, sent)) in cases.iter().enumerate() {
+    Mock::given(method("POST"))
+      .and(path(format!("/raw/{index}")))
+      .and(header("content-type", *content_type))
+      .and(body_string(*sent))
+      .respond_with(ResponseTemplate::new(200))
+      .expect(1)
+      .mount(&test_app._test_server)
+      .await;
+  }
+
+  for (index, (body, _, _)) in cases.into_iter().enumerate() {
+    let url = format!("{}/raw/{index}", test_app._test_server.uri());
+    let res = test_app
+      .app
+      .send_http_request(post(url, vec![], body))
+      .await
+      .unwrap();
+    assert_eq!(res.status_code, 200);
+  }
+}
+
+#[tokio::test]
+async fn a_content_type_header_overrides_the_body_default() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(header("content-type", "application/soap+xml"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = post(
+    test_app._test_server.uri(),
+    vec![("Content-Type".into(), "application/soap+xml".into())],
+    RequestBody::XML("<envelope/>".into()),
+  );
+
+  test_app.app.send_http_request(input).await.unwrap();
+}
+
+#[tokio::test]
+async fn binary_bodies_send_the_file_contents() {
+  let mut test_app = spawn_test_app().await;
+  let bytes = vec![0u8, 159, 146, 150, 255];
+  let file = std::env::temp_dir().join(format!("postie-{}.bin", Uuid::new_v4()));
+  std::fs::write(&file, &bytes).unwrap();
+  Mock::given(method("POST"))
+    .and(header("content-type", "application/octet-stream"))
+    .and(body_bytes(bytes))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = post(
+    test_app._test_server.uri(),
+    vec![],
+    RequestBody::BINARY(file.to_string_lossy().into()),
+  );
+
+  let res = test_app.app.send_http_request(input).await;
+  std::fs::remove_file(&file).unwrap();
+  assert_eq!(res.unwrap().status_code, 200);
+
+  let missing = post(
+    test_app._test_server.uri(),
+    vec![],
+    RequestBody::BINARY("/does/not/exist.bin".into()),
+  );
+  let err = test_app.app.send_http_request(missing).await.unwrap_err();
+  assert!(err.to_string().starts_with("Could not read body file"));
+}
+
+#[test]
+fn postman_body_modes_are_mapped_on_import() {
+  let collection: Collection = serde_json::from_value(json!({
+    "info": { "_postman_id": "bodies", "name": "bodies" },
+    "item": [
+      { "id": "json", "name": "json", "request": { "method": "POST", "url": { "raw": "u" },
+        "body": { "mode": "raw", "raw": "{\"a\": 1}", "options": { "raw": { "language": "json" } } } } },
+      { "id": "vars", "name": "vars", "request": { "method": "POST", "url": { "raw": "u" },
+        "body": { "mode": "raw", "raw": "{\"a\": {{a}}}", "options": { "raw": { "language": "json" } } } } },
+      { "id": "xml", "name": "xml", "request": { "method": "POST", "url": { "raw": "u" },
+        "body": { "mode": "raw", "raw": "<a/>", "options": { "raw": { "language": "xml" } } } } },
+      { "id": "text", "name": "text", "request": { "method": "POST", "url": { "raw": "u" },
+        "body": { "mode": "raw", "raw": "plain" } } },
+      { "id": "file", "name": "file", "request": { "method": "POST", "url": { "raw": "u" },
+        "body": { "mode": "file", "file": { "src": "/tmp/upload.bin" } } } }
+    ]
+  }))
+  .unwrap();
+
+  let bodies: Vec<(BodyType, String)> = collection
+    .requests()
+    .into_iter()
+    .map(|item| {
+      let body = HttpRequest::from_collection_item(&collection, item, EnvironmentFile::default())
+        .body
+        .unwrap();
+      (body.body_type(), body.to_text())
+    })
+    .collect();
+
+  assert_eq!(
+    bodies,
+    vec![
+      (BodyType::JSON, "{\"a\":1}".to_string()),
+      (BodyType::TEXT, "{\"a\": {{a}}}".to_string()),
+      (BodyType::XML, "<a/>".to_string()),
+      (BodyType::TEXT, "plain".to_string()),
+      (BodyType::BINARY, "/tmp/upload.bin".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn bodies_round_trip_through_postman_bodies() {
+  let bodies = vec![
+    RequestBody::FORM(vec![
+      FormField::new("a", "1"),
+      FormField {
+        key: "b".into(),
+        value: "two words".into(),
+        enabled: false,
+      },
+    ]),
+    RequestBody::TEXT("plain".into()),
+    RequestBody::XML("<a/>".into()),
+    RequestBody::HTML("<p/>".into()),
+    RequestBody::JAVASCRIPT("1 + 1".into()),
+    RequestBody::BINARY("/tmp/upload.bin".into()),
+  ];
+
+  for body in bodies {
+    let exported = CollectionBody::from_request_body(&body);
+    let imported = exported.to_request_body().unwrap();
+    assert_eq!(imported.body_type(), body.body_type());
+    assert_eq!(imported.to_text(), body.to_text());
+  }
+}
+
+#[tokio::test]
+async fn tabs_keep_their_body_type() {
+  let mut test_app = spawn_test_app().await;
+  let tab = Tab {
+    req_body: "<a/>".into(),
+    body_type: BodyType::XML,
+    ..Default::default()
+  };
+
+  test_app.app.save_tab(&tab).await.unwrap();
+
+  let saved = test_app
+    .app
+    .load_tabs()
+    .await
+    .unwrap()
+    .into_iter()
+    .find(|t| t.id == tab.id)
+    .unwrap();
+  assert_eq!(saved.body_type, BodyType::XML);
+  assert_eq!(saved.req_body, "<a/>");
+}
+
+#[tokio::test]
+async fn multipart_bodies_send_text_fields_and_files() {
+  let mut test_app = spawn_test_app().await;
+  let file = std::env::temp_dir().join(format!("postie-{}.csv", Uuid::new_v4()));
+  std::fs::write(&file, "id,name\n1,postie\n").unwrap();
+  Mock::given(method("POST"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = post(
+    test_app._test_server.uri(),
+    vec![],
+    RequestBody::MULTIPART(vec![
+      MultipartPart::Text {
+        name: "greeting".into(),
+        value: "hello {{name}}".into(),
+        enabled: true,
+      },
+      MultipartPart::Text {
+        name: "skipped".into(),
+        value: "nope".into(),
+        enabled: false,
+      },
+      MultipartPart::File {
+        name: "upload".into(),
+        path: file.to_string_lossy().into(),
+        filename: Some("users.csv".into()),
+        content_type: Some("text/csv".into()),
+        enabled: true,
+      },
+    ]),
+  );
+
+  let res = test_app.app.send_http_request(input).await;
+  std::fs::remove_file(&file).unwrap();
+  assert_eq!(res.unwrap().status_code, 200);
+
+  let received = &test_app._test_server.received_requests().await.unwrap()[0];
+  let content_type = received
+    .headers
+    .get("content-type")
+    .unwrap()
+    .to_str()
+    .unwrap();
+  assert!(content_type.starts_with("multipart/form-data; boundary="));
+  let body = String::from_utf8_lossy(&received.body);
+  assert!(body.contains("name=\"greeting\"\r\n\r\nhello postie\r\n"));
+  assert!(body.contains("name=\"upload\"; filename=\"users.csv\"\r\nContent-Type: text/csv"));
+  assert!(body.contains("id,name\n1,postie\n"));
+  assert!(!body.contains("skipped"));
+}
+
+#[test]
+fn postman_formdata_is_imported_as_multipart() {
+  let body: CollectionBody = serde_json::from_value(json!({
+    "mode": "formdata",
+    "formdata": [
+      { "key": "count", "value": 3, "type": "text" },
+      { "key": "avatar", "type": "file", "src": ["/tmp/a.png", "/tmp/b.png"], "contentType": "image/png" },
+      { "key": "skipped", "value": "x", "type": "text", "disabled": true },
+      { "key": "empty", "type": "file", "src": null }
+    ]
+  }))
+  .unwrap();
+
+  let Some(RequestBody::MULTIPART(parts)) = body.to_request_body() else {
+    panic!("expected a multipart body");
+  };
+
+  assert_eq!(
+    parts,
+    vec![
+      MultipartPart::Text {
+        name: "count".into(),
+        value: "3".into(),
+        enabled: true,
+      },
+      MultipartPart::File {
+        name: "avatar".into(),
+        path: "/tmp/a.png".into(),
+        filename: None,
+        content_type: Some("image/png".into()),
+        enabled: true,
+      },
+      MultipartPart::Text {
+        name: "skipped".into(),
+        value: "x".into(),
+        enabled: false,
+      },
+      MultipartPart::File {
+        name: "empty".into(),
+        path: "".into(),
+        filename: None,
+        content_type: None,
+        enabled: true,
+      },
+    ]
+  );
+  let exported = CollectionBody::from_request_body(&RequestBody::MULTIPART(parts.clone()));
+  let Some(RequestBody::MULTIPART(reimported)) = exported.to_request_body() else {
+    panic!("expected a multipart body");
+  };
+  assert_eq!(reimported, parts);
+}
+
+#[tokio::test]
+async fn form_bodies_send_enabled_fields_in_order() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(header("content-type", "application/x-www-form-urlencoded"))
+    .and(body_string("user=postie&note=a+b%262&user=again"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = post(
+    test_app._test_server.uri(),
+    vec![],
+    RequestBody::FORM(vec![
+      FormField::new("user", "{{name}}"),
+      FormField {
+        key: "skipped".into(),
+        value: "x".into(),
+        enabled: false,
+      },
+      FormField::new("note", "a b&2"),
+      FormField::new("user", "again"),
+    ]),
+  );
+
+  let res = test_app.app.send_http_request(input).await;
+
+  assert_eq!(res.unwrap().status_code, 200);
+}
+
+#[test]
+fn postman_urlencoded_keeps_disabled_fields() {
+  let body: CollectionBody = serde_json::from_value(json!({
+    "mode": "urlencoded",
+    "urlencoded": [
+      { "key": "a b", "value": "1&2", "type": "text" },
+      { "key": "off", "value": "x", "disabled": true }
+    ]
+  }))
+  .unwrap();
+
+  let imported = body.to_request_body().unwrap();
+
+  assert_eq!(
+    imported,
+    RequestBody::FORM(vec![
+      FormField::new("a b", "1&2"),
+      FormField {
+        key: "off".into(),
+        value: "x".into(),
+        enabled: false,
+      },
+    ])
+  );
+  let exported = CollectionBody::from_request_body(&imported);
+  let params = exported.urlencoded.unwrap();
+  assert_eq!(params.len(), 2);
+  assert!(params[1].disabled);
+}
+
+#[tokio::test]
+async fn json_with_unquoted_variables_is_sent_as_written() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(body_string(r#"{"count": 3, "name": "postie"}"#))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let body = RequestBody::from_text(
+    &BodyType::JSON,
+    r#"{"count": {{count}}, "name": "{{name}}"}"#,
+  );
+  let mut input = post(test_app._test_server.uri(), vec![], body.unwrap());
+  input.variables.push(("count".into(), "3".into()));
+
+  let res = test_app.app.send_http_request(input).await;
+
+  assert_eq!(res.unwrap().status_code, 200);
+}
+
+#[test]
+fn encoded_form_text_is_read_as_fields() {
+  let body = RequestBody::from_text(&BodyType::FORM, "a=1&b=two+words");
+
+  assert_eq!(
+    body,
+    Some(RequestBody::FORM(vec![
+      FormField::new("a", "1"),
+      FormField::new("b", "two words"),
+    ]))
+  );
+}
@@ -0,0 +1,284 @@
+use std::io::{Read, Write};
+
+use api::domain::{
+  client::{ClientCertificate, ClientSettings, RedirectPolicy},
+  request::HttpRequest,
+};
+use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+use wiremock::{
+  matchers::{method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::{fixture_path, spawn_test_app};
+
+fn get(url: String) -> HttpRequest {
+  HttpRequest {
+    url,
+    ..Default::default()
+  }
+}
+
+fn cert_path(name: &str) -> String {
+  fixture_path(&format!("certs/{}", name))
+}
+
+// Answers every connection over https with a certificate signed by certs/ca.pem, asking for a
+// client certificate signed by it too when `client_auth` is set. Returns its url
+fn spawn_tls_server(client_auth: bool) -> String {
+  let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+  acceptor
+    .set_private_key_file(cert_path("server.key"), SslFiletype::PEM)
+    .unwrap();
+  acceptor
+    .set_certificate_chain_file(cert_path("server.pem"))
+    .unwrap();
+  if client_auth {
+    acceptor.set_ca_file(cert_path("ca.pem")).unwrap();
+    acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+  }
+  let acceptor = acceptor.build();
+  let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+  let url = format!(
+    "https://127.0.0.1:{}",
+    listener.local_addr().unwrap().port()
+  );
+  std::thread::spawn(move || {
+    for stream in listener.incoming().flatten() {
+      let Ok(mut stream) = acceptor.accept(stream) else {
+        continue;
+      };
+      let mut request = [0u8; 4096];
+      let _ = stream.read(&mut request);
+      let _ = stream.write_all(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 6\r\nconnection: close\r\n\r\nsecure",
+      );
+      let _ = stream.shutdown();
+    }
+  });
+  url
+}
+
+fn trusting_the_test_ca() -> ClientSettings {
+  ClientSettings {
+    ca_certificates: vec![cert_path("ca.pem")],
+    ..Default::default()
+  }
+}
+
+#[tokio::test]
+async fn servers_signed_by_an_added_ca_are_trusted() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_tls_server(false);
+
+  let untrusted = test_app.app.send_http_request(get(url.clone())).await;
+  test_app
+    .app
+    .save_client_settings(&trusting_the_test_ca())
+    .await
+    .unwrap();
+  let trusted = test_app.app.send_http_request(get(url)).await.unwrap();
+
+  assert!(untrusted.is_err());
+  assert_eq!(trusted.data.to_raw_string(), "secure");
+  assert_eq!(
+    test_app.app.load_client_settings().await.unwrap(),
+    trusting_the_test_ca()
+  );
+}
+
+#[tokio::test]
+async fn certificates_are_not_verified_for_insecure_hosts() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_tls_server(false);
+  let mut settings = ClientSettings {
+    insecure_hosts: vec!["*.example.com".into()],
+    ..Default::default()
+  };
+  test_app.app.save_client_settings(&settings).await.unwrap();
+
+  let other_host = test_app.app.send_http_request(get(url.clone())).await;
+  settings.insecure_hosts.push("127.0.0.1".into());
+  test_app.app.save_client_settings(&settings).await.unwrap();
+  let insecure_host = test_app.app.send_http_request(get(url)).await;
+
+  assert!(other_host.is_err());
+  assert_eq!(insecure_host.unwrap().status_code, 200);
+}
+
+#[tokio::test]
+async fn client_certificates_are_presented_to_their_host() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_tls_server(true);
+  let with_certificate_for = |host: &str| ClientSettings {
+    client_certificates: vec![ClientCertificate {
+      host: host.into(),
+      certificate: cert_path("client.pem"),
+      key: cert_path("client.key"),
+      password: "".into(),
+    }],
+    ..trusting_the_test_ca()
+  };
+
+  test_app
+    .app
+    .save_client_settings(&with_certificate_for("other.host"))
+    .await
+    .unwrap();
+  let without = test_app.app.send_http_request(get(url.clone())).await;
+  test_app
+    .app
+    .save_client_settings(&with_certificate_for("127.0.0.1"))
+    .await
+    .unwrap();
+  let with = test_app.app.send_http_request(get(url)).await;
+
+  assert!(without.is_err());
+  assert_eq!(with.unwrap().data.to_raw_string(), "secure");
+}
+
+#[tokio::test]
+async fn requests_go_through_the_proxy_unless_their_host_is_excluded() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/users"))
+    .respond_with(ResponseTemplate::new(200).set_body_string("proxied"))
+    .mount(&test_app._test_server)
+    .await;
+  test_app
+    .app
+    .save_client_settings(&ClientSettings {
+      http_proxy: test_app._test_server.uri(),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+
+  let proxied = test_app
+    .app
+    .send_http_request(get("http://api.example.invalid/users".into()))
+    .await
+    .unwrap();
+  // nothing listens on the proxy, so only a request that skips it gets an answer
+  test_app
+    .app
+    .save_client_settings(&ClientSettings {
+      http_proxy: "http://127.0.0.1:9".into(),
+      no_proxy: "localhost, 127.0.0.1".into(),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  let direct = test_app
+    .app
+    .send_http_request(get(format!("{}/users", test_app._test_server.uri())))
+    .await
+    .unwrap();
+
+  assert_eq!(proxied.data.to_raw_string(), "proxied");
+  assert_eq!(direct.data.to_raw_string(), "proxied");
+}
+
+#[tokio::test]
+async fn redirects_follow_the_policy() {
+  let mut test_app = spawn_test_app().await;
+  let server = &test_app._test_server;
+  for (from, to) in [("/start", "/middle"), ("/middle", "/end")] {
+    Mock::given(path(from))
+      .respond_with(ResponseTemplate::new(302).insert_header("location", to))
+      .mount(server)
+      .await;
+  }
+  Mock::given(path("/end"))
+    .respond_with(ResponseTemplate::new(200).set_body_string("done"))
+    .mount(server)
+    .await;
+  let start = format!("{}/start", server.uri());
+  let mut statuses = vec![];
+
+  for redirects in [
+    RedirectPolicy::FOLLOW,
+    RedirectPolicy::LIMIT(1),
+    RedirectPolicy::OFF,
+  ] {
+    test_app
+      .app
+      .save_client_settings(&ClientSettings {
+        redirects,
+        ..Default::default()
+      })
+      .await
+      .unwrap();
+    let res = test_app.app.send_http_request(get(start.clone())).await;
+    statuses.push(res.map(|res| res.status_code).ok());
+  }
+
+  assert_eq!(statuses, vec![Some(200), None, Some(302)]);
+}
+
+#[tokio::test]
+async fn redirects_leaving_an_insecure_host_are_not_followed() {
+  let mut test_app = spawn_test_app().await;
+  let server = &test_app._test_server;
+  let port = server.address().port();
+  Mock::given(path("/same"))
+    .respond_with(ResponseTemplate::new(302).insert_header("location", "/end"))
+    .mount(server)
+    .await;
+  Mock::given(path("/other"))
+    .respond_with(
+      ResponseTemplate::new(302).insert_header("location", format!("http://localhost:{port}/end")),
+    )
+    .mount(server)
+    .await;
+  Mock::given(path("/end"))
+    .respond_with(ResponseTemplate::new(200))
+    .mount(server)
+    .await;
+  test_app
+    .app
+    .save_client_settings(&ClientSettings {
+      insecure_hosts: vec!["127.0.0.1".into()],
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+
+  let same = test_app
+    .app
+    .send_http_request(get(format!("http://127.0.0.1:{port}/same")))
+    .await
+    .unwrap();
+  let other = test_app
+    .app
+    .send_http_request(get(format!("http://127.0.0.1:{port}/other")))
+    .await
+    .unwrap();
+
+  assert_eq!(same.status_code, 200);
+  assert_eq!(other.status_code, 302);
+}
+
+#[tokio::test]
+async fn unreadable_certificates_are_reported() {
+  let mut test_app = spawn_test_app().await;
+  test_app
+    .app
+    .save_client_settings(&ClientSettings {
+      ca_certificates: vec!["/no/such/ca.pem".into()],
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+
+  let err = test_app
+    .app
+    .send_http_request(get(test_app._test_server.uri()))
+    .await
+    .err()
+    .unwrap();
+
+  assert!(err
+    .to_string()
+    .starts_with("Could not read CA certificate /no/such/ca.pem"));
+}
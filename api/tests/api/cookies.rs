@@ -0,0 +1,293 @@
+use api::{
+  domain::{cookie::Cookie, environment::EnvironmentFile, request::HttpRequest},
+  utilities::cookies::{cookie_header, parse_set_cookie, store_cookie},
+};
+use chrono::{Duration, Utc};
+use reqwest::Url;
+use uuid::Uuid;
+use wiremock::{
+  matchers::{header, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::{spawn_test_app, TestApp};
+
+fn get(url: String, environment: EnvironmentFile) -> HttpRequest {
+  HttpRequest {
+    url,
+    environment,
+    ..Default::default()
+  }
+}
+
+// /login sets a session cookie, /me only answers 200 when it gets it back
+async fn mount_login(test_app: &TestApp) {
+  let server = &test_app._test_server;
+  Mock::given(path("/login"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .append_header("set-cookie", "session=abc; Path=/; HttpOnly")
+        .append_header("set-cookie", "theme=dark; Max-Age=3600"),
+    )
+    .mount(server)
+    .await;
+  Mock::given(path("/me"))
+    .and(header("cookie", "session=abc; theme=dark"))
+    .respond_with(ResponseTemplate::new(200))
+    .with_priority(1)
+    .mount(server)
+    .await;
+  Mock::given(path("/me"))
+    .respond_with(ResponseTemplate::new(401))
+    .mount(server)
+    .await;
+}
+
+fn url(url: &str) -> Url {
+  Url::parse(url).unwrap()
+}
+
+#[tokio::test]
+async fn cookies_set_by_a_response_are_sent_back() {
+  let mut test_app = spawn_test_app().await;
+  mount_login(&test_app).await;
+  let uri = test_app._test_server.uri();
+
+  let before = test_app
+    .app
+    .send_http_request(get(format!("{}/me", uri), EnvironmentFile::default()))
+    .await
+    .unwrap();
+  let login = test_app
+    .app
+    .send_http_request(get(format!("{}/login", uri), EnvironmentFile::default()))
+    .await
+    .unwrap();
+  let after = test_app
+    .app
+    .send_http_request(get(format!("{}/me", uri), EnvironmentFile::default()))
+    .await
+    .unwrap();
+
+  assert_eq!(before.status_code, 401);
+  let set: Vec<(&str, &str, bool)> = login
+    .cookies
+    .iter()
+    .map(|c| (c.name.as_str(), c.value.as_str(), c.http_only))
+    .collect();
+  assert_eq!(
+    set,
+    vec![("session", "abc", true), ("theme", "dark", false)]
+  );
+  assert_eq!(after.status_code, 200);
+}
+
+#[tokio::test]
+async fn cookies_set_after_a_redirect_belong_to_where_it_ended() {
+  let mut test_app = spawn_test_app().await;
+  let port = test_app._test_server.address().port();
+  Mock::given(path("/start"))
+    .respond_with(ResponseTemplate::new(302).append_header(
+      "location",
+      format!("http://localhost:{}/app/home", port).as_str(),
+    ))
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(path("/app/home"))
+    .respond_with(ResponseTemplate::new(200).append_header("set-cookie", "session=abc"))
+    .mount(&test_app._test_server)
+    .await;
+
+  let res = test_app
+    .app
+    .send_http_request(get(
+      format!("{}/start", test_app._test_server.uri()),
+      EnvironmentFile::default(),
+    ))
+    .await
+    .unwrap();
+
+  assert_eq!(res.status_code, 200);
+  let set: Vec<(&str, &str)> = res
+    .cookies
+    .iter()
+    .map(|c| (c.domain.as_str(), c.path.as_str()))
+    .collect();
+  assert_eq!(set, vec![("localhost", "/app")]);
+}
+
+#[tokio::test]
+async fn each_environment_has_its_own_cookies() {
+  let mut test_app = spawn_test_app().await;
+  mount_login(&test_app).await;
+  let uri = test_app._test_server.uri();
+  let staging = EnvironmentFile {
+    id: Uuid::new_v4().to_string(),
+    name: "staging".into(),
+    values: Some(vec![]),
+  };
+  test_app
+    .app
+    .save_environment(staging.clone())
+    .await
+    .unwrap();
+
+  test_app
+    .app
+    .send_http_request(get(format!("{}/login", uri), staging.clone()))
+    .await
+    .unwrap();
+  let without = test_app
+    .app
+    .send_http_request(get(format!("{}/me", uri), EnvironmentFile::default()))
+    .await
+    .unwrap();
+  let with = test_app
+    .app
+    .send_http_request(get(format!("{}/me", uri), staging.clone()))
+    .await
+    .unwrap();
+
+  assert_eq!(without.status_code, 401);
+  assert_eq!(with.status_code, 200);
+  assert_eq!(test_app.app.load_cookies(&staging).await.unwrap().len(), 2);
+  assert!(test_app
+    .app
+    .load_cookies(&EnvironmentFile::default())
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[tokio::test]
+async fn cookies_the_request_sets_itself_win() {
+  let mut test_app = spawn_test_app().await;
+  mount_login(&test_app).await;
+  let uri = test_app._test_server.uri();
+  test_app
+    .app
+    .send_http_request(get(format!("{}/login", uri), EnvironmentFile::default()))
+    .await
+    .unwrap();
+  let mut request = get(format!("{}/me", uri), EnvironmentFile::default());
+  request.headers = Some(vec![("Cookie".into(), "session=other".into())]);
+
+  let res = test_app.app.send_http_request(request).await.unwrap();
+
+  assert_eq!(res.status_code, 401);
+}
+
+#[tokio::test]
+async fn cookies_go_only_where_they_belong() {
+  let now = Utc::now();
+  let mut jar = vec![];
+  for (set_cookie, from) in [
+    ("a=1", "http://example.com/"),
+    ("b=2; Domain=.example.com; Path=/api", "http://example.com/"),
+    ("c=3; Secure", "https://example.com/"),
+    ("d=4; Path=/", "http://example.com/docs/page"),
+    ("e=5", "http://example.com/docs/page"),
+  ] {
+    let cookie = parse_set_cookie(set_cookie, &url(from), now).unwrap();
+    store_cookie(&mut jar, cookie, now);
+  }
+
+  assert_eq!(
+    cookie_header(&jar, &url("http://example.com/"), now).as_deref(),
+    Some("a=1; d=4")
+  );
+  assert_eq!(
+    cookie_header(&jar, &url("https://api.example.com/api/users"), now).as_deref(),
+    Some("b=2")
+  );
+  assert_eq!(
+    cookie_header(&jar, &url("https://example.com/docs/other"), now).as_deref(),
+    Some("e=5; a=1; c=3; d=4")
+  );
+  assert_eq!(cookie_header(&jar, &url("http://example.org/"), now), None);
+  // a cookie can't be set for another site
+  assert_eq!(
+    parse_set_cookie("x=1; Domain=example.org", &url("http://example.com/"), now),
+    None
+  );
+}
+
+#[tokio::test]
+async fn expired_cookies_delete_the_cookie() {
+  let now = Utc::now();
+  let from = url("http://example.com/");
+  let mut jar = vec![];
+  store_cookie(
+    &mut jar,
+    parse_set_cookie("session=abc; Max-Age=60", &from, now).unwrap(),
+    now,
+  );
+  store_cookie(
+    &mut jar,
+    parse_set_cookie("old=1; Expires=Wed, 21-Oct-2015 07:28:00 GMT", &from, now).unwrap(),
+    now,
+  );
+
+  assert_eq!(jar.len(), 1);
+  assert_eq!(
+    cookie_header(&jar, &from, now).as_deref(),
+    Some("session=abc")
+  );
+  assert_eq!(
+    cookie_header(&jar, &from, now + Duration::seconds(61)),
+    None
+  );
+  store_cookie(
+    &mut jar,
+    parse_set_cookie("session=; Max-Age=0", &from, now).unwrap(),
+    now,
+  );
+  assert!(jar.is_empty());
+}
+
+#[tokio::test]
+async fn cookies_are_edited_and_cleared_from_the_manager() {
+  let mut test_app = spawn_test_app().await;
+  let environment = EnvironmentFile::default();
+  let cookies = vec![
+    Cookie {
+      name: "session".into(),
+      value: "abc".into(),
+      domain: "example.com".into(),
+      host_only: true,
+      path: "/".into(),
+      expires: Some(Utc::now() + Duration::days(1)),
+      secure: true,
+      ..Default::default()
+    },
+    Cookie {
+      name: "theme".into(),
+      value: "dark".into(),
+      domain: "example.org".into(),
+      path: "/".into(),
+      ..Default::default()
+    },
+  ];
+
+  test_app
+    .app
+    .save_cookies(&environment, &cookies)
+    .await
+    .unwrap();
+  let saved = test_app.app.load_cookies(&environment).await.unwrap();
+  test_app.app.save_cookies(&environment, &[]).await.unwrap();
+
+  assert_eq!(saved.len(), 2);
+  assert_eq!(saved[1], cookies[1]);
+  // dates go through the db as text, to the second is enough
+  assert_eq!(
+    saved[0].expires.map(|e| e.timestamp()),
+    cookies[0].expires.map(|e| e.timestamp())
+  );
+  assert!(test_app
+    .app
+    .load_cookies(&environment)
+    .await
+    .unwrap()
+    .is_empty());
+}
@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex};
+
+use api::{
+  domain::{
+    request::HttpRequest,
+    response::{ResponseData, ResponseProgress},
+  },
+  utilities::response::save_body,
+};
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+use crate::helpers::spawn_test_app;
+
+fn get(url: String) -> HttpRequest {
+  HttpRequest {
+    url,
+    ..Default::default()
+  }
+}
+
+async fn saved_body(test_app: &mut crate::helpers::TestApp) -> Option<String> {
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  let responses = test_app.app.load_saved_responses().await.unwrap();
+  responses
+    .into_iter()
+    .find(|r| r.id == history[0].response_id)
+    .unwrap()
+    .body
+}
+
+#[tokio::test]
+async fn bodies_over_the_limit_are_previewed_and_kept_in_a_file() {
+  let mut test_app = spawn_test_app().await;
+  test_app.app.response_preview_limit = 10;
+  test_app.app.keep_response_files = true;
+  let text = "0123456789".repeat(10);
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_raw(text.clone(), "text/plain"))
+    .mount(&test_app._test_server)
+    .await;
+  let progress = Arc::new(Mutex::new(vec![]));
+  let progress_seen = Arc::clone(&progress);
+
+  let res = test_app
+    .app
+    .send_http_request_streaming(
+      get(test_app._test_server.uri()),
+      None,
+      |_| {},
+      move |p| progress_seen.lock().unwrap().push(p),
+    )
+    .await
+    .unwrap();
+
+  let ResponseData::TEXT(preview) = &res.data else {
+    panic!("expected a text preview");
+  };
+  assert_eq!(preview, "0123456789");
+  assert!(res.body.truncated);
+  assert!(!res.body.binary);
+  assert_eq!(res.body.size, 100);
+  let file = res.body.file.clone().expect("the whole body in a file");
+  assert_eq!(std::fs::read_to_string(&file).unwrap(), text);
+  assert_eq!(
+    progress.lock().unwrap().last(),
+    Some(&ResponseProgress {
+      received: 100,
+      total: Some(100)
+    })
+  );
+  assert_eq!(
+    saved_body(&mut test_app).await.as_deref(),
+    Some("0123456789")
+  );
+}
+
+#[tokio::test]
+async fn binary_bodies_are_saved_byte_for_byte() {
+  let mut test_app = spawn_test_app().await;
+  test_app.app.keep_response_files = true;
+  let bytes: Vec<u8> = vec![0x89, b'P', b'N', b'G', 0x00, 0xff, 0xfe, 0x10];
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_raw(bytes.clone(), "image/png"))
+    .mount(&test_app._test_server)
+    .await;
+
+  let res = test_app
+    .app
+    .send_http_request(get(test_app._test_server.uri()))
+    .await
+    .unwrap();
+
+  assert!(res.body.binary);
+  assert!(!res.body.truncated);
+  assert_eq!(res.body.content_type, "image/png");
+  let ResponseData::UNKNOWN(description) = &res.data else {
+    panic!("expected the body to be described");
+  };
+  assert_eq!(description, "Binary response, 8 bytes of image/png");
+  assert_eq!(saved_body(&mut test_app).await, None);
+  let saved_to = std::env::temp_dir().join(format!("{}.png", Uuid::new_v4()));
+  save_body(&res.body, &res.data, saved_to.to_str().unwrap())
+    .await
+    .unwrap();
+  assert_eq!(std::fs::read(&saved_to).unwrap(), bytes);
+}
+
+#[tokio::test]
+async fn text_types_that_are_not_utf8_are_binary() {
+  let test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_raw(vec![b'a', 0xff, b'b'], "text/plain"))
+    .mount(&test_app._test_server)
+    .await;
+  let mut app = test_app.app;
+  app.keep_response_files = true;
+
+  let res = app
+    .send_http_request(get(test_app._test_server.uri()))
+    .await
+    .unwrap();
+
+  assert!(res.body.binary);
+  assert_eq!(
+    std::fs::read(res.body.file.unwrap()).unwrap(),
+    vec![b'a', 0xff, b'b']
+  );
+}
+
+#[tokio::test]
+async fn small_bodies_are_read_whole() {
+  let test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+    .mount(&test_app._test_server)
+    .await;
+  let mut app = test_app.app;
+
+  let res = app
+    .send_http_request(get(test_app._test_server.uri()))
+    .await
+    .unwrap();
+
+  let ResponseData::JSON(json) = &res.data else {
+    panic!("expected json");
+  };
+  assert_eq!(json, &json!({ "ok": true }));
+  assert!(!res.body.truncated && !res.body.binary);
+  assert_eq!(res.body.file, None);
+  assert_eq!(res.body.size, 11);
+}
+
+#[tokio::test]
+async fn no_files_are_written_unless_asked_for() {
+  let mut test_app = spawn_test_app().await;
+  test_app.app.response_preview_limit = 10;
+  let text = "0123456789".repeat(10);
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_raw(text, "text/plain"))
+    .mount(&test_app._test_server)
+    .await;
+
+  let res = test_app
+    .app
+    .send_http_request(get(test_app._test_server.uri()))
+    .await
+    .unwrap();
+
+  assert!(res.body.truncated);
+  assert_eq!(res.body.size, 100);
+  assert_eq!(res.body.file, None);
+  let ResponseData::TEXT(preview) = &res.data else {
+    panic!("expected a text preview");
+  };
+  assert_eq!(preview, "0123456789");
+}
@@ -0,0 +1,68 @@
+use api::domain::environment::EnvironmentValue;
+
+use crate::helpers::{fixture_path, spawn_test_app};
+
+#[tokio::test]
+async fn exported_collection_round_trips_through_parse_collection() {
+  let mut test_app = spawn_test_app().await;
+  let collection_json = std::fs::read_to_string(fixture_path("test_collection.json")).unwrap();
+  let collection = test_app.app.parse_collection(&collection_json);
+  test_app
+    .app
+    .save_collection(collection.clone())
+    .await
+    .unwrap();
+
+  let export_path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+  let export_path = export_path.to_str().unwrap();
+  let res = test_app
+    .app
+    .export_collection(&collection.info.id, export_path)
+    .await
+    .unwrap();
+  assert_eq!(res, "Export successful");
+
+  let exported = std::fs::read_to_string(export_path).unwrap();
+  std::fs::remove_file(export_path).unwrap();
+  let exported_json: serde_json::Value = serde_json::from_str(&exported).unwrap();
+  assert_eq!(
+    exported_json["info"]["schema"],
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+  );
+  assert_eq!(
+    exported_json["info"]["_postman_id"],
+    "ca583cc6-98af-47fe-ab56-d4ef19b583b2"
+  );
+  assert_eq!(test_app.app.parse_collection(&exported), collection);
+}
+
+#[tokio::test]
+async fn exported_environment_round_trips_through_parse_environment() {
+  let mut test_app = spawn_test_app().await;
+  let environment_json = std::fs::read_to_string(fixture_path("test_environment.json")).unwrap();
+  let mut environment = test_app.app.parse_environment(&environment_json);
+  environment.values.as_mut().unwrap().push(EnvironmentValue {
+    key: String::from("API_KEY"),
+    value: String::from("secret"),
+    r#type: String::from("secret"),
+    enabled: false,
+  });
+  test_app.app.save_environment(environment).await.unwrap();
+  let saved = test_app.app.load_environments().await.unwrap()[0].clone();
+
+  let export_path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+  let export_path = export_path.to_str().unwrap();
+  test_app
+    .app
+    .export_environment(&saved.id, export_path)
+    .await
+    .unwrap();
+
+  let exported = std::fs::read_to_string(export_path).unwrap();
+  std::fs::remove_file(export_path).unwrap();
+  let exported_json: serde_json::Value = serde_json::from_str(&exported).unwrap();
+  assert_eq!(exported_json["_postman_variable_scope"], "environment");
+  assert_eq!(exported_json["values"][1]["enabled"], false);
+  assert_eq!(exported_json["values"][1]["type"], "secret");
+  assert_eq!(test_app.app.parse_environment(&exported), saved);
+}
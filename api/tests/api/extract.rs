@@ -0,0 +1,177 @@
+use api::domain::{
+  environment::{EnvironmentFile, EnvironmentValue},
+  extract::{ExtractRule, ExtractSource},
+  request::HttpRequest,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{header, method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn environment(host: &str) -> EnvironmentFile {
+  EnvironmentFile {
+    id: String::from("chain"),
+    name: String::from("chain"),
+    values: Some(vec![EnvironmentValue {
+      key: String::from("HOST_URL"),
+      value: String::from(host),
+      r#type: String::from("default"),
+      enabled: true,
+    }]),
+  }
+}
+
+fn request(url: &str, environment: EnvironmentFile, extract: Vec<ExtractRule>) -> HttpRequest {
+  HttpRequest {
+    url: String::from(url),
+    headers: Some(vec![(
+      String::from("Authorization"),
+      String::from("Bearer {{token}}"),
+    )]),
+    environment,
+    extract,
+    ..Default::default()
+  }
+}
+
+fn rule(variable: &str, source: ExtractSource) -> ExtractRule {
+  ExtractRule {
+    variable: String::from(variable),
+    source,
+  }
+}
+
+fn value_of(environment: &EnvironmentFile, key: &str) -> Option<String> {
+  environment
+    .values
+    .iter()
+    .flatten()
+    .find(|v| v.key == key)
+    .map(|v| v.value.clone())
+}
+
+#[tokio::test]
+async fn extracted_values_are_saved_to_the_environment_and_used_by_the_next_request() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/login"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_json(json!({"token": "abc", "user": {"id": 7}}))
+        .insert_header("x-session", "s-1"),
+    )
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/users/7"))
+    .and(header("Authorization", "Bearer abc"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let mut env = environment(&test_app._test_server.uri());
+  env.values.as_mut().unwrap().push(EnvironmentValue {
+    key: String::from("token"),
+    value: String::from("expired"),
+    r#type: String::from("default"),
+    enabled: true,
+  });
+  test_app.app.save_environment(env.clone()).await.unwrap();
+  let login = request(
+    "{{HOST_URL}}/login",
+    env,
+    vec![
+      rule(
+        "token",
+        ExtractSource::JsonPath {
+          path: String::from("$.token"),
+        },
+      ),
+      rule(
+        "user_id",
+        ExtractSource::JsonPath {
+          path: String::from("$.user.id"),
+        },
+      ),
+      rule(
+        "session",
+        ExtractSource::Header {
+          name: String::from("X-Session"),
+        },
+      ),
+      rule(
+        "quoted_token",
+        ExtractSource::Regex {
+          pattern: String::from(r#""token":\s*("\w+")"#),
+        },
+      ),
+    ],
+  );
+
+  let res = test_app.app.send_http_request(login).await.unwrap();
+
+  assert!(res.test_results.is_empty());
+  let env = res.environment.expect("environment should have changed");
+  assert_eq!(value_of(&env, "token").as_deref(), Some("abc"));
+  assert_eq!(value_of(&env, "user_id").as_deref(), Some("7"));
+  assert_eq!(value_of(&env, "session").as_deref(), Some("s-1"));
+  assert_eq!(value_of(&env, "quoted_token").as_deref(), Some("\"abc\""));
+  let saved = test_app.app.load_environments().await.unwrap();
+  assert_eq!(saved, vec![env.clone()]);
+
+  let fetch = request("{{HOST_URL}}/users/{{user_id}}", env, vec![]);
+  let res = test_app.app.send_http_request(fetch).await.unwrap();
+  assert_eq!(res.status_code, 200);
+}
+
+#[tokio::test]
+async fn rules_that_find_nothing_are_reported_and_failed_responses_are_skipped() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/ok"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 1})))
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(method("GET"))
+    .and(path("/missing"))
+    .respond_with(ResponseTemplate::new(404).set_body_json(json!({"id": 2})))
+    .mount(&test_app._test_server)
+    .await;
+  let env = environment(&test_app._test_server.uri());
+  let rules = vec![
+    rule(
+      "token",
+      ExtractSource::JsonPath {
+        path: String::from("$.token"),
+      },
+    ),
+    rule(
+      "id",
+      ExtractSource::JsonPath {
+        path: String::from("$.id"),
+      },
+    ),
+  ];
+  let mut input = request("{{HOST_URL}}/ok", env.clone(), rules.clone());
+  input.headers = None;
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.test_results.len(), 1);
+  assert_eq!(res.test_results[0].name, "Extract token from $.token");
+  assert!(!res.test_results[0].passed);
+  let changed = res.environment.unwrap();
+  assert_eq!(value_of(&changed, "token"), None);
+  assert_eq!(value_of(&changed, "id").as_deref(), Some("1"));
+  // the environment was never saved, so nothing is written to the db
+  assert!(test_app.app.load_environments().await.unwrap().is_empty());
+
+  let mut input = request("{{HOST_URL}}/missing", env, rules);
+  input.headers = None;
+  let res = test_app.app.send_http_request(input).await.unwrap();
+  assert!(res.test_results.is_empty());
+  assert!(res.environment.is_none());
+}
@@ -0,0 +1,186 @@
+use api::{
+  domain::{
+    collection::RequestBody as CollectionBody,
+    graphql::GraphQLSchema,
+    request::{GraphQLBody, HttpMethod, HttpRequest, RequestBody},
+  },
+  utilities::graphql::complete,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{body_json, body_string_contains, header, method},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn graphql_request(url: String, body: GraphQLBody) -> HttpRequest {
+  HttpRequest {
+    method: HttpMethod::POST,
+    url,
+    headers: Some(vec![("Authorization".into(), "Bearer {{token}}".into())]),
+    body: Some(RequestBody::GRAPHQL(body)),
+    variables: vec![("token".into(), "secret".into()), ("id".into(), "7".into())],
+    ..Default::default()
+  }
+}
+
+fn introspection_response() -> serde_json::Value {
+  let named = |kind: &str, name: &str| json!({ "kind": kind, "name": name, "ofType": null });
+  let non_null = |of: serde_json::Value| json!({ "kind": "NON_NULL", "name": null, "ofType": of });
+  let list = |of: serde_json::Value| json!({ "kind": "LIST", "name": null, "ofType": of });
+  json!({
+    "data": { "__schema": {
+      "queryType": { "name": "Query" },
+      "mutationType": { "name": "Mutation" },
+      "subscriptionType": null,
+      "types": [
+        { "kind": "OBJECT", "name": "Query", "fields": [
+          { "name": "user", "description": "Find a user", "type": named("OBJECT", "User") },
+          { "name": "users", "description": null,
+            "type": non_null(list(non_null(named("OBJECT", "User")))) }
+        ] },
+        { "kind": "OBJECT", "name": "Mutation", "fields": [
+          { "name": "renameUser", "description": null, "type": named("OBJECT", "User") }
+        ] },
+        { "kind": "OBJECT", "name": "User", "fields": [
+          { "name": "id", "description": null, "type": non_null(named("SCALAR", "ID")) },
+          { "name": "name", "description": null, "type": named("SCALAR", "String") },
+          { "name": "friends", "description": null, "type": list(named("OBJECT", "User")) }
+        ] },
+        { "kind": "SCALAR", "name": "ID", "fields": null }
+      ]
+    } }
+  })
+}
+
+fn names(fields: Vec<&api::domain::graphql::GraphQLField>) -> Vec<&str> {
+  fields.iter().map(|f| f.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn graphql_bodies_are_posted_as_json_with_variables_substituted() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(header("authorization", "Bearer secret"))
+    .and(header("content-type", "application/json"))
+    .and(body_json(json!({
+      "query": "query User($id: ID!) { user(id: $id) { name } }",
+      "variables": { "id": 7 },
+      "operationName": "User"
+    })))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": { "user": null } })))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = graphql_request(
+    test_app._test_server.uri(),
+    GraphQLBody {
+      query: "query User($id: ID!) { user(id: $id) { name } }".into(),
+      variables: "{ \"id\": {{id}} }".into(),
+      operation_name: Some("User".into()),
+    },
+  );
+
+  let res = test_app.app.send_http_request(input).await;
+
+  assert_eq!(res.unwrap().status_code, 200);
+}
+
+#[tokio::test]
+async fn invalid_graphql_variables_are_an_error() {
+  let mut test_app = spawn_test_app().await;
+  let input = graphql_request(
+    test_app._test_server.uri(),
+    GraphQLBody {
+      query: "{ users { id } }".into(),
+      variables: "{ id: }".into(),
+      operation_name: None,
+    },
+  );
+
+  let err = test_app.app.send_http_request(input).await.unwrap_err();
+
+  assert!(err
+    .to_string()
+    .contains("GraphQL variables are not valid json"));
+}
+
+#[tokio::test]
+async fn the_schema_is_introspected_from_the_endpoint() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(header("authorization", "Bearer secret"))
+    .and(body_string_contains("__schema"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(introspection_response()))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = graphql_request(test_app._test_server.uri(), GraphQLBody::default());
+
+  let schema = test_app.app.introspect_graphql_schema(input).await.unwrap();
+
+  assert_eq!(schema.query_type.as_deref(), Some("Query"));
+  let users = &schema.find_type("Query").unwrap().fields[1];
+  assert_eq!(users.type_name, "[User!]!");
+  assert_eq!(users.named_type, "User");
+}
+
+#[test]
+fn fields_are_completed_for_the_selection_under_the_cursor() {
+  let schema = GraphQLSchema::from_introspection(&introspection_response()).unwrap();
+  let at_end = |query: &str| names(complete(&schema, query, query.chars().count()));
+
+  assert_eq!(at_end("{ "), vec!["user", "users"]);
+  assert_eq!(
+    at_end("query { user(id: \"{\") { "),
+    vec!["id", "name", "friends"]
+  );
+  assert_eq!(at_end("{ users { friends { na"), vec!["name"]);
+  assert_eq!(at_end("mutation { re"), vec!["renameUser"]);
+  assert_eq!(at_end("{ user { id } us"), vec!["user", "users"]);
+  assert_eq!(at_end("fragment F on User { fr"), vec!["friends"]);
+  assert_eq!(at_end("{ user { name } }"), Vec::<&str>::new());
+  let query = "{ user { } users }";
+  assert_eq!(
+    names(complete(&schema, query, 9)),
+    vec!["id", "name", "friends"]
+  );
+}
+
+#[test]
+fn introspection_errors_are_reported() {
+  let response = json!({ "errors": [{ "message": "introspection is disabled" }] });
+
+  let err = GraphQLSchema::from_introspection(&response).unwrap_err();
+
+  assert!(err.contains("introspection is disabled"));
+}
+
+#[test]
+fn postman_graphql_bodies_round_trip() {
+  let body: CollectionBody = serde_json::from_value(json!({
+    "mode": "graphql",
+    "graphql": { "query": "{ users { id } }", "variables": "{\n  \"id\": 1\n}" }
+  }))
+  .unwrap();
+
+  let imported = body.to_request_body().unwrap();
+
+  assert_eq!(
+    imported,
+    RequestBody::GRAPHQL(GraphQLBody {
+      query: "{ users { id } }".into(),
+      variables: "{\n  \"id\": 1\n}".into(),
+      operation_name: None,
+    })
+  );
+  let exported = serde_json::to_value(CollectionBody::from_request_body(&imported)).unwrap();
+  assert_eq!(
+    exported,
+    json!({
+      "mode": "graphql",
+      "graphql": { "query": "{ users { id } }", "variables": "{\n  \"id\": 1\n}" }
+    })
+  );
+}
@@ -0,0 +1,299 @@
+use std::{
+  convert::Infallible,
+  task::{Context, Poll},
+};
+
+use api::{
+  domain::{environment::EnvironmentFile, grpc::GrpcRequest, response::ResponseData},
+  utilities::grpc::load_proto_files,
+};
+use serde_json::json;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{
+  body::Body,
+  codegen::{http, BoxFuture, BoxStream, Service},
+  server::{Grpc, NamedService, ServerStreamingService, UnaryService},
+  transport::Server,
+  Status,
+};
+use tonic_prost::ProstCodec;
+use uuid::Uuid;
+
+use crate::helpers::{fixture_path, spawn_test_app};
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HelloRequest {
+  #[prost(string, tag = "1")]
+  name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HelloReply {
+  #[prost(string, tag = "1")]
+  message: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CountRequest {
+  #[prost(int32, tag = "1")]
+  to: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CountReply {
+  #[prost(int32, tag = "1")]
+  n: i32,
+}
+
+// The greeter.proto service written out the way tonic's generated code would be
+#[derive(Clone)]
+struct Greeter;
+
+impl NamedService for Greeter {
+  const NAME: &'static str = "test.greeter.Greeter";
+}
+
+impl Service<http::Request<Body>> for Greeter {
+  type Response = http::Response<Body>;
+  type Error = Infallible;
+  type Future = BoxFuture<Self::Response, Self::Error>;
+
+  fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+    match req.uri().path() {
+      "/test.greeter.Greeter/SayHello" => {
+        Box::pin(async move { Ok(Grpc::new(ProstCodec::default()).unary(SayHello, req).await) })
+      }
+      "/test.greeter.Greeter/CountTo" => Box::pin(async move {
+        Ok(
+          Grpc::new(ProstCodec::default())
+            .server_streaming(CountTo, req)
+            .await,
+        )
+      }),
+      _ => Box::pin(async { Ok(Status::unimplemented("").into_http()) }),
+    }
+  }
+}
+
+struct SayHello;
+
+impl UnaryService<HelloRequest> for SayHello {
+  type Response = HelloReply;
+  type Future = BoxFuture<tonic::Response<HelloReply>, Status>;
+
+  fn call(&mut self, req: tonic::Request<HelloRequest>) -> Self::Future {
+    Box::pin(async move {
+      let token = req
+        .metadata()
+        .get("x-token")
+        .and_then(|t| t.to_str().ok())
+        .unwrap_or("none")
+        .to_string();
+      let name = req.into_inner().name;
+      if name.is_empty() {
+        return Err(Status::invalid_argument("name is required"));
+      }
+      Ok(tonic::Response::new(HelloReply {
+        message: format!("Hello {} ({})", name, token),
+      }))
+    })
+  }
+}
+
+struct CountTo;
+
+impl ServerStreamingService<CountRequest> for CountTo {
+  type Response = CountReply;
+  type ResponseStream = BoxStream<CountReply>;
+  type Future = BoxFuture<tonic::Response<Self::ResponseStream>, Status>;
+
+  fn call(&mut self, req: tonic::Request<CountRequest>) -> Self::Future {
+    let to = req.into_inner().to;
+    Box::pin(async move {
+      let replies = (1..=to).map(|n| Ok(CountReply { n }));
+      let stream: Self::ResponseStream = Box::pin(tokio_stream::iter(replies));
+      Ok(tonic::Response::new(stream))
+    })
+  }
+}
+
+fn greeter_proto() -> String {
+  fixture_path("greeter.proto")
+}
+
+// Serves the greeter, along with the reflection service when asked. Returns its grpc:// url
+async fn spawn_greeter(reflection: bool) -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("grpc://{}", listener.local_addr().unwrap());
+  let reflection = reflection.then(|| {
+    let descriptors = load_proto_files(&[greeter_proto()])
+      .unwrap()
+      .encode_to_vec();
+    tonic_reflection::server::Builder::configure()
+      .register_encoded_file_descriptor_set(Box::leak(descriptors.into_boxed_slice()))
+      .build_v1()
+      .unwrap()
+  });
+  let router = Server::builder()
+    .add_service(Greeter)
+    .add_optional_service(reflection);
+  tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+  url
+}
+
+fn grpc_request(url: String, message: &str, proto_files: Vec<String>) -> GrpcRequest {
+  GrpcRequest {
+    tab_id: Uuid::new_v4(),
+    id: Uuid::new_v4(),
+    name: None,
+    url,
+    headers: Some(vec![("X-Token".into(), "{{token}}".into())]),
+    message: message.into(),
+    proto_files,
+    environment: EnvironmentFile::default(),
+    variables: vec![
+      ("token".into(), "secret".into()),
+      ("name".into(), "Ada".into()),
+    ],
+  }
+}
+
+#[tokio::test]
+async fn methods_are_listed_from_proto_files_and_from_reflection() {
+  let test_app = spawn_test_app().await;
+  let url = spawn_greeter(true).await;
+
+  let from_files = test_app
+    .app
+    .load_grpc_methods(&grpc_request(url.clone(), "", vec![greeter_proto()]))
+    .await
+    .unwrap();
+  let from_reflection = test_app
+    .app
+    .load_grpc_methods(&grpc_request(url, "", vec![]))
+    .await
+    .unwrap();
+
+  let summary: Vec<(String, &str)> = from_files.iter().map(|m| (m.path(), m.kind())).collect();
+  assert_eq!(
+    summary,
+    vec![
+      (
+        "test.greeter.Greeter/CountTo".to_string(),
+        "server streaming"
+      ),
+      ("test.greeter.Greeter/SayHello".to_string(), "unary"),
+    ]
+  );
+  assert_eq!(
+    serde_json::from_str::<serde_json::Value>(&from_files[1].example).unwrap(),
+    json!({ "name": "" })
+  );
+  assert_eq!(from_reflection, from_files);
+}
+
+#[tokio::test]
+async fn unary_calls_send_metadata_and_are_saved_to_the_history() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_greeter(false).await;
+  let input = grpc_request(
+    format!("{}/test.greeter.Greeter/SayHello", url),
+    r#"{ "name": "{{name}}" }"#,
+    vec![greeter_proto()],
+  );
+
+  let res = test_app.app.send_grpc_request(input.clone()).await.unwrap();
+
+  assert_eq!(res.status, "0 Ok");
+  let ResponseData::JSON(body) = res.data else {
+    panic!("expected the reply as json");
+  };
+  assert_eq!(body, json!({ "message": "Hello Ada (secret)" }));
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  assert_eq!(history.len(), 1);
+  let requests = test_app.app.load_saved_requests().await.unwrap();
+  let request = requests
+    .iter()
+    .find(|r| r.id == input.id.to_string())
+    .unwrap();
+  assert_eq!(request.method, "POST");
+  assert_eq!(request.body.as_deref(), Some(input.message.as_str()));
+  let responses = test_app.app.load_saved_responses().await.unwrap();
+  let response = responses
+    .iter()
+    .find(|r| r.id == history[0].response_id)
+    .unwrap();
+  assert!(response
+    .headers
+    .iter()
+    .any(|h| h.key == "grpc-status" && h.value == "0"));
+}
+
+#[tokio::test]
+async fn server_streaming_calls_collect_every_message() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_greeter(true).await;
+  // no proto files, the method is found with reflection
+  let input = grpc_request(
+    format!("{}/test.greeter.Greeter/CountTo", url),
+    r#"{ "to": 3 }"#,
+    vec![],
+  );
+
+  let res = test_app.app.send_grpc_request(input).await.unwrap();
+
+  let ResponseData::JSON(body) = res.data else {
+    panic!("expected the replies as json");
+  };
+  assert_eq!(body, json!([{ "n": 1 }, { "n": 2 }, { "n": 3 }]));
+}
+
+#[tokio::test]
+async fn error_statuses_are_part_of_the_response() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_greeter(false).await;
+  let input = grpc_request(
+    format!("{}/test.greeter.Greeter/SayHello", url),
+    "{}",
+    vec![greeter_proto()],
+  );
+
+  let res = test_app.app.send_grpc_request(input).await.unwrap();
+
+  assert_eq!(res.status, "3 InvalidArgument");
+  let ResponseData::JSON(body) = res.data else {
+    panic!("expected the status as json");
+  };
+  assert_eq!(
+    body,
+    json!({ "error": "name is required", "status": "InvalidArgument" })
+  );
+}
+
+#[tokio::test]
+async fn messages_that_do_not_match_the_input_type_are_rejected() {
+  let mut test_app = spawn_test_app().await;
+  let url = spawn_greeter(false).await;
+  let input = grpc_request(
+    format!("{}/test.greeter.Greeter/SayHello", url),
+    r#"{ "nmae": "Ada" }"#,
+    vec![greeter_proto()],
+  );
+
+  let err = test_app.app.send_grpc_request(input).await.err().unwrap();
+
+  assert!(err
+    .to_string()
+    .starts_with("Message is not a valid test.greeter.HelloRequest"));
+  assert!(test_app
+    .app
+    .load_request_response_items()
+    .await
+    .unwrap()
+    .is_empty());
+}
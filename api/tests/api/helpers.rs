@@ -1,4 +1,4 @@
-use api::{db::repository::PostieDb, PostieApi};
+use api::{db::repository::PostieDb, PostieApi, DEFAULT_RESPONSE_PREVIEW_LIMIT};
 use sqlx::sqlite::SqlitePoolOptions;
 use wiremock::MockServer;
 
@@ -19,23 +19,28 @@ impl TestApp {
         db,
         environment: None,
         collection: Some("test_collection.json".to_string()),
+        response_preview_limit: DEFAULT_RESPONSE_PREVIEW_LIMIT,
+        keep_response_files: false,
+        client_profile: None,
       },
     }
   }
 }
 
+pub fn fixture_path(name: &str) -> String {
+  format!("{}/tests/api/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
 pub async fn spawn_test_app() -> TestApp {
   let app = TestApp::new();
   app.await
 }
 pub async fn initialize_test_db() -> anyhow::Result<PostieDb> {
   println!("acquiring sqlite connection");
-  let args: Vec<String> = std::env::args().collect();
-  if args.len() != 2 {
-    println!("Usage: {} <sqlite db file>", args[0]);
-    std::process::exit(1);
-  }
+  // every connection to :memory: gets its own database, so keep the pool to
+  // a single connection to make sure migrations and queries see the same one
   let pool = SqlitePoolOptions::new()
+    .max_connections(1)
     .connect(":memory:")
     .await
     .expect("could not create test connection pool");
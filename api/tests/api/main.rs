@@ -1,5 +1,22 @@
-// mod api;
+mod api;
+mod assertions;
+mod auth;
+mod bodies;
+mod client;
+mod cookies;
+mod downloads;
 // mod collections;
 // mod environments;
+mod exports;
+mod extract;
+mod graphql;
+mod grpc;
 mod helpers;
+mod oauth2;
 mod repository;
+mod requests;
+mod runner;
+mod scripts;
+mod sse;
+mod timeouts;
+mod websocket;
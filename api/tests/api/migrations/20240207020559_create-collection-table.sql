@@ -1,7 +0,0 @@
-CREATE TABLE IF NOT EXISTS collections (
-  id TEXT PRIMARY KEY NOT NULL,
-  name TEXT NOT NULL,
-  description TEXT,
-  `item` JSON,
-  `auth` JSON
-);
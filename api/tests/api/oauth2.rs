@@ -0,0 +1,301 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use api::domain::{
+  auth::{OAuth2ClientAuth, OAuth2Grant, OAuth2Prompt, RequestAuth},
+  request::{HttpRequest, OAuth2Request},
+};
+use base64::Engine as _;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use wiremock::{
+  matchers::{body_string_contains, header, method, path},
+  Mock, Request, Respond, ResponseTemplate,
+};
+
+use crate::helpers::{spawn_test_app, TestApp};
+
+fn config(test_app: &TestApp, grant: OAuth2Grant) -> OAuth2Request {
+  let uri = test_app._test_server.uri();
+  OAuth2Request {
+    grant,
+    access_token_url: format!("{}/token", uri),
+    auth_url: format!("{}/authorize", uri),
+    device_authorization_url: format!("{}/device", uri),
+    client_id: "postie".into(),
+    client_secret: "secret".into(),
+    ..Default::default()
+  }
+}
+
+fn get(url: String, auth: RequestAuth) -> HttpRequest {
+  HttpRequest {
+    url,
+    auth,
+    ..Default::default()
+  }
+}
+
+fn token(access_token: &str, expires_in: i64) -> ResponseTemplate {
+  ResponseTemplate::new(200).set_body_json(json!({
+    "access_token": access_token,
+    "token_type": "bearer",
+    "expires_in": expires_in,
+    "refresh_token": "refresh-1",
+  }))
+}
+
+// /api only answers 200 to the token given
+async fn mount_api(test_app: &TestApp, access_token: &str) {
+  Mock::given(path("/api"))
+    .and(header("authorization", format!("Bearer {}", access_token)))
+    .respond_with(ResponseTemplate::new(200))
+    .with_priority(1)
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(path("/api"))
+    .respond_with(ResponseTemplate::new(401))
+    .mount(&test_app._test_server)
+    .await;
+}
+
+#[tokio::test]
+async fn client_credentials_are_sent_in_a_basic_header() {
+  let mut test_app = spawn_test_app().await;
+  let credentials = base64::engine::general_purpose::STANDARD.encode("postie:secret");
+  Mock::given(method("POST"))
+    .and(path("/token"))
+    .and(header("authorization", format!("Basic {}", credentials)))
+    .and(body_string_contains("grant_type=client_credentials"))
+    .respond_with(token("abc", 3600))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let config = config(&test_app, OAuth2Grant::CLIENTCREDENTIALS);
+
+  let received = test_app
+    .app
+    .request_oauth2_token(&config, |_| {})
+    .await
+    .unwrap();
+  let saved = test_app.app.load_oauth2_token(&config).await.unwrap();
+
+  assert_eq!(received.access_token, "abc");
+  assert_eq!(received.header_value(), "Bearer abc");
+  assert_eq!(saved.map(|t| t.access_token).as_deref(), Some("abc"));
+}
+
+#[tokio::test]
+async fn client_credentials_can_go_in_the_body() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(path("/token"))
+    .and(|req: &Request| !req.headers.contains_key("authorization"))
+    .and(body_string_contains("client_id=postie"))
+    .and(body_string_contains("client_secret=secret"))
+    .respond_with(token("abc", 3600))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let config = OAuth2Request {
+    client_authentication: OAuth2ClientAuth::BODY,
+    ..config(&test_app, OAuth2Grant::CLIENTCREDENTIALS)
+  };
+
+  let received = test_app.app.request_oauth2_token(&config, |_| {}).await;
+
+  assert_eq!(received.unwrap().access_token, "abc");
+}
+
+#[tokio::test]
+async fn requests_get_a_token_once_and_send_it() {
+  let mut test_app = spawn_test_app().await;
+  mount_api(&test_app, "abc").await;
+  Mock::given(path("/token"))
+    .respond_with(token("abc", 3600))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let auth = RequestAuth::OAUTH2(config(&test_app, OAuth2Grant::CLIENTCREDENTIALS));
+  let url = format!("{}/api", test_app._test_server.uri());
+
+  let first = test_app
+    .app
+    .send_http_request(get(url.clone(), auth.clone()))
+    .await
+    .unwrap();
+  let second = test_app
+    .app
+    .send_http_request(get(url, auth))
+    .await
+    .unwrap();
+
+  assert_eq!(first.status_code, 200);
+  assert_eq!(second.status_code, 200);
+}
+
+#[tokio::test]
+async fn expiring_tokens_are_refreshed_before_sending() {
+  let mut test_app = spawn_test_app().await;
+  mount_api(&test_app, "fresh").await;
+  Mock::given(path("/token"))
+    .and(body_string_contains("grant_type=password"))
+    .and(body_string_contains("username=ann"))
+    .and(body_string_contains("password=hunter2"))
+    .respond_with(token("stale", 30))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  // the server doesn't send a new refresh token, the old one is kept
+  Mock::given(path("/refresh"))
+    .and(body_string_contains("grant_type=refresh_token"))
+    .and(body_string_contains("refresh_token=refresh-1"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_json(json!({"access_token": "fresh", "expires_in": 3600})),
+    )
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let config = OAuth2Request {
+    refresh_url: format!("{}/refresh", test_app._test_server.uri()),
+    username: "ann".into(),
+    password: "hunter2".into(),
+    ..config(&test_app, OAuth2Grant::PASSWORD)
+  };
+  test_app
+    .app
+    .request_oauth2_token(&config, |_| {})
+    .await
+    .unwrap();
+
+  let res = test_app
+    .app
+    .send_http_request(get(
+      format!("{}/api", test_app._test_server.uri()),
+      RequestAuth::OAUTH2(config.clone()),
+    ))
+    .await
+    .unwrap();
+  let saved = test_app
+    .app
+    .load_oauth2_token(&config)
+    .await
+    .unwrap()
+    .unwrap();
+
+  assert_eq!(res.status_code, 200);
+  assert_eq!(saved.access_token, "fresh");
+  assert_eq!(saved.refresh_token.as_deref(), Some("refresh-1"));
+}
+
+// Signs the user in straight away, redirecting back with a code for the challenge it was given
+struct SignIn(Arc<Mutex<String>>);
+impl Respond for SignIn {
+  fn respond(&self, req: &Request) -> ResponseTemplate {
+    let params: HashMap<String, String> = req.url.query_pairs().into_owned().collect();
+    *self.0.lock().unwrap() = params["code_challenge"].clone();
+    ResponseTemplate::new(302).append_header(
+      "location",
+      format!(
+        "{}?code=code-1&state={}",
+        params["redirect_uri"], params["state"]
+      ),
+    )
+  }
+}
+
+// Only gives a token for the verifier of the challenge
+struct Exchange(Arc<Mutex<String>>);
+impl Respond for Exchange {
+  fn respond(&self, req: &Request) -> ResponseTemplate {
+    let form: HashMap<String, String> = url::form_urlencoded::parse(&req.body)
+      .into_owned()
+      .collect();
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+      .encode(Sha256::digest(form["code_verifier"].as_bytes()));
+    if form["code"] == "code-1" && challenge == *self.0.lock().unwrap() {
+      token("abc", 3600)
+    } else {
+      ResponseTemplate::new(400).set_body_json(json!({"error": "invalid_grant"}))
+    }
+  }
+}
+
+#[tokio::test]
+async fn authorization_code_signs_in_through_a_local_redirect() {
+  let mut test_app = spawn_test_app().await;
+  let challenge = Arc::new(Mutex::new(String::new()));
+  Mock::given(path("/authorize"))
+    .respond_with(SignIn(challenge.clone()))
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(path("/token"))
+    .and(body_string_contains("grant_type=authorization_code"))
+    .respond_with(Exchange(challenge))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let config = config(&test_app, OAuth2Grant::AUTHORIZATIONCODE);
+
+  // the browser follows the redirect to the listener
+  let received = test_app
+    .app
+    .request_oauth2_token(&config, |prompt| {
+      if let OAuth2Prompt::OpenBrowser(url) = prompt {
+        tokio::spawn(reqwest::get(url));
+      }
+    })
+    .await
+    .unwrap();
+
+  assert_eq!(received.access_token, "abc");
+}
+
+#[tokio::test]
+async fn device_code_polls_until_the_code_is_entered() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(path("/device"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+      "device_code": "device-1",
+      "user_code": "ABCD-EFGH",
+      "verification_uri": "https://example.com/device",
+      "interval": 0,
+      "expires_in": 60,
+    })))
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(path("/token"))
+    .and(body_string_contains("device_code=device-1"))
+    .respond_with(
+      ResponseTemplate::new(400).set_body_json(json!({"error": "authorization_pending"})),
+    )
+    .up_to_n_times(1)
+    .with_priority(1)
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(path("/token"))
+    .and(body_string_contains("device_code=device-1"))
+    .respond_with(token("abc", 3600))
+    .mount(&test_app._test_server)
+    .await;
+  let config = config(&test_app, OAuth2Grant::DEVICECODE);
+  let mut prompts = vec![];
+
+  let received = test_app
+    .app
+    .request_oauth2_token(&config, |prompt| prompts.push(prompt))
+    .await
+    .unwrap();
+
+  assert_eq!(received.access_token, "abc");
+  assert_eq!(
+    prompts,
+    vec![OAuth2Prompt::EnterCode {
+      user_code: "ABCD-EFGH".into(),
+      verification_uri: "https://example.com/device".into(),
+      verification_uri_complete: None,
+    }]
+  );
+}
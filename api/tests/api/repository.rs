@@ -1,50 +1,158 @@
-use api::domain::collection::Collection;
+use api::{
+  db::repository::PostieDb,
+  domain::{
+    collection::Collection,
+    environment::{EnvironmentFile, EnvironmentValue},
+  },
+};
 use serde_json::json;
 
-use crate::helpers::{initialize_test_db, spawn_test_app};
+use crate::helpers::{fixture_path, spawn_test_app};
 
 #[tokio::test]
 async fn can_delete_collection_request() {
   println!("can delete collection request test");
   let mut test_app = spawn_test_app().await;
-  if let Ok(mut db) = initialize_test_db().await {
-    println!("initialized test db");
-    let test_collection_file =
-      std::fs::File::open("test_collection_2.json").expect("could not open test collection");
-    let collection: Collection = serde_json::from_reader(test_collection_file).unwrap();
-    let _ = db.save_collection(collection).await;
-    let collections = db.get_all_collections().await;
-
-    let _ = test_app
-      .app
-      .delete_collection_request(collections.unwrap()[0].info.id.clone(), "delete-me".into())
-      .await;
-
-    let expected_json = json!(
-        {
-          "name": "test collection",
-          "item": [
-            {
-              "name": "folder",
-              "item": [
-                {
-                  "name": "req1",
-                  "request": {
-                    "method": "GET",
-                    "url": {
-                      "raw": "https://httpbin.org/json"
-                    },
+  let test_collection_file = std::fs::File::open(fixture_path("test_collection_2.json"))
+    .expect("could not open test collection");
+  let collection: Collection = serde_json::from_reader(test_collection_file).unwrap();
+  let _ = test_app.app.db.save_collection(collection).await;
+  let collections = test_app.app.db.get_all_collections().await;
+
+  let _ = test_app
+    .app
+    .delete_collection_request(collections.unwrap()[0].info.id.clone(), "delete-me".into())
+    .await;
+
+  let expected_json = json!(
+      {
+        "info": {
+          "_postman_id": "123",
+          "name": "test collection"
+        },
+        "item": [
+          {
+            "id": "folder",
+            "name": "folder",
+            "item": [
+              {
+                "id": "req1",
+                "name": "req1",
+                "request": {
+                  "method": "GET",
+                  "url": {
+                    "raw": "https://httpbin.org/json"
                   }
                 }
-              ]
-            }
-          ]
-        }
-    );
-    let actual = test_app.app.load_collections().await.unwrap()[0].clone();
-    assert_eq!(
-      serde_json::to_value::<Collection>(actual.clone()).expect("could not serialize json"),
-      expected_json
-    );
+              }
+            ]
+          }
+        ]
+      }
+  );
+  let actual = test_app.app.load_collections().await.unwrap()[0].clone();
+  assert_eq!(
+    serde_json::to_value::<Collection>(actual.clone()).expect("could not serialize json"),
+    expected_json
+  );
+}
+
+#[tokio::test]
+async fn collection_variables_are_persisted() {
+  let mut test_app = spawn_test_app().await;
+  let collection: Collection = serde_json::from_value(json!({
+    "info": { "_postman_id": "vars", "name": "vars" },
+    "variable": [{ "key": "host", "value": "localhost", "type": "string" }],
+    "item": [
+      {
+        "id": "folder",
+        "name": "folder",
+        "variable": [{ "key": "id", "value": "1", "disabled": true }],
+        "item": []
+      }
+    ]
+  }))
+  .unwrap();
+  test_app
+    .app
+    .db
+    .save_collection(collection.clone())
+    .await
+    .unwrap();
+
+  let saved = test_app.app.load_collections().await.unwrap()[0].clone();
+
+  assert_eq!(saved, collection);
+}
+
+#[tokio::test]
+async fn saving_globals_replaces_the_previous_set() {
+  let mut test_app = spawn_test_app().await;
+  let global = |key: &str, enabled: bool| EnvironmentValue {
+    key: key.into(),
+    value: format!("{key}-value"),
+    r#type: "default".into(),
+    enabled,
+  };
+  test_app
+    .app
+    .save_globals(&[global("old", true)])
+    .await
+    .unwrap();
+
+  test_app
+    .app
+    .save_globals(&[
+      global("host", true),
+      global("", true),
+      global("token", false),
+    ])
+    .await
+    .unwrap();
+
+  let globals = test_app.app.load_globals().await.unwrap();
+  assert_eq!(globals, vec![global("host", true), global("token", false)]);
+}
+
+#[tokio::test]
+async fn saving_an_environment_again_replaces_it() {
+  let mut test_app = spawn_test_app().await;
+  let mut environment = EnvironmentFile {
+    id: String::from("env"),
+    name: String::from("env"),
+    values: Some(vec![]),
   };
+  test_app
+    .app
+    .db
+    .save_environment(environment.clone())
+    .await
+    .unwrap();
+
+  environment.name = String::from("renamed");
+  test_app
+    .app
+    .db
+    .save_environment(environment.clone())
+    .await
+    .unwrap();
+
+  let environments = test_app.app.load_environments().await.unwrap();
+  assert_eq!(environments, vec![environment]);
+}
+
+#[tokio::test]
+async fn opening_a_db_brings_it_up_to_the_current_schema() {
+  let db_path = std::env::temp_dir().join(format!("postie-{}.sqlite", uuid::Uuid::new_v4()));
+
+  let mut db = PostieDb::from_path(db_path.clone()).await.unwrap();
+  assert_eq!(db.get_all_tabs().await.unwrap().len(), 1);
+  assert!(db.get_cookies("default").await.unwrap().is_empty());
+  db.pool.close().await;
+
+  // migrations already applied are skipped the next time it's opened
+  let mut db = PostieDb::from_path(db_path.clone()).await.unwrap();
+  assert_eq!(db.get_all_tabs().await.unwrap().len(), 1);
+  db.pool.close().await;
+  let _ = std::fs::remove_file(db_path);
 }
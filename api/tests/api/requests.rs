@@ -0,0 +1,304 @@
+use api::domain::{
+  auth::{ApiKeyPlacement, AuthSource, RequestAuth},
+  collection::Collection,
+  environment::{EnvironmentFile, EnvironmentValue},
+  request::HttpRequest,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{header, method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::{fixture_path, spawn_test_app};
+
+#[test]
+fn collection_requests_are_listed_in_tree_order() {
+  let file = std::fs::File::open(fixture_path("test_collection_2.json")).unwrap();
+  let collection: Collection = serde_json::from_reader(file).unwrap();
+
+  let names: Vec<&str> = collection
+    .requests()
+    .iter()
+    .map(|r| r.name.as_str())
+    .collect();
+  assert_eq!(names, vec!["delete-me", "req1"]);
+
+  let folder = collection.find_folder("folder").expect("folder not found");
+  assert_eq!(folder.requests().len(), 1);
+  assert!(collection.find_folder("missing").is_none());
+}
+
+#[tokio::test]
+async fn sending_a_collection_request_saves_history_and_reports_status() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/json"))
+    .respond_with(ResponseTemplate::new(201).set_body_json(json!({"ok": true})))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let file = std::fs::File::open(fixture_path("test_collection_2.json")).unwrap();
+  let collection: Collection = serde_json::from_reader(file).unwrap();
+  let item = collection.requests()[0];
+  let mut input = HttpRequest::from_collection_item(&collection, item, EnvironmentFile::default());
+  input.url = format!("{}/json", test_app._test_server.uri());
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.status_code, 201);
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  assert_eq!(history.len(), 1);
+  // only the tab seeded by the migrations, sending outside the gui shouldnt create one
+  assert_eq!(test_app.app.load_tabs().await.unwrap().len(), 1);
+}
+
+fn scoped_collection() -> Collection {
+  serde_json::from_value(json!({
+    "info": { "_postman_id": "scoped", "name": "scoped" },
+    "variable": [
+      { "key": "path", "value": "collection" },
+      { "key": "version", "value": 1, "type": "number" },
+      { "key": "skipped", "value": "nope", "disabled": true }
+    ],
+    "item": [
+      {
+        "id": "folder",
+        "name": "folder",
+        "variable": [{ "key": "path", "value": "folder" }],
+        "item": [
+          {
+            "id": "req1",
+            "name": "req1",
+            "variable": [{ "key": "user", "value": "request" }],
+            "request": { "method": "GET", "url": { "raw": "{{HOST_URL}}/{{path}}/v{{version}}/{{user}}" } }
+          }
+        ]
+      },
+      {
+        "id": "req2",
+        "name": "req2",
+        "request": { "method": "GET", "url": { "raw": "{{HOST_URL}}/{{path}}" } }
+      }
+    ]
+  }))
+  .unwrap()
+}
+
+#[test]
+fn collection_variables_are_listed_from_lowest_to_highest_scope() {
+  let collection = scoped_collection();
+
+  let to_pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+    pairs
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect()
+  };
+  assert_eq!(
+    collection.variables_for("req1"),
+    to_pairs(&[
+      ("path", "collection"),
+      ("version", "1"),
+      ("path", "folder"),
+      ("user", "request")
+    ])
+  );
+  assert_eq!(
+    collection.variables_for("req2"),
+    to_pairs(&[("path", "collection"), ("version", "1")])
+  );
+  assert!(collection.variables_for("missing").is_empty());
+}
+
+#[tokio::test]
+async fn scoped_variables_take_precedence_over_the_environment() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .and(path("/folder/v1/request"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let collection = scoped_collection();
+  let environment = EnvironmentFile {
+    id: "env".into(),
+    name: "env".into(),
+    values: Some(vec![
+      EnvironmentValue {
+        key: "HOST_URL".into(),
+        value: test_app._test_server.uri(),
+        r#type: "default".into(),
+        enabled: true,
+      },
+      EnvironmentValue {
+        key: "path".into(),
+        value: "environment".into(),
+        r#type: "default".into(),
+        enabled: true,
+      },
+    ]),
+  };
+  let item = collection.requests()[0];
+  let input = HttpRequest::from_collection_item(&collection, item, environment);
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.status_code, 200);
+}
+
+fn authed_collection(host: &str) -> Collection {
+  serde_json::from_value(json!({
+    "info": { "_postman_id": "authed", "name": "authed" },
+    "variable": [{ "key": "token", "value": "collection-token" }],
+    "auth": { "type": "bearer", "bearer": [{ "key": "token", "value": "{{token}}", "type": "string" }] },
+    "item": [
+      {
+        "id": "admin",
+        "name": "admin",
+        "auth": {
+          "type": "basic",
+          "basic": [
+            { "key": "username", "value": "ann", "type": "string" },
+            { "key": "password", "value": "hunter2", "type": "string" }
+          ]
+        },
+        "item": [
+          {
+            "id": "inherits",
+            "name": "inherits",
+            "request": {
+              "method": "GET",
+              "url": { "raw": format!("{}/admin", host) },
+              "auth": { "type": "inherit" }
+            }
+          },
+          {
+            "id": "public",
+            "name": "public",
+            "request": {
+              "method": "GET",
+              "url": { "raw": format!("{}/public", host) },
+              "auth": { "type": "noauth" }
+            }
+          }
+        ]
+      },
+      {
+        "id": "keyed",
+        "name": "keyed",
+        "request": {
+          "method": "GET",
+          "url": { "raw": format!("{}/keyed", host) },
+          "auth": {
+            "type": "apikey",
+            "apikey": [
+              { "key": "key", "value": "x-api-key", "type": "string" },
+              { "key": "value", "value": "secret", "type": "string" }
+            ]
+          }
+        }
+      },
+      {
+        "id": "root",
+        "name": "root",
+        "request": { "method": "GET", "url": { "raw": format!("{}/root", host) } }
+      }
+    ]
+  }))
+  .unwrap()
+}
+
+#[test]
+fn requests_take_the_closest_auth_set_above_them() {
+  let collection = authed_collection("http://localhost");
+
+  assert_eq!(
+    collection.auth_for("inherits"),
+    Some((
+      RequestAuth::BASIC {
+        username: "ann".into(),
+        password: "hunter2".into()
+      },
+      AuthSource::FOLDER("admin".into())
+    ))
+  );
+  assert_eq!(
+    collection.auth_for("public"),
+    Some((RequestAuth::NONE, AuthSource::REQUEST))
+  );
+  assert_eq!(
+    collection.auth_for("keyed"),
+    Some((
+      RequestAuth::APIKEY {
+        key: "x-api-key".into(),
+        value: "secret".into(),
+        placement: ApiKeyPlacement::HEADER,
+      },
+      AuthSource::REQUEST
+    ))
+  );
+  assert_eq!(
+    collection.auth_for("root"),
+    Some((
+      RequestAuth::BEARER("{{token}}".into()),
+      AuthSource::COLLECTION("authed".into())
+    ))
+  );
+  assert_eq!(collection.auth_for("missing"), None);
+}
+
+#[test]
+fn inherited_auth_leaves_out_the_requests_own() {
+  let collection = authed_collection("http://localhost");
+
+  assert_eq!(
+    collection.inherited_auth_for("public"),
+    Some((
+      RequestAuth::BASIC {
+        username: "ann".into(),
+        password: "hunter2".into()
+      },
+      AuthSource::FOLDER("admin".into())
+    ))
+  );
+  assert_eq!(
+    collection.inherited_auth_for("keyed"),
+    Some((
+      RequestAuth::BEARER("{{token}}".into()),
+      AuthSource::COLLECTION("authed".into())
+    ))
+  );
+}
+
+#[tokio::test]
+async fn collection_requests_are_sent_with_their_inherited_auth() {
+  let mut test_app = spawn_test_app().await;
+  let server = &test_app._test_server;
+  // base64 of ann:hunter2
+  for (route, name, value) in [
+    ("/admin", "authorization", "Basic YW5uOmh1bnRlcjI="),
+    ("/keyed", "x-api-key", "secret"),
+    ("/root", "authorization", "Bearer collection-token"),
+  ] {
+    Mock::given(path(route))
+      .and(header(name, value))
+      .respond_with(ResponseTemplate::new(200))
+      .expect(1)
+      .mount(server)
+      .await;
+  }
+  Mock::given(path("/public"))
+    .and(|req: &wiremock::Request| !req.headers.contains_key("authorization"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(1)
+    .mount(server)
+    .await;
+  let collection = authed_collection(&server.uri());
+
+  for item in collection.requests() {
+    let input = HttpRequest::from_collection_item(&collection, item, EnvironmentFile::default());
+    let res = test_app.app.send_http_request(input).await.unwrap();
+    assert_eq!(res.status_code, 200, "{}", item.name);
+  }
+}
@@ -0,0 +1,153 @@
+use api::{
+  domain::{
+    collection::Collection,
+    environment::EnvironmentFile,
+    run::{RunOptions, RunResult},
+  },
+  utilities::data_file::{parse_csv, parse_json},
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn users_collection(host: &str) -> Collection {
+  serde_json::from_value(json!({
+    "info": { "_postman_id": "runner", "name": "runner" },
+    "item": [
+      {
+        "id": "create",
+        "name": "create",
+        "request": { "method": "POST", "url": { "raw": format!("{host}/users/{{{{user}}}}") } },
+        "assertions": [{ "type": "status_equals", "status": 201 }]
+      },
+      {
+        "id": "fetch",
+        "name": "fetch",
+        "request": { "method": "GET", "url": { "raw": format!("{host}/users/{{{{user}}}}") } }
+      }
+    ]
+  }))
+  .unwrap()
+}
+
+#[tokio::test]
+async fn every_request_runs_once_per_data_row_and_the_report_is_saved() {
+  let mut test_app = spawn_test_app().await;
+  for user in ["ann", "bob"] {
+    Mock::given(method("POST"))
+      .and(path(format!("/users/{user}")))
+      .respond_with(ResponseTemplate::new(201))
+      .expect(1)
+      .mount(&test_app._test_server)
+      .await;
+    Mock::given(method("GET"))
+      .and(path(format!("/users/{user}")))
+      .respond_with(ResponseTemplate::new(200))
+      .expect(1)
+      .mount(&test_app._test_server)
+      .await;
+  }
+  let collection = users_collection(&test_app._test_server.uri());
+  let options = RunOptions {
+    data: parse_csv("user,role\nann,admin\nbob,viewer\n").unwrap(),
+    ..Default::default()
+  };
+  let mut seen = vec![];
+
+  let report = test_app
+    .app
+    .run_collection(
+      &collection,
+      &collection.requests(),
+      EnvironmentFile::default(),
+      &options,
+      |result| seen.push(result.clone()),
+    )
+    .await
+    .unwrap();
+
+  let order: Vec<(usize, &str, Option<u16>)> = report
+    .results
+    .iter()
+    .map(|r| (r.iteration, r.name.as_str(), r.status_code))
+    .collect();
+  assert_eq!(
+    order,
+    vec![
+      (1, "create", Some(201)),
+      (1, "fetch", Some(200)),
+      (2, "create", Some(201)),
+      (2, "fetch", Some(200)),
+    ]
+  );
+  assert_eq!(report.iterations, 2);
+  assert_eq!(report.passed(), 4);
+  assert!(!report.stopped_early);
+  assert_eq!(seen, report.results);
+  assert_eq!(test_app.app.load_run_reports().await.unwrap(), vec![report]);
+}
+
+#[tokio::test]
+async fn stop_on_failure_ends_the_run_at_the_first_failed_request() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .respond_with(ResponseTemplate::new(500))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(0)
+    .mount(&test_app._test_server)
+    .await;
+  let collection = users_collection(&test_app._test_server.uri());
+  let options = RunOptions {
+    iterations: Some(3),
+    data: vec![vec![("user".into(), "ann".into())]],
+    stop_on_failure: true,
+    ..Default::default()
+  };
+
+  let report = test_app
+    .app
+    .run_collection(
+      &collection,
+      &collection.requests(),
+      EnvironmentFile::default(),
+      &options,
+      |_: &RunResult| {},
+    )
+    .await
+    .unwrap();
+
+  assert!(report.stopped_early);
+  assert_eq!(report.results.len(), 1);
+  assert_eq!(report.failed(), 1);
+  assert_eq!(
+    report.results[0].test_results[0].message.as_deref(),
+    Some("got 500")
+  );
+}
+
+#[test]
+fn json_data_rows_are_stringified_and_the_last_row_is_reused() {
+  let data = parse_json(r#"[{"user": "ann", "age": 30}, {"user": "bob", "admin": true}]"#).unwrap();
+  let options = RunOptions {
+    iterations: Some(3),
+    data,
+    ..Default::default()
+  };
+
+  assert_eq!(options.iteration_count(), 3);
+  assert_eq!(
+    options.data_for(0),
+    [("age".into(), "30".into()), ("user".into(), "ann".into())]
+  );
+  assert_eq!(options.data_for(2), options.data_for(1));
+  assert_eq!(RunOptions::default().iteration_count(), 1);
+  assert!(parse_json(r#"{"user": "ann"}"#).is_err());
+}
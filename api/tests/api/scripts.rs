@@ -0,0 +1,200 @@
+use api::domain::{
+  assertion::Assertion,
+  collection::Collection,
+  environment::{EnvironmentFile, EnvironmentValue},
+  extract::{ExtractRule, ExtractSource},
+  request::{HttpMethod, HttpRequest, RequestBody, RequestScripts},
+  tab::Tab,
+};
+use serde_json::json;
+use wiremock::{
+  matchers::{body_json, header, method, path},
+  Mock, ResponseTemplate,
+};
+
+use crate::helpers::spawn_test_app;
+
+fn request_with_scripts(host: &str, pre_request: &str, test: &str) -> HttpRequest {
+  HttpRequest {
+    method: HttpMethod::POST,
+    url: String::from("{{HOST_URL}}/users"),
+    body: Some(RequestBody::JSON(json!({"name": "postie"}))),
+    environment: EnvironmentFile {
+      id: String::from("env"),
+      name: String::from("env"),
+      values: Some(vec![EnvironmentValue {
+        key: String::from("HOST_URL"),
+        value: String::from(host),
+        r#type: String::from("default"),
+        enabled: true,
+      }]),
+    },
+    scripts: RequestScripts {
+      pre_request: String::from(pre_request),
+      test: String::from(test),
+    },
+    ..Default::default()
+  }
+}
+
+#[tokio::test]
+async fn pre_request_scripts_can_change_the_request_and_environment() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .and(path("/users/42"))
+    .and(header("X-Trace", "from-script"))
+    .and(body_json(json!({"name": "postie", "age": 3})))
+    .respond_with(ResponseTemplate::new(201))
+    .expect(1)
+    .mount(&test_app._test_server)
+    .await;
+  let input = request_with_scripts(
+    &test_app._test_server.uri(),
+    r#"
+      env.user_id = "42";
+      request.url += "/{{user_id}}";
+      request.headers["X-Trace"] = "from-script";
+      request.body.age = 3;
+    "#,
+    "",
+  );
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  assert_eq!(res.status_code, 201);
+  let environment = res.environment.expect("environment should have changed");
+  let user_id = environment
+    .values
+    .unwrap()
+    .into_iter()
+    .find(|v| v.key == "user_id")
+    .unwrap();
+  assert_eq!(user_id.value, "42");
+}
+
+#[tokio::test]
+async fn test_scripts_record_results_in_the_response_and_history() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": 7, "name": "postie"})))
+    .mount(&test_app._test_server)
+    .await;
+  let input = request_with_scripts(
+    &test_app._test_server.uri(),
+    "",
+    r#"
+      test("status is 200", response.status == 200);
+      test("name is returned", response.json.name == "someone else");
+      env.created_id = response.json.id;
+      undefined_function();
+    "#,
+  );
+
+  let res = test_app.app.send_http_request(input).await.unwrap();
+
+  let results: Vec<(String, bool)> = res
+    .test_results
+    .iter()
+    .map(|t| (t.name.clone(), t.passed))
+    .collect();
+  assert_eq!(
+    results,
+    vec![
+      ("status is 200".to_string(), true),
+      ("name is returned".to_string(), false),
+      ("Test script".to_string(), false),
+    ]
+  );
+  assert!(res.test_results[2]
+    .message
+    .as_ref()
+    .unwrap()
+    .contains("undefined_function"));
+  let created_id = res
+    .environment
+    .unwrap()
+    .values
+    .unwrap()
+    .into_iter()
+    .find(|v| v.key == "created_id")
+    .unwrap();
+  assert_eq!(created_id.value, "7");
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  assert_eq!(history[0].test_results, res.test_results);
+}
+
+#[tokio::test]
+async fn failing_pre_request_scripts_stop_the_request() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("POST"))
+    .respond_with(ResponseTemplate::new(200))
+    .expect(0)
+    .mount(&test_app._test_server)
+    .await;
+  let input = request_with_scripts(&test_app._test_server.uri(), "throw \"nope\";", "");
+
+  let err = test_app.app.send_http_request(input).await.unwrap_err();
+
+  assert!(err.to_string().starts_with("Pre-request script failed"));
+  assert!(test_app
+    .app
+    .load_request_response_items()
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[test]
+fn only_rhai_scripts_are_loaded_from_collection_events() {
+  let collection: Collection = serde_json::from_value(json!({
+    "info": { "_postman_id": "scripts", "name": "scripts" },
+    "item": [
+      {
+        "id": "req",
+        "name": "req",
+        "event": [
+          { "listen": "prerequest", "script": { "type": "text/javascript", "exec": ["pm.environment.set('a', 1)"] } },
+          { "listen": "test", "script": { "type": "text/rhai", "exec": ["let ok = response.status == 200;", "test(\"ok\", ok);"] } }
+        ],
+        "request": { "method": "GET", "url": { "raw": "https://example.com" } }
+      }
+    ]
+  }))
+  .unwrap();
+  let item = collection.requests()[0];
+
+  let input = HttpRequest::from_collection_item(&collection, item, EnvironmentFile::default());
+
+  assert_eq!(input.scripts.pre_request, "");
+  assert_eq!(
+    input.scripts.test,
+    "let ok = response.status == 200;\ntest(\"ok\", ok);"
+  );
+}
+
+#[tokio::test]
+async fn scripts_assertions_and_extract_rules_are_saved_on_tabs() {
+  let mut test_app = spawn_test_app().await;
+  let tab = Tab {
+    scripts: RequestScripts {
+      pre_request: String::from(r#"env["token"] = "abc";"#),
+      test: String::from(r#"test("ok", || response.status == 200);"#),
+    },
+    assertions: vec![Assertion::StatusEquals { status: 201 }],
+    extract: vec![ExtractRule {
+      variable: String::from("id"),
+      source: ExtractSource::JsonPath {
+        path: String::from("$.id"),
+      },
+    }],
+    ..Default::default()
+  };
+  test_app.app.save_tab(&tab).await.unwrap();
+
+  let tabs = test_app.app.load_tabs().await.unwrap();
+  let saved = tabs.into_iter().find(|t| t.id == tab.id).unwrap();
+
+  assert_eq!(saved.scripts, tab.scripts);
+  assert_eq!(saved.assertions, tab.assertions);
+  assert_eq!(saved.extract, tab.extract);
+}
@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use api::{
+  domain::{request::HttpRequest, response::ResponseData, sse::SseEvent},
+  utilities::sse::SseParser,
+};
+use tokio::{io::AsyncWriteExt, net::TcpListener, sync::watch};
+use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+use crate::helpers::spawn_test_app;
+
+fn get(url: String) -> HttpRequest {
+  HttpRequest {
+    url,
+    ..Default::default()
+  }
+}
+
+fn summary(events: &[SseEvent]) -> Vec<(Option<&str>, &str, &str)> {
+  events
+    .iter()
+    .map(|e| (e.id.as_deref(), e.event_type(), e.data.as_str()))
+    .collect()
+}
+
+#[test]
+fn events_are_parsed_across_chunk_boundaries() {
+  let stream = "retry: 500\n: keep alive\ndata: first\r\n\r\nid: 7\nevent: tick\ndata:  two\ndata: lines\n\ndata: caf\u{e9}\n\ndata\n\nevent: empty\n\n";
+  let bytes = stream.as_bytes();
+  let mut parser = SseParser::new();
+  let mut events = vec![];
+  // one byte at a time splits every \r\n and the two byte é
+  for byte in bytes {
+    events.extend(parser.push(&[*byte]));
+  }
+  events.extend(parser.finish());
+
+  assert_eq!(
+    summary(&events),
+    vec![
+      (None, "message", "first"),
+      (Some("7"), "tick", " two\nlines"),
+      (Some("7"), "message", "caf\u{e9}"),
+      (Some("7"), "message", ""),
+    ]
+  );
+  assert_eq!(events[0].retry, Some(500));
+}
+
+#[tokio::test]
+async fn event_streams_are_passed_on_as_they_arrive_and_saved() {
+  let mut test_app = spawn_test_app().await;
+  Mock::given(method("GET"))
+    .respond_with(ResponseTemplate::new(200).set_body_raw(
+      "event: greeting\ndata: hello\n\nid: 2\ndata: {\"n\": 2}\n\n",
+      "text/event-stream",
+    ))
+    .mount(&test_app._test_server)
+    .await;
+  let seen = Arc::new(Mutex::new(vec![]));
+  let seen_by_stream = Arc::clone(&seen);
+
+  let res = test_app
+    .app
+    .send_http_request_streaming(
+      get(test_app._test_server.uri()),
+      None,
+      move |e| seen_by_stream.lock().unwrap().push(e.clone()),
+      |_| {},
+    )
+    .await
+    .unwrap();
+
+  let seen = seen.lock().unwrap().clone();
+  assert_eq!(
+    summary(&seen),
+    vec![
+      (None, "greeting", "hello"),
+      (Some("2"), "message", "{\"n\": 2}")
+    ]
+  );
+  let ResponseData::JSON(transcript) = res.data else {
+    panic!("expected the events as json");
+  };
+  assert_eq!(
+    serde_json::from_value::<Vec<SseEvent>>(transcript).unwrap(),
+    seen
+  );
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  let responses = test_app.app.load_saved_responses().await.unwrap();
+  let saved = responses
+    .iter()
+    .find(|r| r.id == history[0].response_id)
+    .unwrap();
+  let saved: Vec<SseEvent> = serde_json::from_str(saved.body.as_deref().unwrap()).unwrap();
+  assert_eq!(saved, seen);
+}
+
+#[tokio::test]
+async fn stopping_ends_a_stream_that_is_still_open() {
+  let mut test_app = spawn_test_app().await;
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+  tokio::spawn(async move {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    socket
+      .write_all(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\ndata: first\n\n",
+      )
+      .await
+      .unwrap();
+    // never sends anything else or closes
+    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+  });
+  let (stop, stop_rx) = watch::channel(false);
+
+  let res = test_app
+    .app
+    .send_http_request_streaming(
+      get(url),
+      Some(stop_rx),
+      move |_| {
+        stop.send(true).unwrap();
+      },
+      |_| {},
+    )
+    .await
+    .unwrap();
+
+  let ResponseData::JSON(transcript) = res.data else {
+    panic!("expected the events as json");
+  };
+  let events: Vec<SseEvent> = serde_json::from_value(transcript).unwrap();
+  assert_eq!(summary(&events), vec![(None, "message", "first")]);
+}
@@ -0,0 +1,214 @@
+use std::time::{Duration, Instant};
+
+use api::domain::{
+  request::{HttpRequest, RequestTimeouts},
+  tab::Tab,
+};
+use tokio::{io::AsyncWriteExt, net::TcpListener, sync::watch};
+use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_test_app, TestApp};
+
+fn get(url: String, timeouts: RequestTimeouts) -> HttpRequest {
+  HttpRequest {
+    url,
+    timeouts,
+    ..Default::default()
+  }
+}
+
+async fn respond_after(test_app: &TestApp, delay: Duration) {
+  Mock::given(method("GET"))
+    .respond_with(
+      ResponseTemplate::new(200)
+        .set_body_string("late")
+        .set_delay(delay),
+    )
+    .mount(&test_app._test_server)
+    .await;
+}
+
+#[tokio::test]
+async fn slow_servers_time_out_with_the_default_read_timeout() {
+  let mut test_app = spawn_test_app().await;
+  test_app
+    .app
+    .save_default_timeouts(&RequestTimeouts {
+      read_ms: Some(100),
+      ..RequestTimeouts::DEFAULTS
+    })
+    .await
+    .unwrap();
+  respond_after(&test_app, Duration::from_secs(5)).await;
+
+  let err = test_app
+    .app
+    .send_http_request(get(test_app._test_server.uri(), RequestTimeouts::default()))
+    .await
+    .err()
+    .unwrap();
+
+  assert_eq!(
+    err.to_string(),
+    "Timed out after 100 ms waiting for a response"
+  );
+  assert!(test_app
+    .app
+    .load_request_response_items()
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[tokio::test]
+async fn requests_can_lift_the_default_timeouts() {
+  let mut test_app = spawn_test_app().await;
+  test_app
+    .app
+    .save_default_timeouts(&RequestTimeouts {
+      read_ms: Some(100),
+      ..RequestTimeouts::DEFAULTS
+    })
+    .await
+    .unwrap();
+  respond_after(&test_app, Duration::from_millis(300)).await;
+  // 0 is no limit
+  let timeouts = RequestTimeouts {
+    read_ms: Some(0),
+    ..Default::default()
+  };
+
+  let res = test_app
+    .app
+    .send_http_request(get(test_app._test_server.uri(), timeouts))
+    .await
+    .unwrap();
+
+  assert_eq!(res.status_code, 200);
+}
+
+#[tokio::test]
+async fn the_total_timeout_includes_reading_the_body() {
+  let mut test_app = spawn_test_app().await;
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+  tokio::spawn(async move {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    // starts the body, then keeps sending a byte now and then without ever finishing
+    socket
+      .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 100\r\n\r\n")
+      .await
+      .unwrap();
+    for _ in 0..100 {
+      if socket.write_all(b"a").await.is_err() {
+        return;
+      }
+      tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+  });
+  let timeouts = RequestTimeouts {
+    read_ms: Some(1000),
+    total_ms: Some(300),
+    ..Default::default()
+  };
+
+  let err = test_app
+    .app
+    .send_http_request(get(url, timeouts))
+    .await
+    .err()
+    .unwrap();
+
+  assert_eq!(err.to_string(), "Timed out after 300 ms in total");
+}
+
+#[tokio::test]
+async fn bodies_that_stop_arriving_time_out() {
+  let mut test_app = spawn_test_app().await;
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+  tokio::spawn(async move {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    socket
+      .write_all(
+        b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 100\r\n\r\nstarted",
+      )
+      .await
+      .unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+  });
+  let timeouts = RequestTimeouts {
+    read_ms: Some(200),
+    ..Default::default()
+  };
+
+  let err = test_app
+    .app
+    .send_http_request(get(url, timeouts))
+    .await
+    .err()
+    .unwrap();
+
+  assert_eq!(
+    err.to_string(),
+    "Timed out after 200 ms waiting for the rest of the response body"
+  );
+}
+
+#[tokio::test]
+async fn requests_are_cancelled_while_waiting_for_a_response() {
+  let mut test_app = spawn_test_app().await;
+  respond_after(&test_app, Duration::from_secs(30)).await;
+  let (stop, stop_rx) = watch::channel(false);
+  tokio::spawn(async move {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    stop.send(true).unwrap();
+  });
+  let started = Instant::now();
+
+  let err = test_app
+    .app
+    .send_http_request_streaming(
+      get(test_app._test_server.uri(), RequestTimeouts::default()),
+      Some(stop_rx),
+      |_| {},
+      |_| {},
+    )
+    .await
+    .err()
+    .unwrap();
+
+  assert_eq!(err.to_string(), "Request was cancelled");
+  assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn timeouts_are_saved_as_defaults_and_on_tabs() {
+  let mut test_app = spawn_test_app().await;
+  assert_eq!(
+    test_app.app.load_default_timeouts().await.unwrap(),
+    RequestTimeouts::DEFAULTS
+  );
+  let defaults = RequestTimeouts {
+    connect_ms: Some(2000),
+    read_ms: Some(0),
+    total_ms: Some(60_000),
+  };
+  test_app.app.save_default_timeouts(&defaults).await.unwrap();
+  let tab = Tab {
+    timeouts: RequestTimeouts {
+      total_ms: Some(500),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  test_app.app.save_tab(&tab).await.unwrap();
+
+  assert_eq!(
+    test_app.app.load_default_timeouts().await.unwrap(),
+    defaults
+  );
+  let tabs = test_app.app.load_tabs().await.unwrap();
+  let saved = tabs.iter().find(|t| t.id == tab.id).unwrap();
+  assert_eq!(saved.timeouts, tab.timeouts);
+}
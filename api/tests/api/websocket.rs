@@ -0,0 +1,174 @@
+use api::domain::{
+  environment::EnvironmentFile,
+  websocket::{
+    WebSocketCommand, WebSocketDirection, WebSocketFrame, WebSocketMessage, WebSocketMessageKind,
+    WebSocketRequest,
+  },
+};
+use futures_util::{SinkExt, StreamExt};
+use std::sync::{Arc, Mutex};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use uuid::Uuid;
+
+use crate::helpers::spawn_test_app;
+
+// Echoes text and binary messages back, returns its url and the token header it was opened with.
+// The handshake callback's error is an http response, which clippy finds too large
+#[allow(clippy::result_large_err)]
+async fn spawn_echo_server() -> (String, tokio::sync::oneshot::Receiver<Option<String>>) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("ws://{}", listener.local_addr().unwrap());
+  let (token_tx, token_rx) = tokio::sync::oneshot::channel();
+  tokio::spawn(async move {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut token = None;
+    let mut socket = tokio_tungstenite::accept_hdr_async(stream, |req: &Request, res: Response| {
+      token = req
+        .headers()
+        .get("x-token")
+        .map(|t| t.to_str().unwrap().to_string());
+      Ok(res)
+    })
+    .await
+    .unwrap();
+    let _ = token_tx.send(token);
+    // the close handshake is answered by the stream, reading on ends once it is done
+    while let Some(Ok(message)) = socket.next().await {
+      if message.is_text() || message.is_binary() {
+        socket.send(message).await.unwrap();
+      }
+    }
+  });
+  (url, token_rx)
+}
+
+fn websocket_request(url: String) -> WebSocketRequest {
+  WebSocketRequest {
+    tab_id: Uuid::new_v4(),
+    id: Uuid::new_v4(),
+    name: None,
+    url,
+    headers: Some(vec![("X-Token".into(), "{{token}}".into())]),
+    environment: EnvironmentFile::default(),
+    variables: vec![("token".into(), "secret".into())],
+  }
+}
+
+fn lines(
+  transcript: &[WebSocketMessage],
+  direction: WebSocketDirection,
+) -> Vec<(WebSocketMessageKind, String)> {
+  transcript
+    .iter()
+    .filter(|m| m.direction == direction)
+    .map(|m| (m.kind, m.data.clone()))
+    .collect()
+}
+
+#[tokio::test]
+async fn a_websocket_session_sends_frames_and_logs_the_echoes() {
+  let test_app = spawn_test_app().await;
+  let (url, token) = spawn_echo_server().await;
+  let input = websocket_request(url);
+  let connection = test_app.app.connect_websocket(&input).await.unwrap();
+  assert_eq!(token.await.unwrap().as_deref(), Some("secret"));
+
+  let (tx, rx) = mpsc::channel(8);
+  for frame in [
+    WebSocketFrame::Text("hello".into()),
+    WebSocketFrame::Json("{ \"a\": 1 }".into()),
+    WebSocketFrame::Json("{ nope".into()),
+    WebSocketFrame::Binary(vec![1, 2, 3]),
+  ] {
+    tx.send(WebSocketCommand::Send(frame)).await.unwrap();
+  }
+  let seen = Arc::new(Mutex::new(vec![]));
+  let seen_by_run = Arc::clone(&seen);
+  let handle =
+    tokio::spawn(connection.run(rx, move |m| seen_by_run.lock().unwrap().push(m.clone())));
+  // close once every echo came back
+  tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+  tx.send(WebSocketCommand::Close).await.unwrap();
+  let transcript = handle.await.unwrap();
+
+  let transcript = transcript.messages;
+  assert_eq!(*seen.lock().unwrap(), transcript);
+  let sent = vec![
+    (WebSocketMessageKind::Text, "hello".to_string()),
+    (WebSocketMessageKind::Text, "{\"a\":1}".to_string()),
+    (WebSocketMessageKind::Binary, "AQID".to_string()),
+  ];
+  assert_eq!(lines(&transcript, WebSocketDirection::Sent), sent);
+  assert_eq!(lines(&transcript, WebSocketDirection::Received), sent);
+  let events: Vec<WebSocketMessageKind> = lines(&transcript, WebSocketDirection::Event)
+    .into_iter()
+    .map(|(kind, _)| kind)
+    .collect();
+  assert_eq!(
+    events,
+    vec![
+      WebSocketMessageKind::Open,
+      WebSocketMessageKind::Error,
+      WebSocketMessageKind::Close
+    ]
+  );
+}
+
+#[tokio::test]
+async fn websocket_sessions_are_saved_to_the_history() {
+  let mut test_app = spawn_test_app().await;
+  let (url, _) = spawn_echo_server().await;
+  let input = websocket_request(url.clone());
+  let mut connection = test_app.app.connect_websocket(&input).await.unwrap();
+  connection
+    .send(WebSocketFrame::Text("ping".into()))
+    .await
+    .unwrap();
+  connection.next_message().await.unwrap();
+  connection.close().await.unwrap();
+
+  test_app
+    .app
+    .save_websocket_session(&input, &connection.transcript)
+    .await
+    .unwrap();
+
+  let history = test_app.app.load_request_response_items().await.unwrap();
+  assert_eq!(history.len(), 1);
+  let requests = test_app.app.load_saved_requests().await.unwrap();
+  let request = requests
+    .iter()
+    .find(|r| r.id == input.id.to_string())
+    .unwrap();
+  assert_eq!(request.url, url);
+  assert_eq!(request.method, "GET");
+  let responses = test_app.app.load_saved_responses().await.unwrap();
+  let response = responses
+    .iter()
+    .find(|r| r.id == history[0].response_id)
+    .unwrap();
+  assert_eq!(response.status_code, 101);
+  let saved: Vec<WebSocketMessage> =
+    serde_json::from_str(response.body.as_deref().unwrap()).unwrap();
+  assert_eq!(saved, connection.transcript.messages);
+}
+
+#[tokio::test]
+async fn connecting_to_a_closed_port_is_an_error() {
+  let test_app = spawn_test_app().await;
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("ws://{}", listener.local_addr().unwrap());
+  drop(listener);
+
+  let err = test_app
+    .app
+    .connect_websocket(&websocket_request(url))
+    .await
+    .err()
+    .unwrap();
+
+  assert!(err
+    .to_string()
+    .starts_with("Could not connect to ws://127.0.0.1"));
+}
@@ -0,0 +1,234 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use api::{
+  db::repository::{self, PostieDb},
+  domain::{
+    collection::{Collection, CollectionItem},
+    environment::EnvironmentFile,
+    run::{RunOptions, RunResult},
+  },
+  utilities::data_file::parse_data_file,
+  PostieApi,
+};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(
+  name = "postie",
+  about = "Run saved Postie collections without the gui"
+)]
+struct Cli {
+  /// Path to the sqlite db, defaults to the same one the gui uses. Pending migrations are run on it
+  /// before anything else, so a db from an older version can be used directly
+  #[arg(long, global = true)]
+  db: Option<PathBuf>,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// List saved collections
+  Collections,
+  /// List saved environments
+  Environments,
+  /// Run every request in a collection, or just one folder or request from it
+  Run {
+    /// Collection name or id
+    collection: String,
+    /// Only run requests inside this folder (name or id)
+    #[arg(long)]
+    folder: Option<String>,
+    /// Only run this request (name or id)
+    #[arg(long)]
+    request: Option<String>,
+    /// Environment name or id to substitute variables from
+    #[arg(short, long)]
+    environment: Option<String>,
+    /// How many times to run the requests, defaults to one per data file row
+    #[arg(short = 'n', long)]
+    iterations: Option<usize>,
+    /// CSV or JSON file whose rows are used as variables, one row per iteration
+    #[arg(short, long)]
+    data: Option<PathBuf>,
+    /// Milliseconds to wait between requests
+    #[arg(long, default_value_t = 0)]
+    delay: u64,
+    /// Stop the run at the first failed request
+    #[arg(long)]
+    stop_on_failure: bool,
+  },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+  env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+  let cli = Cli::parse();
+  match run_command(cli).await {
+    Ok(code) => code,
+    Err(e) => {
+      eprintln!("error: {e:#}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+async fn run_command(cli: Cli) -> anyhow::Result<ExitCode> {
+  let db_path = cli.db.unwrap_or_else(repository::get_persistent_db_path);
+  let mut api = PostieApi::with_db(PostieDb::from_path(db_path).await?);
+  match cli.command {
+    Command::Collections => {
+      for collection in api.load_collections().await? {
+        println!(
+          "{}\t{}\t{} requests",
+          collection.info.id,
+          collection.info.name,
+          collection.requests().len()
+        );
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Command::Environments => {
+      for environment in api.load_environments().await? {
+        println!("{}\t{}", environment.id, environment.name);
+      }
+      Ok(ExitCode::SUCCESS)
+    }
+    Command::Run {
+      collection,
+      folder,
+      request,
+      environment,
+      iterations,
+      data,
+      delay,
+      stop_on_failure,
+    } => {
+      let data = match data {
+        Some(path) => parse_data_file(&path.to_string_lossy())?,
+        None => vec![],
+      };
+      let options = RunOptions {
+        iterations,
+        data,
+        delay_ms: delay,
+        stop_on_failure,
+      };
+      run_collection(&mut api, &collection, folder, request, environment, options).await
+    }
+  }
+}
+
+async fn run_collection(
+  api: &mut PostieApi,
+  collection_name: &str,
+  folder: Option<String>,
+  request: Option<String>,
+  environment_name: Option<String>,
+  options: RunOptions,
+) -> anyhow::Result<ExitCode> {
+  let collection = find_collection(api, collection_name).await?;
+  let environment = match environment_name {
+    Some(name) => find_environment(api, &name).await?,
+    None => EnvironmentFile {
+      id: "".into(),
+      name: "none".into(),
+      values: None,
+    },
+  };
+
+  let mut items: Vec<&CollectionItem> = match &folder {
+    Some(f) => collection
+      .find_folder(f)
+      .ok_or_else(|| anyhow::anyhow!("no folder named {f} in {}", collection.info.name))?
+      .requests(),
+    None => collection.requests(),
+  };
+  if let Some(r) = &request {
+    items.retain(|i| &i.id == r || &i.name == r);
+    if items.is_empty() {
+      anyhow::bail!("no request named {r} in {}", collection.info.name);
+    }
+  }
+
+  let iterations = options.iteration_count();
+  println!(
+    "Running {} ({} requests, {} iterations) with environment {}",
+    collection.info.name,
+    items.len(),
+    iterations,
+    environment.name
+  );
+  let report = api
+    .run_collection(&collection, &items, environment, &options, |result| {
+      print_result(result, iterations > 1)
+    })
+    .await?;
+
+  if report.stopped_early {
+    println!("\nStopped at the first failure");
+  }
+  let failed = report.failed();
+  println!(
+    "\n{} requests, {} passed, {} failed in {} ms",
+    report.results.len(),
+    report.passed(),
+    failed,
+    report.duration_ms
+  );
+  if failed > 0 {
+    Ok(ExitCode::FAILURE)
+  } else {
+    Ok(ExitCode::SUCCESS)
+  }
+}
+
+fn print_result(result: &RunResult, show_iteration: bool) {
+  let label = if result.passed() { "PASS" } else { "FAIL" };
+  let name = if show_iteration {
+    format!("#{} {}", result.iteration, result.name)
+  } else {
+    result.name.clone()
+  };
+  match &result.error {
+    None => {
+      println!(
+        "  {label}  {} {} {}  {}  {} ms",
+        name,
+        result.method,
+        result.url,
+        result.status.as_deref().unwrap_or_default(),
+        result.response_time.unwrap_or_default()
+      );
+      for test in &result.test_results {
+        let mark = if test.passed { "ok" } else { "failed" };
+        match &test.message {
+          Some(message) => println!("          {mark}  {}: {message}", test.name),
+          None => println!("          {mark}  {}", test.name),
+        }
+      }
+    }
+    Some(e) => println!(
+      "  {label}  {} {} {}  error: {e}",
+      name, result.method, result.url
+    ),
+  }
+}
+
+async fn find_collection(api: &PostieApi, name_or_id: &str) -> anyhow::Result<Collection> {
+  api
+    .load_collections()
+    .await?
+    .into_iter()
+    .find(|c| c.info.id == name_or_id || c.info.name == name_or_id)
+    .ok_or_else(|| anyhow::anyhow!("no collection named {name_or_id}"))
+}
+
+async fn find_environment(api: &PostieApi, name_or_id: &str) -> anyhow::Result<EnvironmentFile> {
+  api
+    .load_environments()
+    .await?
+    .into_iter()
+    .find(|e| e.id == name_or_id || e.name == name_or_id)
+    .ok_or_else(|| anyhow::anyhow!("no environment named {name_or_id}"))
+}
@@ -1,10 +1,18 @@
-use std::{cell::RefCell, collections::HashSet, rc::Rc, sync::Arc};
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet},
+  rc::Rc,
+  sync::Arc,
+};
 
 use api::domain::{
+  auth::RequestAuth,
   environment::EnvironmentFile,
+  grpc::GrpcRequest,
   request::{self, HttpMethod, HttpRequest},
   tab::Tab,
   ui::{self, RequestWindowMode},
+  websocket::WebSocketRequest,
 };
 use egui::{ComboBox, InnerResponse, TopBottomPanel};
 use tokio::sync::{mpsc::Sender, RwLock};
@@ -17,6 +25,12 @@ pub struct ContentHeaderPanel {
   pub request_window_mode: Arc<RwLock<RequestWindowMode>>,
 }
 
+impl Default for ContentHeaderPanel {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl ContentHeaderPanel {
   pub fn new() -> Self {
     Self {
@@ -31,10 +45,7 @@ impl ContentHeaderPanel {
     active_tab: Arc<RwLock<Tab>>,
     environment: Rc<RefCell<EnvironmentFile>>,
     headers: Rc<RefCell<Vec<(bool, String, String)>>>,
-    auth_mode: ui::AuthMode,
-    api_key_name: String,
-    api_key: String,
-    bearer_token: String,
+    grpc_proto_files: Rc<RefCell<HashMap<Uuid, Vec<String>>>>,
     oauth_token: String,
   ) -> InnerResponse<Option<HttpRequest>> {
     TopBottomPanel::top("top_panel").show(ctx, |ui| {
@@ -44,10 +55,7 @@ impl ContentHeaderPanel {
         active_tab,
         environment,
         headers,
-        auth_mode,
-        api_key_name,
-        api_key,
-        bearer_token,
+        grpc_proto_files,
         oauth_token,
       )
     })
@@ -60,10 +68,7 @@ impl ContentHeaderPanel {
     active_tab: Arc<RwLock<Tab>>,
     environment: Rc<RefCell<EnvironmentFile>>,
     headers: Rc<RefCell<Vec<(bool, String, String)>>>,
-    auth_mode: ui::AuthMode,
-    api_key_name: String,
-    api_key: String,
-    bearer_token: String,
+    grpc_proto_files: Rc<RefCell<HashMap<Uuid, Vec<String>>>>,
     oauth_token: String,
   ) -> Option<HttpRequest> {
     ui.horizontal(|ui| {
@@ -76,23 +81,80 @@ impl ContentHeaderPanel {
         ui.add(egui::TextEdit::singleline(&mut tab.url).desired_width(400.0));
       }
 
+      // websocket tabs open a connection instead, messages are sent from the body tab
+      let is_websocket = active_tab
+        .try_read()
+        .is_ok_and(|tab| WebSocketRequest::is_websocket_url(&tab.url));
+      // grpc tabs call the method in their url with the body as the message
+      let is_grpc = active_tab
+        .try_read()
+        .is_ok_and(|tab| GrpcRequest::is_grpc_url(&tab.url));
+      if is_grpc {
+        let invoke = ui.button("Invoke").clicked();
+        let load_methods = ui.button("Load Methods").clicked();
+        if invoke || load_methods {
+          if let Some(req) = self.build_request(
+            active_tab.clone(),
+            environment.clone(),
+            headers.clone(),
+            oauth_token.clone(),
+          ) {
+            let message = active_tab
+              .try_read()
+              .map(|tab| tab.req_body.clone())
+              .unwrap_or_default();
+            let proto_files = grpc_proto_files
+              .borrow()
+              .get(&req.tab_id)
+              .cloned()
+              .unwrap_or_default();
+            let req = GrpcRequest::from_http_request(req, message, proto_files);
+            let _ = event_tx.try_send(if invoke {
+              events::GuiEvent::SubmitGrpcRequest(req)
+            } else {
+              events::GuiEvent::LoadGrpcMethods(req)
+            });
+          }
+        }
+      } else if is_websocket {
+        if ui.button("Connect").clicked() {
+          if let Some(req) = self.build_request(
+            active_tab.clone(),
+            environment.clone(),
+            headers.clone(),
+            oauth_token.clone(),
+          ) {
+            let _ = event_tx.try_send(events::GuiEvent::ConnectWebSocket(
+              WebSocketRequest::from_http_request(req),
+            ));
+          }
+        }
       // Submit Button
-      if ui.button("Submit").clicked() {
-        if let Some(req) = self.build_request(
-          active_tab,
-          environment,
-          headers,
-          auth_mode,
-          api_key_name,
-          api_key,
-          bearer_token,
-          oauth_token,
+      } else if ui.button("Submit").clicked() {
+        let auth = active_tab.try_read().map(|tab| tab.auth.clone());
+        if let (Some(request), Ok(auth)) = (
+          self.build_request(
+            active_tab.clone(),
+            environment.clone(),
+            headers.clone(),
+            oauth_token.clone(),
+          ),
+          auth,
         ) {
           event_tx
-            .try_send(events::GuiEvent::SubmitRequest(req))
+            .try_send(events::GuiEvent::SubmitRequest { request, auth })
             .unwrap();
         };
       }
+
+      let is_graphql = active_tab
+        .try_read()
+        .is_ok_and(|tab| tab.body_type == request::BodyType::GRAPHQL);
+      if is_graphql && ui.button("Fetch Schema").clicked() {
+        if let Some(req) = self.build_request(active_tab, environment, headers, oauth_token) {
+          let _ = event_tx.try_send(events::GuiEvent::FetchGraphQLSchema(req));
+        }
+      }
     });
 
     self.render_mode_switcher(ui);
@@ -127,6 +189,10 @@ impl ContentHeaderPanel {
           ("Auth", ui::RequestWindowMode::AUTHORIZATION),
           ("Headers", ui::RequestWindowMode::HEADERS),
           ("Body", ui::RequestWindowMode::BODY),
+          ("Scripts", ui::RequestWindowMode::SCRIPTS),
+          ("Assertions", ui::RequestWindowMode::ASSERTIONS),
+          ("Extract", ui::RequestWindowMode::EXTRACT),
+          ("Settings", ui::RequestWindowMode::SETTINGS),
         ] {
           if ui.button(label).clicked() {
             *mode = target_mode;
@@ -141,51 +207,47 @@ impl ContentHeaderPanel {
     active_tab: Arc<RwLock<Tab>>,
     environment: Rc<RefCell<EnvironmentFile>>,
     headers: Rc<RefCell<Vec<(bool, String, String)>>>,
-    auth_mode: ui::AuthMode,
-    api_key_name: String,
-    api_key: String,
-    bearer_token: String,
     oauth_token: String,
   ) -> Option<request::HttpRequest> {
-    let body = if active_tab.try_read().unwrap().method != request::HttpMethod::GET {
-      Some(request::RequestBody::JSON(
-        serde_json::from_str(&active_tab.try_read().unwrap().req_body).unwrap_or_default(),
-      ))
-    } else {
-      None
+    let body = match active_tab.try_read() {
+      Ok(tab) if tab.method != request::HttpMethod::GET => {
+        request::RequestBody::from_text(&tab.body_type, &tab.req_body)
+      }
+      _ => None,
     };
     let active_tab_guard = Arc::clone(&active_tab);
-    let processed_headers = self.process_headers(
-      &*headers.borrow(),
-      auth_mode,
-      api_key_name,
-      api_key,
-      bearer_token,
-      oauth_token,
-    );
 
     match active_tab_guard.clone().try_read() {
       Ok(tab) => Some(request::HttpRequest {
         tab_id: tab.id,
         id: Uuid::new_v4(),
         name: None,
-        headers: Some(processed_headers),
+        headers: Some(self.process_headers(
+          &headers.borrow(),
+          &tab.auth.to_request_auth(),
+          oauth_token,
+        )),
         body,
         method: tab.method.clone(),
         url: tab.url.clone(),
         environment: environment.borrow().clone(),
+        variables: vec![],
+        scripts: tab.scripts.clone(),
+        assertions: tab.assertions.clone(),
+        extract: tab.extract.clone(),
+        timeouts: tab.timeouts,
+        auth: tab.auth.to_request_auth(),
       }),
       Err(_) => None,
     }
   }
 
+  // Websocket and grpc tabs only send headers, so auth that is a fixed header is added to them
+  // here. The api applies the rest of the auth to http requests, see PostieApi::apply_auth
   fn process_headers(
     &self,
-    headers: &Vec<(bool, String, String)>,
-    auth_mode: ui::AuthMode,
-    api_key_name: String,
-    api_key: String,
-    bearer_token: String,
+    headers: &[(bool, String, String)],
+    auth: &RequestAuth,
     oauth_token: String,
   ) -> Vec<(String, String)> {
     let mut headers = headers
@@ -194,15 +256,12 @@ impl ContentHeaderPanel {
       .map(|h| (h.1.clone(), h.2.clone()))
       .collect::<Vec<_>>();
 
-    match auth_mode {
-      ui::AuthMode::APIKEY => headers.push((api_key_name, api_key)),
-      ui::AuthMode::BEARER => {
-        headers.push(("Authorization".into(), format!("Bearer {}", bearer_token)))
-      }
-      ui::AuthMode::OAUTH2 => {
+    match auth {
+      // the token got in the auth tab
+      RequestAuth::OAUTH2(_) if !oauth_token.is_empty() => {
         headers.push(("Authorization".into(), format!("Bearer {}", oauth_token)))
       }
-      _ => (),
+      auth => headers.extend(auth.header()),
     }
 
     let mut unique_keys = HashSet::new();
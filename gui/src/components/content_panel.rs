@@ -1,24 +1,52 @@
 use std::{
   cell::RefCell,
+  collections::HashMap,
+  path::Path,
   rc::Rc,
   sync::{Arc, Mutex},
 };
 
 use api::domain::{
+  assertion::Assertion,
+  auth::{ApiKeyPlacement, JwtAuth, OAuth2ClientAuth, OAuth2Grant, RequestAuth},
+  cookie::Cookie,
   environment::{EnvironmentFile, EnvironmentValue},
+  extract::{ExtractRule, ExtractSource},
+  graphql::GraphQLSchema,
+  grpc::{GrpcMethod, GrpcRequest},
   request,
-  response::{OAuthResponse, ResponseData},
+  response::ResponseData,
+  tab::Tab,
   ui::{self},
+  websocket::{
+    WebSocketCommand, WebSocketDirection, WebSocketFrame, WebSocketMessage, WebSocketMessageKind,
+    WebSocketRequest,
+  },
+};
+use api::utilities::graphql::complete;
+use base64::Engine as _;
+use egui::{
+  text::{CCursor, CCursorRange},
+  CentralPanel, ScrollArea, TextEdit, TextStyle, TopBottomPanel,
 };
-use egui::{CentralPanel, ScrollArea, TextEdit, TextStyle, TopBottomPanel};
 use egui_extras::{Column, TableBuilder};
 use egui_json_tree::JsonTree;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
-use crate::{events, GuiState, ThreadSafeState};
+use crate::{
+  components::{cookies_window::cookie_expiry, settings_window::timeout_fields},
+  events, GuiState, ThreadSafeState, WebSocketSession,
+};
 
 pub struct ContentPanel {}
 
+impl Default for ContentPanel {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl ContentPanel {
   pub fn new() -> Self {
     Self {}
@@ -37,47 +65,140 @@ impl ContentPanel {
       ui::RequestWindowMode::BODY
     };
 
+    let is_websocket = worker_state
+      .active_tab
+      .try_read()
+      .is_ok_and(|tab| WebSocketRequest::is_websocket_url(&tab.url));
+    let is_grpc = worker_state
+      .active_tab
+      .try_read()
+      .is_ok_and(|tab| GrpcRequest::is_grpc_url(&tab.url));
     match mode {
-      ui::RequestWindowMode::BODY => {
-        self.render_body_tab(ctx, &mut gui_state.body_str.clone(), &worker_state.response)
+      ui::RequestWindowMode::BODY if is_websocket => self.render_websocket_tab(
+        ctx,
+        &worker_state.active_tab,
+        &worker_state.websocket_sessions,
+        &gui_state.websocket_frame_mode,
+        &gui_state.websocket_draft,
+      ),
+      ui::RequestWindowMode::BODY if is_grpc => {
+        self.render_grpc_tab(
+          ctx,
+          &worker_state.active_tab,
+          &gui_state.grpc_proto_files,
+          &gui_state.grpc_methods,
+        );
+        self.render_response_panel(ctx, worker_state, &gui_state.response_cookies, event_tx);
       }
+      ui::RequestWindowMode::BODY => self.render_body_tab(
+        ctx,
+        worker_state,
+        &gui_state.graphql_schemas,
+        &gui_state.response_cookies,
+        event_tx,
+      ),
       ui::RequestWindowMode::AUTHORIZATION => {
         self.render_auth_tab(
           ctx,
-          &mut gui_state.selected_auth_mode.clone(),
-          &mut gui_state.api_key_name.clone(),
-          &mut gui_state.api_key.clone(),
-          &mut gui_state.bearer_token.clone(),
-          &mut gui_state.oauth_config.clone(),
-          &mut gui_state.oauth_token.clone(),
-          &worker_state.received_token,
-          &worker_state.oauth_response,
+          &gui_state.oauth_token,
+          &gui_state.oauth_status,
+          worker_state,
+          &gui_state.selected_environment,
           event_tx,
         );
       }
       ui::RequestWindowMode::HEADERS => self.render_headers_tab(ctx, &gui_state.headers),
-      ui::RequestWindowMode::ENVIRONMENT => {
-        self.render_environment_tab(ctx, &gui_state.selected_environment)
-      }
+      ui::RequestWindowMode::ENVIRONMENT => self.render_environment_tab(
+        ctx,
+        &gui_state.selected_environment,
+        &gui_state.globals,
+        event_tx,
+      ),
       ui::RequestWindowMode::PARAMS => {
         self.render_params_tab(ctx, &mut gui_state.url.clone());
       }
+      ui::RequestWindowMode::SCRIPTS => self.render_scripts_tab(ctx, &worker_state.active_tab),
+      ui::RequestWindowMode::ASSERTIONS => {
+        self.render_assertions_tab(ctx, &worker_state.active_tab)
+      }
+      ui::RequestWindowMode::EXTRACT => self.render_extract_tab(ctx, &worker_state.active_tab),
+      ui::RequestWindowMode::SETTINGS => {
+        self.render_settings_tab(ctx, &worker_state.active_tab, &gui_state.default_timeouts)
+      }
     }
   }
 
   fn render_body_tab(
     &mut self,
     ctx: &egui::Context,
-    body_str: &mut String,
-    response_lock: &Arc<RwLock<Option<ResponseData>>>,
+    worker_state: &ThreadSafeState,
+    graphql_schemas: &Rc<RefCell<HashMap<String, Result<GraphQLSchema, String>>>>,
+    response_cookies: &Rc<RefCell<HashMap<Uuid, Vec<Cookie>>>>,
+    event_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
   ) {
     TopBottomPanel::top("request_panel")
       .resizable(true)
       .min_height(250.0)
       .show(ctx, |ui| {
+        let Ok(mut tab) = worker_state.active_tab.try_write() else {
+          return;
+        };
+        let tab = &mut *tab;
+        ui.horizontal(|ui| {
+          egui::ComboBox::from_label("Body type")
+            .selected_text(tab.body_type.to_string())
+            .show_ui(ui, |ui| {
+              for body_type in request::BodyType::all() {
+                let label = body_type.to_string();
+                ui.selectable_value(&mut tab.body_type, body_type, label);
+              }
+            });
+          ui.weak(tab.body_type.content_type());
+        });
+        if tab.body_type == request::BodyType::MULTIPART {
+          // the tab keeps the parts as json, only write it back when something changed
+          let mut parts: Vec<request::MultipartPart> =
+            serde_json::from_str(&tab.req_body).unwrap_or_default();
+          if self.render_multipart_editor(ui, &mut parts) {
+            tab.req_body = serde_json::to_string_pretty(&parts).unwrap_or_default();
+          }
+          return;
+        }
+        if tab.body_type == request::BodyType::FORM {
+          let mut fields = match request::RequestBody::from_text(&tab.body_type, &tab.req_body) {
+            Some(request::RequestBody::FORM(fields)) => fields,
+            _ => vec![],
+          };
+          if self.render_form_editor(ui, &mut fields) {
+            tab.req_body = serde_json::to_string_pretty(&fields).unwrap_or_default();
+          }
+          return;
+        }
+        if tab.body_type == request::BodyType::GRAPHQL {
+          let mut graphql = match request::RequestBody::from_text(&tab.body_type, &tab.req_body) {
+            Some(request::RequestBody::GRAPHQL(graphql)) => graphql,
+            _ => request::GraphQLBody::default(),
+          };
+          let schemas = graphql_schemas.borrow();
+          if self.render_graphql_editor(ui, &mut graphql, schemas.get(&tab.url)) {
+            tab.req_body = serde_json::to_string_pretty(&graphql).unwrap_or_default();
+          }
+          return;
+        }
+        if tab.body_type == request::BodyType::BINARY {
+          ui.horizontal(|ui| {
+            if ui.button("Browse").clicked() {
+              if let Some(file) = rfd::FileDialog::new().pick_file() {
+                tab.req_body = file.to_str().unwrap_or("").into();
+              }
+            }
+            ui.add(TextEdit::singleline(&mut tab.req_body).hint_text("File to send"));
+          });
+          return;
+        }
         ScrollArea::vertical().show(ui, |ui| {
           ui.add(
-            TextEdit::multiline(body_str)
+            TextEdit::multiline(&mut tab.req_body)
               .code_editor()
               .desired_width(f32::INFINITY)
               .font(TextStyle::Monospace),
@@ -85,9 +206,148 @@ impl ContentPanel {
         });
       });
 
+    self.render_response_panel(ctx, worker_state, response_cookies, event_tx);
+  }
+
+  // The response of the active tab, in the space left below the request
+  fn render_response_panel(
+    &mut self,
+    ctx: &egui::Context,
+    worker_state: &ThreadSafeState,
+    response_cookies: &Rc<RefCell<HashMap<Uuid, Vec<Cookie>>>>,
+    event_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
+  ) {
+    let stop_request = &worker_state.stop_request;
     CentralPanel::default().show(ctx, |ui| {
-      if let Ok(res_guard) = response_lock.try_read() {
+      if let Some(progress) = *worker_state.download_progress.lock().unwrap() {
+        ui.horizontal(|ui| {
+          let received = format_size(progress.received);
+          match progress.total {
+            Some(total) if total > 0 => {
+              ui.add(
+                egui::ProgressBar::new(progress.received as f32 / total as f32)
+                  .desired_width(200.0)
+                  .text(format!("{} of {}", received, format_size(total))),
+              );
+            }
+            _ => {
+              ui.spinner();
+              ui.label(format!("{} received", received));
+            }
+          }
+          if ui.button("Stop").clicked() {
+            if let Some(stop) = stop_request.lock().unwrap().as_ref() {
+              let _ = stop.send(true);
+            }
+          }
+        });
+        ui.separator();
+      }
+      if let Ok(test_results) = worker_state.test_results.try_read() {
+        if !test_results.is_empty() {
+          let passed = test_results.iter().filter(|t| t.passed).count();
+          ui.collapsing(format!("Tests ({}/{})", passed, test_results.len()), |ui| {
+            for result in test_results.iter() {
+              let (icon, color) = if result.passed {
+                ("✔", egui::Color32::GREEN)
+              } else {
+                ("✘", egui::Color32::RED)
+              };
+              ui.horizontal(|ui| {
+                ui.colored_label(color, icon);
+                ui.label(&result.name);
+                if let Some(message) = &result.message {
+                  ui.weak(message);
+                }
+              });
+            }
+          });
+          ui.separator();
+        }
+      }
+      // events show up as they arrive, the whole stream is the response once it ends
+      if let Some(events) = worker_state.event_stream.lock().unwrap().as_ref() {
+        ui.horizontal(|ui| {
+          ui.label(format!("Streaming, {} events", events.len()));
+          if ui.button("Stop").clicked() {
+            if let Some(stop) = stop_request.lock().unwrap().as_ref() {
+              let _ = stop.send(true);
+            }
+          }
+        });
+        ui.separator();
+        ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+          egui::Grid::new("event_stream")
+            .striped(true)
+            .show(ui, |ui| {
+              for event in events {
+                ui.weak(&event.received_at);
+                ui.label(event.id.as_deref().unwrap_or(""));
+                ui.label(event.event_type());
+                ui.label(&event.data);
+                ui.end_row();
+              }
+            });
+        });
+        return;
+      }
+      if let Ok(res_guard) = worker_state.response.try_read() {
         if let Some(res) = res_guard.as_ref() {
+          let body = worker_state.active_tab.try_read().ok().and_then(|tab| {
+            worker_state
+              .response_bodies
+              .lock()
+              .unwrap()
+              .get(&tab.id)
+              .cloned()
+          });
+          if let Some(body) = body.filter(|body| body.size > 0) {
+            ui.horizontal(|ui| {
+              let mut summary = format_size(body.size);
+              if !body.content_type.is_empty() {
+                summary = format!("{}, {}", summary, body.content_type);
+              }
+              if body.truncated {
+                summary = match body.file {
+                  Some(_) => format!("{}, showing the start of it", summary),
+                  None => format!("{}, stopped before the end", summary),
+                };
+              }
+              ui.weak(summary);
+              if ui.button("Save Response").clicked() {
+                if let Some(path) = rfd::FileDialog::new().save_file() {
+                  let _ = event_tx.try_send(events::GuiEvent::SaveResponseBody {
+                    body: body.clone(),
+                    data: res.clone(),
+                    path: path.to_string_lossy().into(),
+                  });
+                }
+              }
+            });
+            ui.separator();
+          }
+          let cookies = worker_state
+            .active_tab
+            .try_read()
+            .ok()
+            .and_then(|tab| response_cookies.borrow().get(&tab.id).cloned())
+            .unwrap_or_default();
+          if !cookies.is_empty() {
+            ui.collapsing(format!("Cookies ({})", cookies.len()), |ui| {
+              egui::Grid::new("response_cookies")
+                .striped(true)
+                .show(ui, |ui| {
+                  for cookie in &cookies {
+                    ui.label(&cookie.name);
+                    ui.label(&cookie.value);
+                    ui.weak(format!("{}{}", cookie.domain, cookie.path));
+                    ui.weak(cookie_expiry(cookie));
+                    ui.end_row();
+                  }
+                });
+            });
+            ui.separator();
+          }
           ScrollArea::vertical().show(ui, |ui| match res {
             ResponseData::JSON(json) => {
               JsonTree::new("res", json).show(ui);
@@ -101,76 +361,822 @@ impl ContentPanel {
     });
   }
 
+  // Picks the proto files and method of a grpc tab and edits its message as json
+  fn render_grpc_tab(
+    &mut self,
+    ctx: &egui::Context,
+    active_tab: &Arc<RwLock<Tab>>,
+    proto_files: &Rc<RefCell<HashMap<Uuid, Vec<String>>>>,
+    methods: &Rc<RefCell<HashMap<Uuid, Result<Vec<GrpcMethod>, String>>>>,
+  ) {
+    TopBottomPanel::top("request_panel")
+      .resizable(true)
+      .min_height(250.0)
+      .show(ctx, |ui| {
+        let Ok(mut tab) = active_tab.try_write() else {
+          return;
+        };
+        let tab = &mut *tab;
+        let mut proto_files = proto_files.borrow_mut();
+        let files = proto_files.entry(tab.id).or_default();
+        ui.horizontal_wrapped(|ui| {
+          ui.label("Proto files:");
+          if files.is_empty() {
+            ui.weak("none, the server's reflection service is used");
+          }
+          let mut remove_index = None;
+          for (index, file) in files.iter().enumerate() {
+            let name = Path::new(file)
+              .file_name()
+              .map(|name| name.to_string_lossy().to_string())
+              .unwrap_or_else(|| file.clone());
+            ui.label(name).on_hover_text(file);
+            if ui.small_button("x").clicked() {
+              remove_index = Some(index);
+            }
+          }
+          if let Some(index) = remove_index {
+            files.remove(index);
+          }
+          if ui.button("Add .proto").clicked() {
+            if let Some(picked) = rfd::FileDialog::new()
+              .add_filter("proto", &["proto"])
+              .pick_files()
+            {
+              files.extend(picked.iter().filter_map(|p| p.to_str()).map(String::from));
+            }
+          }
+        });
+
+        // the method is the path of the url, grpc://host:port/package.Service/Method
+        let (endpoint, path) = match tab.url.trim().split_once("://") {
+          Some((scheme, rest)) => {
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            (
+              format!("{}://{}", scheme, authority),
+              path.trim_matches('/').to_string(),
+            )
+          }
+          None => (tab.url.clone(), String::new()),
+        };
+        ui.horizontal(|ui| match methods.borrow().get(&tab.id) {
+          None => {
+            ui.weak("Load Methods to pick the one to call");
+          }
+          Some(Err(e)) => {
+            ui.colored_label(egui::Color32::RED, e);
+          }
+          Some(Ok(methods)) => {
+            let selected = if path.is_empty() {
+              "Pick a method"
+            } else {
+              path.as_str()
+            };
+            egui::ComboBox::from_label("Method")
+              .selected_text(selected)
+              .show_ui(ui, |ui| {
+                for method in methods {
+                  let label = format!("{} ({})", method.path(), method.kind());
+                  if ui.selectable_label(method.path() == path, label).clicked() {
+                    tab.url = format!("{}/{}", endpoint, method.path());
+                    if tab.req_body.trim().is_empty() {
+                      tab.req_body = method.example.clone();
+                    }
+                  }
+                }
+              });
+            if let Some(method) = methods.iter().find(|m| m.path() == path) {
+              if ui.button("Example Message").clicked() {
+                tab.req_body = method.example.clone();
+              }
+            }
+          }
+        });
+        ScrollArea::vertical().show(ui, |ui| {
+          ui.add(
+            TextEdit::multiline(&mut tab.req_body)
+              .code_editor()
+              .desired_width(f32::INFINITY)
+              .font(TextStyle::Monospace),
+          );
+        });
+      });
+  }
+
+  // Returns true when a part was added, removed or edited
+  fn render_multipart_editor(
+    &mut self,
+    ui: &mut egui::Ui,
+    parts: &mut Vec<request::MultipartPart>,
+  ) -> bool {
+    let mut changed = false;
+    let mut remove_index = None;
+    ScrollArea::vertical().show(ui, |ui| {
+      for (index, part) in parts.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+          match part {
+            request::MultipartPart::Text {
+              name,
+              value,
+              enabled,
+            } => {
+              changed |= ui.checkbox(enabled, "").changed();
+              ui.label("Text");
+              changed |= ui
+                .add(TextEdit::singleline(name).hint_text("Name"))
+                .changed();
+              changed |= ui
+                .add(TextEdit::singleline(value).hint_text("Value"))
+                .changed();
+            }
+            request::MultipartPart::File {
+              name,
+              path,
+              filename,
+              content_type,
+              enabled,
+            } => {
+              changed |= ui.checkbox(enabled, "").changed();
+              ui.label("File");
+              changed |= ui
+                .add(TextEdit::singleline(name).hint_text("Name"))
+                .changed();
+              if ui.button("Browse").clicked() {
+                if let Some(file) = rfd::FileDialog::new().pick_file() {
+                  *path = file.to_str().unwrap_or("").into();
+                  changed = true;
+                }
+              }
+              changed |= ui
+                .add(TextEdit::singleline(path).hint_text("Path"))
+                .changed();
+              let mut file_name = filename.clone().unwrap_or_default();
+              if ui
+                .add(TextEdit::singleline(&mut file_name).hint_text("File name"))
+                .changed()
+              {
+                *filename = Some(file_name).filter(|f| !f.is_empty());
+                changed = true;
+              }
+              let mut mime = content_type.clone().unwrap_or_default();
+              if ui
+                .add(TextEdit::singleline(&mut mime).hint_text("Content type"))
+                .changed()
+              {
+                *content_type = Some(mime).filter(|c| !c.is_empty());
+                changed = true;
+              }
+            }
+          }
+          if ui.button("Remove").clicked() {
+            remove_index = Some(index);
+          }
+        });
+      }
+    });
+    if let Some(index) = remove_index {
+      parts.remove(index);
+      changed = true;
+    }
+    ui.horizontal(|ui| {
+      if ui.button("Add Field").clicked() {
+        parts.push(request::MultipartPart::Text {
+          name: String::new(),
+          value: String::new(),
+          enabled: true,
+        });
+        changed = true;
+      }
+      if ui.button("Add File").clicked() {
+        parts.push(request::MultipartPart::File {
+          name: String::new(),
+          path: String::new(),
+          filename: None,
+          content_type: None,
+          enabled: true,
+        });
+        changed = true;
+      }
+    });
+    changed
+  }
+
+  fn render_websocket_tab(
+    &mut self,
+    ctx: &egui::Context,
+    active_tab: &Arc<RwLock<Tab>>,
+    sessions: &Arc<Mutex<HashMap<Uuid, WebSocketSession>>>,
+    frame_mode_rc: &Rc<RefCell<ui::WebSocketFrameMode>>,
+    draft_rc: &Rc<RefCell<String>>,
+  ) {
+    let Ok(tab_id) = active_tab.try_read().map(|tab| tab.id) else {
+      return;
+    };
+    let mut sessions = sessions.lock().unwrap();
+    let session = sessions.get_mut(&tab_id);
+    let commands = session.as_ref().and_then(|s| s.commands.clone());
+    TopBottomPanel::top("request_panel")
+      .resizable(true)
+      .min_height(150.0)
+      .show(ctx, |ui| {
+        let mut frame_mode = frame_mode_rc.borrow_mut();
+        let mut draft = draft_rc.borrow_mut();
+        let mut error = None;
+        ui.horizontal(|ui| {
+          egui::ComboBox::from_label("Message type")
+            .selected_text(frame_mode.to_string())
+            .show_ui(ui, |ui| {
+              for mode in [
+                ui::WebSocketFrameMode::TEXT,
+                ui::WebSocketFrameMode::JSON,
+                ui::WebSocketFrameMode::BINARY,
+              ] {
+                ui.selectable_value(&mut *frame_mode, mode, mode.to_string());
+              }
+            });
+          let Some(commands) = &commands else {
+            ui.weak("Not connected, use Connect to open the connection");
+            return;
+          };
+          if ui.button("Send").clicked() {
+            let frame = match *frame_mode {
+              ui::WebSocketFrameMode::TEXT => Ok(WebSocketFrame::Text(draft.clone())),
+              ui::WebSocketFrameMode::JSON => Ok(WebSocketFrame::Json(draft.clone())),
+              ui::WebSocketFrameMode::BINARY => base64::engine::general_purpose::STANDARD
+                .decode(draft.trim())
+                .map(WebSocketFrame::Binary)
+                .map_err(|e| format!("Message is not valid base64: {}", e)),
+            };
+            match frame {
+              Ok(frame) => {
+                let _ = commands.try_send(WebSocketCommand::Send(frame));
+              }
+              Err(e) => error = Some(e),
+            }
+          }
+          if ui.button("Disconnect").clicked() {
+            let _ = commands.try_send(WebSocketCommand::Close);
+          }
+        });
+        ScrollArea::vertical().show(ui, |ui| {
+          ui.add(
+            TextEdit::multiline(&mut *draft)
+              .code_editor()
+              .desired_width(f32::INFINITY)
+              .font(TextStyle::Monospace)
+              .hint_text(match *frame_mode {
+                ui::WebSocketFrameMode::BINARY => "base64 encoded bytes",
+                _ => "Message",
+              }),
+          );
+        });
+        if let (Some(error), Some(session)) = (error, sessions.get_mut(&tab_id)) {
+          session.log.push(WebSocketMessage::new(
+            WebSocketDirection::Event,
+            WebSocketMessageKind::Error,
+            error,
+          ));
+        }
+      });
+
+    CentralPanel::default().show(ctx, |ui| {
+      let Some(session) = sessions.get(&tab_id) else {
+        return;
+      };
+      ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+        for message in &session.log {
+          ui.horizontal_wrapped(|ui| {
+            ui.weak(&message.timestamp);
+            let (arrow, color) = match (message.direction, message.kind) {
+              (_, WebSocketMessageKind::Error) => ("!", egui::Color32::RED),
+              (WebSocketDirection::Sent, _) => ("↑", egui::Color32::LIGHT_BLUE),
+              (WebSocketDirection::Received, _) => ("↓", egui::Color32::GREEN),
+              (WebSocketDirection::Event, _) => ("•", egui::Color32::GRAY),
+            };
+            ui.colored_label(color, arrow);
+            if message.kind == WebSocketMessageKind::Binary {
+              ui.weak("[binary]");
+            }
+            ui.monospace(&message.data);
+          });
+        }
+      });
+    });
+  }
+
+  fn render_form_editor(
+    &mut self,
+    ui: &mut egui::Ui,
+    fields: &mut Vec<request::FormField>,
+  ) -> bool {
+    let mut changed = false;
+    let mut remove_index = None;
+    TableBuilder::new(ui)
+      .id_salt("form_fields")
+      .column(Column::auto())
+      .column(Column::remainder())
+      .column(Column::remainder())
+      .column(Column::auto())
+      .header(20.0, |mut h| {
+        h.col(|ui| {
+          ui.label("On");
+        });
+        h.col(|ui| {
+          ui.label("Key");
+        });
+        h.col(|ui| {
+          ui.label("Value");
+        });
+        h.col(|_| {});
+      })
+      .body(|mut body| {
+        for (index, field) in fields.iter_mut().enumerate() {
+          body.row(25.0, |mut row| {
+            row.col(|ui| {
+              changed |= ui.checkbox(&mut field.enabled, "").changed();
+            });
+            row.col(|ui| {
+              changed |= ui.text_edit_singleline(&mut field.key).changed();
+            });
+            row.col(|ui| {
+              changed |= ui.text_edit_singleline(&mut field.value).changed();
+            });
+            row.col(|ui| {
+              if ui.button("Remove").clicked() {
+                remove_index = Some(index);
+              }
+            });
+          });
+        }
+      });
+    if let Some(index) = remove_index {
+      fields.remove(index);
+      changed = true;
+    }
+    if ui.button("Add Field").clicked() {
+      fields.push(request::FormField::new("", ""));
+      changed = true;
+    }
+    changed
+  }
+
+  fn render_graphql_editor(
+    &mut self,
+    ui: &mut egui::Ui,
+    graphql: &mut request::GraphQLBody,
+    schema: Option<&Result<GraphQLSchema, String>>,
+  ) -> bool {
+    let mut changed = false;
+    match schema {
+      Some(Ok(schema)) => ui.weak(format!("Schema loaded, {} types", schema.types.len())),
+      Some(Err(e)) => ui.colored_label(egui::Color32::RED, e),
+      None => ui.weak("Fetch the schema to complete fields"),
+    };
+    ScrollArea::vertical().show(ui, |ui| {
+      ui.label("Query");
+      let mut output = TextEdit::multiline(&mut graphql.query)
+        .code_editor()
+        .desired_rows(8)
+        .desired_width(f32::INFINITY)
+        .font(TextStyle::Monospace)
+        .show(ui);
+      changed |= output.response.changed();
+      // the cursor is kept after focus moves to a suggestion, so clicking one still knows
+      // where to insert it
+      if let (Some(Ok(schema)), Some(range)) = (schema, output.state.cursor.char_range()) {
+        let cursor = range.primary.index;
+        let suggestions = complete(schema, &graphql.query, cursor);
+        let typed = graphql
+          .query
+          .chars()
+          .take(cursor)
+          .collect::<Vec<_>>()
+          .into_iter()
+          .rev()
+          .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+          .count();
+        let mut picked = None;
+        ui.horizontal_wrapped(|ui| {
+          for field in suggestions.iter().take(30) {
+            if ui
+              .small_button(&field.name)
+              .on_hover_text(match &field.description {
+                Some(description) => format!("{}\n{}", field.type_name, description),
+                None => field.type_name.clone(),
+              })
+              .clicked()
+            {
+              picked = Some(field.name.clone());
+            }
+          }
+        });
+        if let Some(name) = picked {
+          let chars: Vec<char> = graphql.query.chars().collect();
+          let start = cursor - typed;
+          graphql.query = chars[..start]
+            .iter()
+            .chain(name.chars().collect::<Vec<_>>().iter())
+            .chain(chars[cursor..].iter())
+            .collect();
+          let end = start + name.chars().count();
+          output
+            .state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(end))));
+          output.state.store(ui.ctx(), output.response.id);
+          output.response.request_focus();
+          changed = true;
+        }
+      }
+      ui.label("Variables");
+      changed |= ui
+        .add(
+          TextEdit::multiline(&mut graphql.variables)
+            .code_editor()
+            .desired_rows(4)
+            .desired_width(f32::INFINITY)
+            .font(TextStyle::Monospace)
+            .hint_text("{ \"id\": 1 }"),
+        )
+        .changed();
+      ui.horizontal(|ui| {
+        ui.label("Operation name");
+        let mut operation_name = graphql.operation_name.clone().unwrap_or_default();
+        if ui.text_edit_singleline(&mut operation_name).changed() {
+          graphql.operation_name = Some(operation_name).filter(|n| !n.is_empty());
+          changed = true;
+        }
+      });
+    });
+    changed
+  }
+
+  fn render_scripts_tab(&mut self, ctx: &egui::Context, active_tab: &Arc<RwLock<Tab>>) {
+    CentralPanel::default().show(ctx, |ui| {
+      let Ok(mut tab) = active_tab.try_write() else {
+        return;
+      };
+      ScrollArea::vertical().show(ui, |ui| {
+        ui.heading("Pre-request Script");
+        ui.weak("Runs before sending. Read and change `request` and `env`.");
+        ui.add(
+          TextEdit::multiline(&mut tab.scripts.pre_request)
+            .code_editor()
+            .desired_rows(10)
+            .desired_width(f32::INFINITY)
+            .hint_text("env.user_id = \"42\";"),
+        );
+        ui.add_space(10.0);
+        ui.heading("Tests");
+        ui.weak("Runs after the response arrives. Use `response`, `env` and `test(name, passed)`.");
+        ui.add(
+          TextEdit::multiline(&mut tab.scripts.test)
+            .code_editor()
+            .desired_rows(10)
+            .desired_width(f32::INFINITY)
+            .hint_text("test(\"status is 200\", response.status == 200);"),
+        );
+      });
+    });
+  }
+
+  fn render_settings_tab(
+    &mut self,
+    ctx: &egui::Context,
+    active_tab: &Arc<RwLock<Tab>>,
+    default_timeouts: &Rc<RefCell<request::RequestTimeouts>>,
+  ) {
+    CentralPanel::default().show(ctx, |ui| {
+      let Ok(mut tab) = active_tab.try_write() else {
+        return;
+      };
+      ui.heading("Timeouts");
+      ui.weak("Override the defaults from Menu > Settings for this request. 0 is no limit.");
+      let mut defaults = *default_timeouts.borrow();
+      let defaults = timeout_fields(&mut defaults);
+      egui::Grid::new("request_timeouts").show(ui, |ui| {
+        for ((label, value), (_, default)) in
+          timeout_fields(&mut tab.timeouts).into_iter().zip(defaults)
+        {
+          let mut overridden = value.is_some();
+          if ui.checkbox(&mut overridden, label).changed() {
+            *value = overridden.then(|| default.unwrap_or(0));
+          }
+          match value {
+            Some(ms) => {
+              ui.add(egui::DragValue::new(ms).speed(100).suffix(" ms"));
+            }
+            None => {
+              ui.weak(match default {
+                Some(ms) if *ms > 0 => format!("{} ms", ms),
+                _ => "no limit".into(),
+              });
+            }
+          }
+          ui.end_row();
+        }
+      });
+    });
+  }
+
+  fn render_assertions_tab(&mut self, ctx: &egui::Context, active_tab: &Arc<RwLock<Tab>>) {
+    CentralPanel::default().show(ctx, |ui| {
+      let Ok(mut tab) = active_tab.try_write() else {
+        return;
+      };
+      ui.heading("Assertions");
+      ui.weak("Checked against every response, results are listed above the response body.");
+      let mut remove_index = None;
+      ScrollArea::vertical().show(ui, |ui| {
+        for (index, assertion) in tab.assertions.iter_mut().enumerate() {
+          ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt(("assertion_kind", index))
+              .selected_text(assertion.kind())
+              .show_ui(ui, |ui| {
+                for kind in Assertion::all_kinds() {
+                  let selected = kind.kind() == assertion.kind();
+                  if ui.selectable_label(selected, kind.kind()).clicked() && !selected {
+                    *assertion = kind;
+                  }
+                }
+              });
+            match assertion {
+              Assertion::StatusEquals { status } => {
+                ui.add(egui::DragValue::new(status).range(100..=599));
+              }
+              Assertion::StatusInRange { min, max } => {
+                ui.add(egui::DragValue::new(min).range(100..=599));
+                ui.label("to");
+                ui.add(egui::DragValue::new(max).range(100..=599));
+              }
+              Assertion::HeaderPresent { name } => {
+                ui.add(TextEdit::singleline(name).hint_text("Header name"));
+              }
+              Assertion::HeaderMatches { name, pattern } => {
+                ui.add(TextEdit::singleline(name).hint_text("Header name"));
+                ui.add(TextEdit::singleline(pattern).hint_text("Regex"));
+              }
+              Assertion::JsonPathEquals { path, value } => {
+                ui.add(TextEdit::singleline(path).hint_text("$.data.id"));
+                // plain text is compared as a string, anything that parses as json as json
+                let mut raw = match &*value {
+                  serde_json::Value::String(s) => s.clone(),
+                  other => other.to_string(),
+                };
+                if ui.add(TextEdit::singleline(&mut raw)).changed() {
+                  *value = serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw));
+                }
+              }
+              Assertion::BodyContains { text } => {
+                ui.add(TextEdit::singleline(text).hint_text("Text"));
+              }
+              Assertion::ResponseTimeUnder { ms } => {
+                ui.add(egui::DragValue::new(ms).suffix(" ms"));
+              }
+            }
+            if ui.button("Remove").clicked() {
+              remove_index = Some(index);
+            }
+          });
+        }
+      });
+      if let Some(index) = remove_index {
+        tab.assertions.remove(index);
+      }
+      if ui.button("Add Assertion").clicked() {
+        tab.assertions.push(Assertion::StatusEquals { status: 200 });
+      }
+    });
+  }
+
+  fn render_extract_tab(&mut self, ctx: &egui::Context, active_tab: &Arc<RwLock<Tab>>) {
+    CentralPanel::default().show(ctx, |ui| {
+      let Ok(mut tab) = active_tab.try_write() else {
+        return;
+      };
+      ui.heading("Extract");
+      ui.weak("Values found in a successful response are saved to the selected environment.");
+      let mut remove_index = None;
+      ScrollArea::vertical().show(ui, |ui| {
+        for (index, rule) in tab.extract.iter_mut().enumerate() {
+          ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut rule.variable).hint_text("Variable"));
+            egui::ComboBox::from_id_salt(("extract_kind", index))
+              .selected_text(rule.source.kind())
+              .show_ui(ui, |ui| {
+                for kind in ExtractSource::all_kinds() {
+                  let selected = kind.kind() == rule.source.kind();
+                  if ui.selectable_label(selected, kind.kind()).clicked() && !selected {
+                    rule.source = kind;
+                  }
+                }
+              });
+            match &mut rule.source {
+              ExtractSource::JsonPath { path } => {
+                ui.add(TextEdit::singleline(path).hint_text("$.data.id"));
+              }
+              ExtractSource::Header { name } => {
+                ui.add(TextEdit::singleline(name).hint_text("Header name"));
+              }
+              ExtractSource::Regex { pattern } => {
+                ui.add(TextEdit::singleline(pattern).hint_text("\"id\":\\s*(\\d+)"));
+              }
+            }
+            if ui.button("Remove").clicked() {
+              remove_index = Some(index);
+            }
+          });
+        }
+      });
+      if let Some(index) = remove_index {
+        tab.extract.remove(index);
+      }
+      if ui.button("Add Rule").clicked() {
+        tab.extract.push(ExtractRule {
+          variable: String::new(),
+          source: ExtractSource::JsonPath {
+            path: String::from("$"),
+          },
+        });
+      }
+    });
+  }
+
   fn render_auth_tab(
     &mut self,
     ctx: &egui::Context,
-    auth_mode: &mut ui::AuthMode,
-    api_key_name: &mut String,
-    api_key_value: &mut String,
-    bearer_token: &mut String,
-    oauth_config: &mut request::OAuth2Request,
-    oauth_token: &mut String,
-    received_token_lock: &Arc<Mutex<bool>>,
-    oauth_response_lock: &Arc<RwLock<Option<ResponseData>>>,
+    oauth_token: &String,
+    oauth_status: &str,
+    worker_state: &ThreadSafeState,
+    selected_environment: &Rc<RefCell<EnvironmentFile>>,
     event_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
   ) {
     CentralPanel::default().show(ctx, |ui| {
       ui.heading("Authentication");
+      // the settings are kept on the tab, so each tab has its own and they're saved with it
+      let Ok(mut tab) = worker_state.active_tab.try_write() else {
+        return;
+      };
+      let linked_request = tab.collection_id.clone().zip(tab.request_id.clone());
+      let auth = &mut tab.auth;
 
+      let modes = [
+        (ui::AuthMode::INHERIT, "Inherit"),
+        (ui::AuthMode::NONE, "No Auth"),
+        (ui::AuthMode::APIKEY, "API Key"),
+        (ui::AuthMode::BEARER, "Bearer Token"),
+        (ui::AuthMode::BASIC, "Basic Auth"),
+        (ui::AuthMode::DIGEST, "Digest Auth"),
+        (ui::AuthMode::AWSSIGV4, "AWS Signature"),
+        (ui::AuthMode::JWT, "JWT Bearer"),
+        (ui::AuthMode::OAUTH2, "OAuth 2.0"),
+      ];
       ui.horizontal(|ui| {
         ui.label("Type:");
+        let selected = modes
+          .iter()
+          .find(|(mode, _)| *mode == auth.mode)
+          .map(|(_, label)| *label)
+          .unwrap_or_default();
         egui::ComboBox::from_id_salt("auth_type_selector")
-          .selected_text(format!("{:?}", auth_mode))
+          .selected_text(selected)
           .show_ui(ui, |ui| {
-            ui.selectable_value(auth_mode, ui::AuthMode::NONE, "No Auth");
-            ui.selectable_value(auth_mode, ui::AuthMode::APIKEY, "API Key");
-            ui.selectable_value(auth_mode, ui::AuthMode::BEARER, "Bearer Token");
-            ui.selectable_value(auth_mode, ui::AuthMode::OAUTH2, "OAuth 2.0");
+            for (mode, label) in modes.clone() {
+              ui.selectable_value(&mut auth.mode, mode, label);
+            }
           });
       });
 
       ui.separator();
 
-      match auth_mode {
+      match auth.mode {
         ui::AuthMode::APIKEY => {
           egui::Grid::new("api_key_leaf_grid")
             .num_columns(2)
             .show(ui, |ui| {
-              ui.label("Header Name:");
-              ui.text_edit_singleline(api_key_name);
+              ui.label("Key:");
+              ui.text_edit_singleline(&mut auth.api_key_name);
               ui.end_row();
               ui.label("Value:");
-              ui.text_edit_singleline(api_key_value);
+              ui.text_edit_singleline(&mut auth.api_key);
+              ui.end_row();
+              ui.label("Add To:");
+              egui::ComboBox::from_id_salt("api_key_placement_selector")
+                .selected_text(auth.api_key_placement.to_string())
+                .show_ui(ui, |ui| {
+                  for placement in [ApiKeyPlacement::HEADER, ApiKeyPlacement::QUERY] {
+                    ui.selectable_value(
+                      &mut auth.api_key_placement,
+                      placement,
+                      placement.to_string(),
+                    );
+                  }
+                });
               ui.end_row();
             });
         }
         ui::AuthMode::BEARER => {
           ui.label("Bearer Token:");
           ui.add(
-            egui::TextEdit::multiline(bearer_token)
+            egui::TextEdit::multiline(&mut auth.bearer_token)
               .hint_text("eyJhbGciOiJIUzI1...")
               .desired_rows(8)
               .font(egui::TextStyle::Monospace)
               .desired_width(f32::INFINITY),
           );
         }
+        ui::AuthMode::BASIC | ui::AuthMode::DIGEST => {
+          egui::Grid::new("basic_auth_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+              ui.label("Username:");
+              ui.text_edit_singleline(&mut auth.username);
+              ui.end_row();
+              ui.label("Password:");
+              ui.add(egui::TextEdit::singleline(&mut auth.password).password(true));
+              ui.end_row();
+            });
+          if auth.mode == ui::AuthMode::DIGEST {
+            ui.weak("The request is sent again with the answer to the server's challenge");
+          }
+        }
+        ui::AuthMode::AWSSIGV4 => {
+          egui::Grid::new("aws_sigv4_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+              ui.label("Access Key:");
+              ui.text_edit_singleline(&mut auth.aws.access_key);
+              ui.end_row();
+              ui.label("Secret Key:");
+              ui.add(egui::TextEdit::singleline(&mut auth.aws.secret_key).password(true));
+              ui.end_row();
+              ui.label("Session Token:");
+              ui.add(
+                egui::TextEdit::singleline(&mut auth.aws.session_token)
+                  .hint_text("for temporary credentials"),
+              );
+              ui.end_row();
+              ui.label("Region:");
+              ui.add(egui::TextEdit::singleline(&mut auth.aws.region).hint_text("us-east-1"));
+              ui.end_row();
+              ui.label("Service:");
+              ui.add(egui::TextEdit::singleline(&mut auth.aws.service).hint_text("execute-api"));
+              ui.end_row();
+            });
+        }
+        ui::AuthMode::JWT => {
+          egui::Grid::new("jwt_grid").num_columns(2).show(ui, |ui| {
+            ui.label("Algorithm:");
+            egui::ComboBox::from_id_salt("jwt_algorithm_selector")
+              .selected_text(auth.jwt.algorithm.clone())
+              .show_ui(ui, |ui| {
+                for algorithm in JwtAuth::ALGORITHMS {
+                  ui.selectable_value(&mut auth.jwt.algorithm, algorithm.to_string(), algorithm);
+                }
+              });
+            ui.end_row();
+            ui.label("Secret or Private Key:");
+            ui.add(
+              egui::TextEdit::multiline(&mut auth.jwt.secret)
+                .desired_rows(3)
+                .font(egui::TextStyle::Monospace),
+            );
+            ui.end_row();
+            ui.label("");
+            ui.checkbox(&mut auth.jwt.secret_base64, "Secret is base64 encoded");
+            ui.end_row();
+            ui.label("Payload:");
+            ui.add(
+              egui::TextEdit::multiline(&mut auth.jwt.payload)
+                .desired_rows(6)
+                .font(egui::TextStyle::Monospace),
+            );
+            ui.end_row();
+            ui.label("Header Prefix:");
+            ui.text_edit_singleline(&mut auth.jwt.header_prefix);
+            ui.end_row();
+          });
+        }
         ui::AuthMode::OAUTH2 => {
           // Sub-leaf for the OAuth configuration grid
-          self.render_oauth_details(ui, oauth_config, oauth_token, event_tx);
-
-          // Logic to ingest the token from the background worker
-          if let Ok(mut lock) = received_token_lock.lock() {
-            if !*lock {
-              if let Ok(res_guard) = oauth_response_lock.try_read() {
-                if let Some(ResponseData::JSON(j)) = res_guard.as_ref() {
-                  if let Ok(data) = serde_json::from_value::<OAuthResponse>(j.clone()) {
-                    *oauth_token = data.access_token;
-                    *lock = true;
-                  }
-                }
-              }
+          auth.oauth.environment = selected_environment.borrow().clone();
+          self.render_oauth_details(ui, &mut auth.oauth, oauth_token, oauth_status, event_tx);
+        }
+        ui::AuthMode::INHERIT => {
+          let inherited = linked_request.and_then(|(col_id, item_id)| {
+            let collections = worker_state.collections.try_read().ok()?;
+            collections
+              .iter()
+              .find(|c| c.info.id == col_id)?
+              .inherited_auth_for(&item_id)
+          });
+          match inherited {
+            Some((RequestAuth::NONE, source)) => {
+              ui.label(format!("Sent without auth, as set on {}", source))
             }
-          }
+            Some((auth, source)) => ui.label(format!("Sends the {} set on {}", auth, source)),
+            None => ui.weak("Nothing to inherit, this request is sent without auth"),
+          };
         }
         ui::AuthMode::NONE => {
           ui.weak("No authentication headers will be sent with this request.");
@@ -184,16 +1190,51 @@ impl ContentPanel {
     ui: &mut egui::Ui,
     config: &mut request::OAuth2Request,
     current_token: &String,
+    status: &str,
     event_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
   ) {
     egui::Grid::new("oauth_details")
       .num_columns(2)
       .spacing([20.0, 8.0])
       .show(ui, |ui| {
+        ui.label("Grant Type:");
+        egui::ComboBox::from_id_salt("oauth_grant_selector")
+          .selected_text(config.grant.to_string())
+          .show_ui(ui, |ui| {
+            for grant in OAuth2Grant::all() {
+              ui.selectable_value(&mut config.grant, grant, grant.to_string());
+            }
+          });
+        ui.end_row();
+
+        if config.grant == OAuth2Grant::AUTHORIZATIONCODE {
+          ui.label("Auth URL:");
+          ui.text_edit_singleline(&mut config.auth_url);
+          ui.end_row();
+
+          ui.label("Redirect URI:");
+          ui.add(
+            egui::TextEdit::singleline(&mut config.redirect_uri)
+              .hint_text("http://127.0.0.1:<any free port>/callback"),
+          );
+          ui.end_row();
+        }
+        if config.grant == OAuth2Grant::DEVICECODE {
+          ui.label("Device Authorization URL:");
+          ui.text_edit_singleline(&mut config.device_authorization_url);
+          ui.end_row();
+        }
+
         ui.label("Access Token URL:");
         ui.text_edit_singleline(&mut config.access_token_url);
         ui.end_row();
 
+        ui.label("Refresh URL:");
+        ui.add(
+          egui::TextEdit::singleline(&mut config.refresh_url).hint_text("the access token url"),
+        );
+        ui.end_row();
+
         ui.label("Client ID:");
         ui.text_edit_singleline(&mut config.client_id);
         ui.end_row();
@@ -201,10 +1242,51 @@ impl ContentPanel {
         ui.label("Client Secret:");
         ui.add(egui::TextEdit::singleline(&mut config.client_secret).password(true));
         ui.end_row();
+
+        ui.label("Client Authentication:");
+        egui::ComboBox::from_id_salt("oauth_client_auth_selector")
+          .selected_text(config.client_authentication.to_string())
+          .show_ui(ui, |ui| {
+            for client_auth in [OAuth2ClientAuth::HEADER, OAuth2ClientAuth::BODY] {
+              ui.selectable_value(
+                &mut config.client_authentication,
+                client_auth,
+                client_auth.to_string(),
+              );
+            }
+          });
+        ui.end_row();
+
+        if config.grant == OAuth2Grant::PASSWORD {
+          ui.label("Username:");
+          ui.text_edit_singleline(&mut config.username);
+          ui.end_row();
+
+          ui.label("Password:");
+          ui.add(egui::TextEdit::singleline(&mut config.password).password(true));
+          ui.end_row();
+        }
+
+        ui.label("Scope:");
+        ui.text_edit_singleline(&mut config.request.scope);
+        ui.end_row();
+
+        ui.label("Audience:");
+        ui.text_edit_singleline(&mut config.request.audience);
+        ui.end_row();
       });
 
-    if ui.button("Get New Access Token").clicked() {
-      let _ = event_tx.try_send(events::GuiEvent::SubmitOAuth2Request(config.clone()));
+    ui.horizontal(|ui| {
+      if ui.button("Get New Access Token").clicked() {
+        let _ = event_tx.try_send(events::GuiEvent::SubmitOAuth2Request(config.clone()));
+      }
+      if ui.button("Refresh Token").clicked() {
+        let _ = event_tx.try_send(events::GuiEvent::RefreshOAuth2Token(config.clone()));
+      }
+    });
+    ui.weak("Saved tokens are sent with requests and refreshed before they expire");
+    if !status.is_empty() {
+      ui.label(status);
     }
 
     if !current_token.is_empty() {
@@ -259,73 +1341,86 @@ impl ContentPanel {
     });
   }
 
-  fn render_environment_tab(&mut self, ctx: &egui::Context, env_rc: &Rc<RefCell<EnvironmentFile>>) {
+  fn render_environment_tab(
+    &mut self,
+    ctx: &egui::Context,
+    env_rc: &Rc<RefCell<EnvironmentFile>>,
+    globals_rc: &Rc<RefCell<Vec<EnvironmentValue>>>,
+    event_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
+  ) {
     CentralPanel::default().show(ctx, |ui| {
-      ui.heading("Environment Variables");
+      egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.heading("Environment Variables");
+        let mut env = env_rc.borrow_mut();
+        let values = env.values.get_or_insert_with(Vec::new);
+        self.render_variable_table(ui, "environment_variables", values);
 
-      let mut add_clicked = false;
+        ui.add_space(20.0);
+        ui.heading("Global Variables");
+        ui.weak("Available in every environment, environment values take precedence.");
+        let mut globals = globals_rc.borrow_mut();
+        self.render_variable_table(ui, "global_variables", &mut globals);
+        if ui.button("Save Globals").clicked() {
+          let _ = event_tx.try_send(events::GuiEvent::SaveGlobals(globals.clone()));
+        }
+      });
+    });
+  }
 
-      egui::ScrollArea::vertical().show(ui, |ui| {
-        TableBuilder::new(ui)
-          .striped(true)
-          .column(Column::auto()) // Enabled
-          .column(Column::remainder()) // Key
-          .column(Column::remainder()) // Value
-          .column(Column::auto()) // Type
-          .header(20.0, |mut header| {
-            header.col(|ui| {
-              ui.strong("On");
-            });
-            header.col(|ui| {
-              ui.strong("Key");
-            });
-            header.col(|ui| {
-              ui.strong("Value");
-            });
-            header.col(|ui| {
-              ui.strong("Type");
-            });
-          })
-          .body(|mut body| {
-            let mut env = env_rc.borrow_mut();
-            if let Some(values) = env.values.as_mut() {
-              for var in values {
-                body.row(25.0, |mut row| {
-                  row.col(|ui| {
-                    ui.checkbox(&mut var.enabled, "");
-                  });
-                  row.col(|ui| {
-                    ui.text_edit_singleline(&mut var.key);
-                  });
-                  row.col(|ui| {
-                    ui.text_edit_singleline(&mut var.value);
-                  });
-                  row.col(|ui| {
-                    ui.text_edit_singleline(&mut var.r#type);
-                  });
-                });
-              }
-            }
+  fn render_variable_table(
+    &mut self,
+    ui: &mut egui::Ui,
+    id: &str,
+    values: &mut Vec<EnvironmentValue>,
+  ) {
+    ui.push_id(id, |ui| {
+      TableBuilder::new(ui)
+        .striped(true)
+        .vscroll(false)
+        .column(Column::auto()) // Enabled
+        .column(Column::remainder()) // Key
+        .column(Column::remainder()) // Value
+        .column(Column::auto()) // Type
+        .header(20.0, |mut header| {
+          header.col(|ui| {
+            ui.strong("On");
           });
-      });
+          header.col(|ui| {
+            ui.strong("Key");
+          });
+          header.col(|ui| {
+            ui.strong("Value");
+          });
+          header.col(|ui| {
+            ui.strong("Type");
+          });
+        })
+        .body(|mut body| {
+          for var in values.iter_mut() {
+            body.row(25.0, |mut row| {
+              row.col(|ui| {
+                ui.checkbox(&mut var.enabled, "");
+              });
+              row.col(|ui| {
+                ui.text_edit_singleline(&mut var.key);
+              });
+              row.col(|ui| {
+                ui.text_edit_singleline(&mut var.value);
+              });
+              row.col(|ui| {
+                ui.text_edit_singleline(&mut var.r#type);
+              });
+            });
+          }
+        });
 
       if ui.button("Add Variable").clicked() {
-        add_clicked = true;
-      }
-
-      if add_clicked {
-        let mut env = env_rc.borrow_mut();
-        let new_var = EnvironmentValue {
+        values.push(EnvironmentValue {
           key: "".into(),
           value: "".into(),
           r#type: "default".into(),
           enabled: true,
-        };
-        if let Some(values) = env.values.as_mut() {
-          values.push(new_var);
-        } else {
-          env.values = Some(vec![new_var]);
-        }
+        });
       }
     });
   }
@@ -410,3 +1505,11 @@ impl ContentPanel {
     });
   }
 }
+
+fn format_size(bytes: u64) -> String {
+  match bytes {
+    0..1024 => format!("{} B", bytes),
+    1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+    _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+  }
+}
@@ -3,12 +3,13 @@ use std::{collections::HashMap, str::FromStr as _, sync::Arc};
 
 use crate::events;
 use api::domain::{
+  auth::AuthSettings,
   collection::{
     Collection, CollectionFolder, CollectionItem, CollectionItemOrFolder, CollectionRequest,
   },
   environment::EnvironmentFile,
   header::Headers,
-  request::{DBRequest, HttpMethod},
+  request::{BodyType, DBRequest, HttpMethod, RequestScripts, RequestTimeouts},
   request_item::RequestHistoryItem,
   response::DBResponse,
   tab,
@@ -22,6 +23,12 @@ pub struct ContentSidePanel {
   selected_environment: Option<EnvironmentFile>,
   selected_history_item: Option<RequestHistoryItem>,
 }
+impl Default for ContentSidePanel {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl ContentSidePanel {
   pub fn new() -> Self {
     Self {
@@ -84,15 +91,15 @@ impl ContentSidePanel {
     if let Ok(guard) = collections_read {
       for c in guard.iter() {
         ui.horizontal(|ui| {
-          self.render_context_menu(ui, tabs, &c, None, None, event_tx);
+          self.render_context_menu(ui, tabs, c, None, None, event_tx);
           ui.collapsing(c.info.name.clone(), |ui| {
             for i in c.item.clone() {
               match i {
                 CollectionItemOrFolder::Item(item) => {
-                  self.render_request(ui, ctx, &c, None, &item, tabs, event_tx);
+                  self.render_request(ui, ctx, c, None, &item, tabs, event_tx);
                 }
                 CollectionItemOrFolder::Folder(folder) => {
-                  self.render_collection_folder(ui, ctx, tabs, &c, &folder, event_tx);
+                  self.render_collection_folder(ui, ctx, tabs, c, &folder, event_tx);
                 }
               };
             }
@@ -122,7 +129,7 @@ impl ContentSidePanel {
 
         let _ = event_tx.try_send(events::GuiEvent::SelectRequest {
           col_id: collection.info.id.clone(),
-          request: item.request.clone(),
+          item: item.clone(),
         });
 
         ctx.request_repaint();
@@ -211,7 +218,7 @@ impl ContentSidePanel {
           .selectable_value(
             &mut self.selected_history_item,
             Some(item.clone()),
-            format!("{:?}", req_name), // TODO - create function to get name
+            history_label(&req_name, item), // TODO - create function to get name
           )
           .clicked()
         {
@@ -236,11 +243,21 @@ impl ContentSidePanel {
             None => {
               println!("no matching tab found, creating new");
               let id = uuid::Uuid::new_v4();
+              let req_body = historical_request.body.clone().unwrap_or_default();
+              // history only keeps the body text, anything that isn't json is shown as text
+              let body_type = if req_body.is_empty()
+                || serde_json::from_str::<serde_json::Value>(&req_body).is_ok()
+              {
+                BodyType::JSON
+              } else {
+                BodyType::TEXT
+              };
               let new_tab = Tab {
                 id,
                 method: HttpMethod::from_str(&historical_request.method).unwrap(),
                 url: historical_request.url.clone(),
-                req_body: historical_request.body.clone().unwrap_or_default(),
+                req_body,
+                body_type,
                 req_headers: Headers(historical_request.headers.clone()),
                 res_status: None,
                 res_body: match historical_response {
@@ -254,6 +271,13 @@ impl ContentSidePanel {
                     Headers::from_iter(headers)
                   }
                 },
+                collection_id: None,
+                request_id: None,
+                scripts: RequestScripts::default(),
+                assertions: vec![],
+                extract: vec![],
+                timeouts: RequestTimeouts::default(),
+                auth: AuthSettings::default(),
               };
               tabs_lock.insert(new_tab.id.clone().to_string(), new_tab.clone());
               event_tx
@@ -271,7 +295,7 @@ impl ContentSidePanel {
   fn render_context_menu(
     &mut self,
     ui: &mut egui::Ui,
-    tabs: &Arc<RwLock<HashMap<String, tab::Tab>>>,
+    _tabs: &Arc<RwLock<HashMap<String, tab::Tab>>>,
     col: &Collection,
     fol: Option<&CollectionFolder>,
     req: Option<&CollectionItem>,
@@ -301,6 +325,8 @@ impl ContentSidePanel {
                   id: uuid::Uuid::new_v4().to_string(),
                   name: "New Folder".into(),
                   item: vec![],
+                  auth: None,
+                  variable: None,
                 },
               })
               .unwrap();
@@ -346,3 +372,12 @@ impl ContentSidePanel {
     })
   }
 }
+
+// Adds a passed/total summary when the request ran tests
+fn history_label(req_name: &str, item: &RequestHistoryItem) -> String {
+  if item.test_results.is_empty() {
+    return format!("{:?}", req_name);
+  }
+  let passed = item.test_results.iter().filter(|t| t.passed).count();
+  format!("{:?} ({}/{})", req_name, passed, item.test_results.len())
+}
@@ -0,0 +1,159 @@
+use api::domain::cookie::Cookie;
+use chrono::Utc;
+use egui::{ScrollArea, TextEdit};
+
+use crate::{events::GuiEvent, Gui};
+
+// Views and edits the cookies of the selected environment, grouped by domain
+pub fn cookies_window(gui: &mut Gui, ctx: &egui::Context) {
+  let cookies_window_open = gui.gui_state.cookies_window_open.clone();
+  let Ok(mut cookies_window_open) = cookies_window_open.try_write() else {
+    return;
+  };
+  if !*cookies_window_open {
+    return;
+  }
+  let environment = gui.gui_state.selected_environment.borrow().clone();
+  let mut loaded = gui.gui_state.cookies.borrow_mut();
+  if loaded.as_ref().map(|(id, _)| id) != Some(&environment.id) {
+    // shown empty until they arrive, asking only once
+    *loaded = Some((environment.id.clone(), vec![]));
+    let _ = gui
+      .event_tx
+      .try_send(GuiEvent::LoadCookies(environment.clone()));
+  }
+  let Some((_, cookies)) = loaded.as_mut() else {
+    return;
+  };
+  let mut save = false;
+  egui::Window::new("Cookies")
+    .open(&mut cookies_window_open)
+    .show(ctx, |ui| {
+      ui.weak(format!(
+        "Sent with requests using the {} environment",
+        environment.name
+      ));
+      ui.separator();
+      let mut domains: Vec<String> = cookies.iter().map(|c| c.domain.clone()).collect();
+      domains.sort();
+      domains.dedup();
+      if domains.is_empty() {
+        ui.label("No cookies, they are kept from the Set-Cookie headers of responses");
+      }
+      ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+        for domain in &domains {
+          let count = cookies.iter().filter(|c| &c.domain == domain).count();
+          egui::CollapsingHeader::new(format!("{} ({})", domain, count))
+            .id_salt(domain)
+            .default_open(true)
+            .show(ui, |ui| {
+              let mut remove_index = None;
+              egui::Grid::new(format!("cookies_{}", domain))
+                .striped(true)
+                .show(ui, |ui| {
+                  ui.strong("Name");
+                  ui.strong("Value");
+                  ui.strong("Path");
+                  ui.strong("Expires");
+                  ui.strong("Secure");
+                  ui.strong("HttpOnly");
+                  ui.end_row();
+                  for (index, cookie) in cookies
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(_, c)| &c.domain == domain)
+                  {
+                    ui.add(TextEdit::singleline(&mut cookie.name).desired_width(120.0));
+                    ui.add(TextEdit::singleline(&mut cookie.value).desired_width(200.0));
+                    ui.add(TextEdit::singleline(&mut cookie.path).desired_width(80.0));
+                    ui.weak(cookie_expiry(cookie));
+                    ui.checkbox(&mut cookie.secure, "");
+                    ui.checkbox(&mut cookie.http_only, "");
+                    if ui.button("x").clicked() {
+                      remove_index = Some(index);
+                    }
+                    ui.end_row();
+                  }
+                });
+              if let Some(index) = remove_index {
+                cookies.remove(index);
+                save = true;
+              }
+              if ui.button(format!("Clear {}", domain)).clicked() {
+                cookies.retain(|c| &c.domain != domain);
+                save = true;
+              }
+            });
+        }
+      });
+      ui.separator();
+      let mut new_cookie = gui.gui_state.new_cookie.borrow_mut();
+      ui.horizontal(|ui| {
+        ui.add(
+          TextEdit::singleline(&mut new_cookie.domain)
+            .hint_text("example.com")
+            .desired_width(120.0),
+        );
+        ui.add(
+          TextEdit::singleline(&mut new_cookie.name)
+            .hint_text("name")
+            .desired_width(100.0),
+        );
+        ui.add(
+          TextEdit::singleline(&mut new_cookie.value)
+            .hint_text("value")
+            .desired_width(150.0),
+        );
+        let domain = new_cookie
+          .domain
+          .trim()
+          .trim_start_matches('.')
+          .to_lowercase();
+        let can_add = !domain.is_empty() && !new_cookie.name.trim().is_empty();
+        if ui
+          .add_enabled(can_add, egui::Button::new("Add Cookie"))
+          .clicked()
+        {
+          let cookie = Cookie {
+            name: new_cookie.name.trim().to_string(),
+            value: new_cookie.value.clone(),
+            domain,
+            path: "/".into(),
+            ..Default::default()
+          };
+          cookies.retain(|c| !c.same_as(&cookie));
+          cookies.push(cookie);
+          *new_cookie = Cookie::default();
+          save = true;
+        }
+      });
+      ui.horizontal(|ui| {
+        if ui.button("Save").clicked() {
+          save = true;
+        }
+        if ui.button("Clear All").clicked() {
+          cookies.clear();
+          save = true;
+        }
+        if ui.button("Reload").clicked() {
+          let _ = gui
+            .event_tx
+            .try_send(GuiEvent::LoadCookies(environment.clone()));
+        }
+      });
+    });
+  if save {
+    let _ = gui.event_tx.try_send(GuiEvent::SaveCookies {
+      environment,
+      cookies: cookies.clone(),
+    });
+  }
+}
+
+pub fn cookie_expiry(cookie: &Cookie) -> String {
+  match cookie.expires {
+    Some(_) if cookie.is_expired(Utc::now()) => "Deleted".into(),
+    Some(expires) => expires.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    None => "Session".into(),
+  }
+}
@@ -0,0 +1,115 @@
+use api::domain::ui;
+
+use crate::Gui;
+
+pub fn export_modal(gui: &mut Gui, ctx: &egui::Context) {
+  if let Ok(mut export_window_open) = gui.gui_state.export_window_open.try_write() {
+    if *export_window_open {
+      egui::Window::new("Export File")
+        .open(&mut export_window_open)
+        .show(ctx, |ui| {
+          let Ok(export_mode) = gui.gui_state.export_mode.try_read() else {
+            return;
+          };
+          let default_file_name = match *export_mode {
+            ui::ExportMode::COLLECTION => {
+              let collections = gui.worker_state.collections.try_read().unwrap().clone();
+              let selected_collection = &mut gui.gui_state.selected_export_collection;
+              egui::ComboBox::from_label("Collection to export")
+                .selected_text(
+                  selected_collection
+                    .as_ref()
+                    .map_or("Select a collection".to_string(), |col| {
+                      col.info.name.clone()
+                    }),
+                )
+                .show_ui(ui, |ui| {
+                  for col in collections {
+                    let name = col.info.name.clone();
+                    ui.selectable_value(selected_collection, Some(col), name);
+                  }
+                });
+              selected_collection
+                .as_ref()
+                .map(|col| format!("{}.postman_collection.json", col.info.name))
+            }
+            ui::ExportMode::ENVIRONMENT => {
+              let environments = gui.worker_state.environments.try_read().unwrap().clone();
+              let selected_environment = &mut gui.gui_state.selected_export_environment;
+              egui::ComboBox::from_label("Environment to export")
+                .selected_text(
+                  selected_environment
+                    .as_ref()
+                    .map_or("Select an environment".to_string(), |env| env.name.clone()),
+                )
+                .show_ui(ui, |ui| {
+                  for env in environments {
+                    let name = env.name.clone();
+                    ui.selectable_value(selected_environment, Some(env), name);
+                  }
+                });
+              selected_environment
+                .as_ref()
+                .map(|env| format!("{}.postman_environment.json", env.name))
+            }
+          };
+
+          ui.label("Please select a file or enter path to export to");
+          ui.horizontal(|ui| {
+            if ui.button("Browse").clicked() {
+              let file = rfd::FileDialog::new()
+                .add_filter("json", &["json"])
+                .set_file_name(default_file_name.unwrap_or_default())
+                .save_file();
+              if let Some(file) = file {
+                println!("Exporting to file: {:?}", file);
+                gui.gui_state.export_file_path = file.to_str().unwrap_or("").into();
+              }
+            };
+            ui.text_edit_singleline(&mut gui.gui_state.export_file_path);
+            if ui.button("Export").clicked() {
+              let path = gui.gui_state.export_file_path.to_owned();
+              let export_result_clone = gui.worker_state.export_result.clone();
+              let api_for_worker = std::sync::Arc::clone(&gui.worker_state.api);
+              match *export_mode {
+                ui::ExportMode::COLLECTION => {
+                  if let Some(col) = &gui.gui_state.selected_export_collection {
+                    let id = col.info.id.clone();
+                    _ = tokio::spawn(async move {
+                      let res = api_for_worker
+                        .write()
+                        .await
+                        .export_collection(&id, &path)
+                        .await
+                        .unwrap_or_else(|e| format!("Error exporting collection: {e}"));
+                      let mut data = export_result_clone.lock().unwrap();
+                      *data = Some(res);
+                    });
+                  }
+                }
+                ui::ExportMode::ENVIRONMENT => {
+                  if let Some(env) = &gui.gui_state.selected_export_environment {
+                    let id = env.id.clone();
+                    _ = tokio::spawn(async move {
+                      let res = api_for_worker
+                        .write()
+                        .await
+                        .export_environment(&id, &path)
+                        .await
+                        .unwrap_or_else(|e| format!("Error exporting environment: {e}"));
+                      let mut data = export_result_clone.lock().unwrap();
+                      *data = Some(res);
+                    });
+                  }
+                }
+              };
+            }
+          });
+          let e = gui.worker_state.export_result.lock().unwrap();
+          if let Some(export_res) = &*e {
+            ui.label(export_res);
+          }
+        });
+    }
+  }
+}
@@ -1,18 +1,27 @@
 use std::collections::HashMap;
-use std::{ops::Deref, sync::Arc};
+use std::{
+  ops::Deref,
+  sync::{Arc, Mutex},
+};
 
 use api::domain::tab::Tab;
-use api::domain::ui::{ImportMode, NewWindowMode};
+use api::domain::ui::{ExportMode, ImportMode, NewWindowMode};
 use egui::TopBottomPanel;
 
 use api::domain::{tab, ui};
 use tokio::sync::mpsc::Sender;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 
 use crate::events::GuiEvent;
 use crate::{events, GuiState, ThreadSafeState};
 
 pub struct MenuPanel {}
+impl Default for MenuPanel {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl MenuPanel {
   pub fn new() -> Self {
     Self {}
@@ -33,7 +42,13 @@ impl MenuPanel {
       &gui_state.save_window_open,
       &gui_state.import_window_open,
       &gui_state.import_mode,
+      &gui_state.export_window_open,
+      &gui_state.export_mode,
+      &gui_state.runner_window_open,
+      &gui_state.settings_window_open,
+      &gui_state.cookies_window_open,
       &worker_state.is_requesting,
+      &worker_state.stop_request,
       &worker_state.res_status,
     );
     self.render_tabs_panel(ctx, event_tx, &worker_state.tabs.clone());
@@ -48,7 +63,13 @@ impl MenuPanel {
     save_window_open: &RwLock<bool>,
     import_window_open: &RwLock<bool>,
     import_mode: &RwLock<ImportMode>,
+    export_window_open: &RwLock<bool>,
+    export_mode: &RwLock<ExportMode>,
+    runner_window_open: &RwLock<bool>,
+    settings_window_open: &RwLock<bool>,
+    cookies_window_open: &RwLock<bool>,
     is_requesting: &Arc<RwLock<Option<bool>>>,
+    stop_request: &Mutex<Option<watch::Sender<bool>>>,
     res_status: &Arc<RwLock<String>>,
   ) {
     TopBottomPanel::top("menu_panel").show(ctx, |ui| {
@@ -112,30 +133,64 @@ impl MenuPanel {
           });
           ui.menu_button("Export", |ui| {
             if ui.button("Collection").clicked() {
+              if let Ok(mut export_open) = export_window_open.try_write() {
+                *export_open = true;
+              }
+              if let Ok(mut export_mode) = export_mode.try_write() {
+                *export_mode = ui::ExportMode::COLLECTION;
+              }
               ui.close();
             };
             if ui.button("Environment").clicked() {
+              if let Ok(mut export_open) = export_window_open.try_write() {
+                *export_open = true;
+              }
+              if let Ok(mut export_mode) = export_mode.try_write() {
+                *export_mode = ui::ExportMode::ENVIRONMENT;
+              }
+              ui.close();
+            };
+          });
+          ui.menu_button("Run", |ui| {
+            if ui.button("Collection").clicked() {
+              if let Ok(mut runner_open) = runner_window_open.try_write() {
+                *runner_open = true;
+              }
               ui.close();
             };
           });
+          if ui.button("Settings").clicked() {
+            if let Ok(mut settings_open) = settings_window_open.try_write() {
+              *settings_open = true;
+            }
+            ui.close();
+          }
+          if ui.button("Cookies").clicked() {
+            if let Ok(mut cookies_open) = cookies_window_open.try_write() {
+              *cookies_open = true;
+            }
+            ui.close();
+          }
         });
         let is_requesting_lock = is_requesting.try_read();
         if is_requesting_lock.is_ok() {
           if let Ok(is_requesting) = is_requesting_lock {
-            match is_requesting.deref() {
-              Some(r) => {
-                if *r {
-                  ui.label("Requesting...");
-                } else {
-                  let response_status_lock = res_status.try_read();
-                  if response_status_lock.is_ok() {
-                    if let Ok(response_status) = response_status_lock {
-                      ui.label(response_status.deref());
-                    }
+            if let Some(r) = is_requesting.deref() {
+              if *r {
+                ui.label("Requesting...");
+                if ui.button("Cancel").clicked() {
+                  if let Some(stop) = stop_request.lock().unwrap().as_ref() {
+                    let _ = stop.send(true);
+                  }
+                }
+              } else {
+                let response_status_lock = res_status.try_read();
+                if response_status_lock.is_ok() {
+                  if let Ok(response_status) = response_status_lock {
+                    ui.label(response_status.deref());
                   }
                 }
               }
-              None => {}
             }
           }
         }
@@ -150,7 +205,7 @@ impl MenuPanel {
     tabs: &Arc<RwLock<HashMap<String, Tab>>>,
   ) {
     TopBottomPanel::top("tabs panel").show(ctx, |ui| {
-      let tabs_clone = Arc::clone(&tabs);
+      let tabs_clone = Arc::clone(tabs);
       let tabs = tabs_clone.try_read().unwrap();
       ui.horizontal(|ui| {
         for tab in &*tabs {
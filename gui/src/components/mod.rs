@@ -1,8 +1,12 @@
 pub mod content_header_panel;
 pub mod content_panel;
 pub mod content_side_panel;
+pub mod cookies_window;
+pub mod export_modal;
 pub mod import_modal;
 pub mod menu_panel;
 pub mod new_modal;
+pub mod runner_window;
 pub mod save_window;
+pub mod settings_window;
 pub mod side_panel;
@@ -12,6 +12,12 @@ pub struct NewWindow {
   pub is_open: bool,
   pub new_name: String,
 }
+impl Default for NewWindow {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl NewWindow {
   pub fn new() -> Self {
     Self {
@@ -36,29 +42,26 @@ impl NewWindow {
             ui.horizontal(|ui| {
               ui.label("Enter name: ");
               ui.text_edit_singleline(&mut self.new_name);
-              match self.window_mode {
-                NewWindowMode::FOLDER => {
-                  let collections = &worker_state.collections.try_read().unwrap().clone();
-                  let selected_collection = &mut gui_state.selected_save_window_collection.clone();
-                  egui::ComboBox::from_label("Collection to add folder to")
-                    .selected_text(
-                      selected_collection
-                        .as_ref()
-                        .map_or("Select a collection".to_string(), |col| {
-                          col.info.name.clone()
-                        }),
-                    )
-                    .show_ui(ui, |ui| {
-                      for col in collections {
-                        ui.selectable_value(
-                          selected_collection,
-                          Some(col.clone()),
-                          col.info.name.clone(),
-                        );
-                      }
-                    });
-                }
-                _ => {}
+              if let NewWindowMode::FOLDER = self.window_mode {
+                let collections = &worker_state.collections.try_read().unwrap().clone();
+                let selected_collection = &mut gui_state.selected_save_window_collection.clone();
+                egui::ComboBox::from_label("Collection to add folder to")
+                  .selected_text(
+                    selected_collection
+                      .as_ref()
+                      .map_or("Select a collection".to_string(), |col| {
+                        col.info.name.clone()
+                      }),
+                  )
+                  .show_ui(ui, |ui| {
+                    for col in collections {
+                      ui.selectable_value(
+                        selected_collection,
+                        Some(col.clone()),
+                        col.info.name.clone(),
+                      );
+                    }
+                  });
               }
               if ui.button("Save").clicked() {
                 match self.window_mode {
@@ -67,7 +70,7 @@ impl NewWindow {
                     let tx_clone = event_tx.clone();
                     let tx_clone2 = event_tx.clone();
                     let name_for_worker = self.new_name.clone();
-                    let _ = tokio::spawn(async move {
+                    tokio::spawn(async move {
                       tx_clone
                         .try_send(events::GuiEvent::NewCollection(Some(name_for_worker)))
                         .unwrap();
@@ -85,7 +88,7 @@ impl NewWindow {
                     let tx_clone = event_tx.clone();
                     let tx_clone2 = event_tx.clone();
                     let name_clone2 = self.new_name.clone();
-                    let _ = tokio::spawn(async move {
+                    tokio::spawn(async move {
                       tx_clone
                         .try_send(events::GuiEvent::NewEnvironment(Some(name_clone2)))
                         .unwrap();
@@ -107,7 +110,7 @@ impl NewWindow {
                     let name_for_worker = self.new_name.clone();
 
                     let tx_clone = event_tx.clone();
-                    let _ = tokio::spawn(async move {
+                    tokio::spawn(async move {
                       // take selected collection, add new folder to the top
                       // `item` field as with no requests
                       let mut collection_items = collection_for_worker.item;
@@ -115,12 +118,15 @@ impl NewWindow {
                         id: uuid::Uuid::new_v4().to_string(),
                         name: name_for_worker,
                         item: vec![],
+                        auth: None,
+                        variable: None,
                       });
                       collection_items.push(new_folder);
                       let updated_collection: Collection = Collection {
                         info: collection_for_worker.info,
                         item: collection_items,
                         auth: collection_for_worker.auth,
+                        variable: collection_for_worker.variable,
                       };
 
                       tx_clone
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use api::{
+  domain::run::{RunOptions, RunReport, RunResult},
+  utilities::data_file::parse_data_file,
+};
+
+use crate::Gui;
+
+pub fn runner_window(gui: &mut Gui, ctx: &egui::Context) {
+  let runner_window_open = gui.gui_state.runner_window_open.clone();
+  if let Ok(mut runner_window_open) = runner_window_open.try_write() {
+    if *runner_window_open {
+      egui::Window::new("Collection Runner")
+        .open(&mut runner_window_open)
+        .default_width(640.0)
+        .show(ctx, |ui| {
+          let collections = gui.worker_state.collections.try_read().unwrap().clone();
+          let selected_collection = &mut gui.gui_state.selected_run_collection;
+          egui::ComboBox::from_label("Collection to run")
+            .selected_text(
+              selected_collection
+                .as_ref()
+                .map_or("Select a collection".to_string(), |col| {
+                  col.info.name.clone()
+                }),
+            )
+            .show_ui(ui, |ui| {
+              for col in collections {
+                let name = col.info.name.clone();
+                ui.selectable_value(selected_collection, Some(col), name);
+              }
+            });
+          ui.horizontal(|ui| {
+            ui.label("Iterations");
+            ui.add(egui::DragValue::new(&mut gui.gui_state.run_iterations).range(0..=10000));
+            ui.weak("(0 runs once per data row)");
+          });
+          ui.horizontal(|ui| {
+            ui.label("Data file");
+            if ui.button("Browse").clicked() {
+              let file = rfd::FileDialog::new()
+                .add_filter("csv or json", &["csv", "json"])
+                .pick_file();
+              if let Some(file) = file {
+                gui.gui_state.run_data_file_path = file.to_str().unwrap_or("").into();
+              }
+            }
+            ui.text_edit_singleline(&mut gui.gui_state.run_data_file_path);
+          });
+          ui.horizontal(|ui| {
+            ui.label("Delay");
+            ui.add(egui::DragValue::new(&mut gui.gui_state.run_delay_ms).suffix(" ms"));
+            ui.checkbox(&mut gui.gui_state.run_stop_on_failure, "Stop on failure");
+          });
+
+          let is_running = gui.worker_state.run_progress.lock().unwrap().is_some();
+          let can_run = !is_running && gui.gui_state.selected_run_collection.is_some();
+          if ui.add_enabled(can_run, egui::Button::new("Run")).clicked() {
+            start_run(gui);
+          }
+          if let Some(error) = &*gui.worker_state.run_error.lock().unwrap() {
+            ui.colored_label(egui::Color32::RED, error);
+          }
+          ui.separator();
+
+          if let Some(progress) = &*gui.worker_state.run_progress.lock().unwrap() {
+            ui.label(format!("Running... {} requests sent", progress.len()));
+            render_results(ui, progress);
+            ctx.request_repaint();
+            return;
+          }
+          let Ok(reports) = gui.worker_state.run_reports.try_read() else {
+            return;
+          };
+          if reports.is_empty() {
+            ui.label("No runs yet");
+            return;
+          }
+          let selected_report = &mut gui.gui_state.selected_run_report;
+          let report = reports
+            .iter()
+            .find(|r| Some(&r.id) == selected_report.as_ref())
+            .unwrap_or(&reports[0]);
+          egui::ComboBox::from_label("Run")
+            .selected_text(report_label(report))
+            .show_ui(ui, |ui| {
+              for r in reports.iter() {
+                ui.selectable_value(selected_report, Some(r.id.clone()), report_label(r));
+              }
+            });
+          ui.label(format!(
+            "{} iterations with environment {}, {} passed, {} failed in {} ms{}",
+            report.iterations,
+            report.environment_name,
+            report.passed(),
+            report.failed(),
+            report.duration_ms,
+            if report.stopped_early {
+              ", stopped at the first failure"
+            } else {
+              ""
+            }
+          ));
+          render_results(ui, &report.results);
+        });
+    }
+  };
+}
+
+fn start_run(gui: &mut Gui) {
+  let Some(collection) = gui.gui_state.selected_run_collection.clone() else {
+    return;
+  };
+  let environment = gui.gui_state.selected_environment.borrow().clone();
+  let data_file_path = gui.gui_state.run_data_file_path.trim().to_string();
+  let iterations = gui.gui_state.run_iterations;
+  let delay_ms = gui.gui_state.run_delay_ms;
+  let stop_on_failure = gui.gui_state.run_stop_on_failure;
+  let api_for_worker = Arc::clone(&gui.worker_state.api);
+  let progress = Arc::clone(&gui.worker_state.run_progress);
+  let run_error = Arc::clone(&gui.worker_state.run_error);
+  let run_reports = Arc::clone(&gui.worker_state.run_reports);
+  gui.gui_state.selected_run_report = None;
+  *run_error.lock().unwrap() = None;
+  *progress.lock().unwrap() = Some(vec![]);
+  tokio::spawn(async move {
+    let outcome = async {
+      let data = if data_file_path.is_empty() {
+        vec![]
+      } else {
+        parse_data_file(&data_file_path)?
+      };
+      let options = RunOptions {
+        iterations: (iterations > 0).then_some(iterations),
+        data,
+        delay_ms,
+        stop_on_failure,
+      };
+      let items = collection.requests();
+      api_for_worker
+        .write()
+        .await
+        .run_collection(&collection, &items, environment, &options, |result| {
+          if let Some(results) = progress.lock().unwrap().as_mut() {
+            results.push(result.clone());
+          }
+        })
+        .await
+    }
+    .await;
+    match outcome {
+      Ok(report) => run_reports.write().await.insert(0, report),
+      Err(e) => *run_error.lock().unwrap() = Some(format!("Error running collection: {e:#}")),
+    }
+    *progress.lock().unwrap() = None;
+  });
+}
+
+fn report_label(report: &RunReport) -> String {
+  format!(
+    "{} at {} ({}/{})",
+    report.collection_name,
+    report.started_at,
+    report.passed(),
+    report.results.len()
+  )
+}
+
+fn render_results(ui: &mut egui::Ui, results: &[RunResult]) {
+  egui::ScrollArea::vertical().show(ui, |ui| {
+    egui::Grid::new("run_results")
+      .striped(true)
+      .num_columns(6)
+      .show(ui, |ui| {
+        ui.strong("#");
+        ui.strong("Request");
+        ui.strong("Status");
+        ui.strong("Time");
+        ui.strong("Tests");
+        ui.strong("");
+        ui.end_row();
+        for result in results {
+          ui.label(result.iteration.to_string());
+          ui.label(format!("{} {}", result.method, result.name));
+          ui.label(result.status.clone().unwrap_or_default());
+          ui.label(
+            result
+              .response_time
+              .map(|t| format!("{} ms", t))
+              .unwrap_or_default(),
+          );
+          let passed = result.test_results.iter().filter(|t| t.passed).count();
+          ui.label(format!("{}/{}", passed, result.test_results.len()));
+          if result.passed() {
+            ui.colored_label(egui::Color32::GREEN, "PASS");
+          } else {
+            ui.colored_label(egui::Color32::RED, "FAIL");
+          }
+          ui.end_row();
+          let failures = result.error.iter().cloned().chain(
+            result
+              .test_results
+              .iter()
+              .filter(|t| !t.passed)
+              .map(|t| match &t.message {
+                Some(message) => format!("{}: {}", t.name, message),
+                None => t.name.clone(),
+              }),
+          );
+          for failure in failures {
+            ui.label("");
+            ui.weak(failure);
+            ui.end_row();
+          }
+        }
+      });
+  });
+}
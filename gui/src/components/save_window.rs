@@ -93,14 +93,18 @@ pub fn save_window(gui: &mut Gui, ctx: &egui::Context) {
                 method: tab.method.clone(),
                 url: tab.url.clone(),
                 headers: Some(headers),
-                body: serde_json::from_str(&tab.req_body)
-                  .ok()
-                  .map(RequestBody::JSON),
+                body: RequestBody::from_text(&tab.body_type, &tab.req_body),
                 environment: EnvironmentFile {
                   id: "".into(),
                   name: "".into(),
                   values: None,
                 },
+                variables: vec![],
+                scripts: tab.scripts.clone(),
+                assertions: tab.assertions.clone(),
+                extract: tab.extract.clone(),
+                timeouts: tab.timeouts,
+                auth: tab.auth.to_request_auth(),
               };
 
               let _ = api_for_worker
@@ -0,0 +1,184 @@
+use api::domain::{
+  client::{ClientCertificate, ClientSettings, HttpVersion, RedirectPolicy},
+  request::RequestTimeouts,
+};
+use egui::{DragValue, ScrollArea, TextEdit};
+
+use crate::{events::GuiEvent, Gui};
+
+pub fn settings_window(gui: &mut Gui, ctx: &egui::Context) {
+  let settings_window_open = gui.gui_state.settings_window_open.clone();
+  let Ok(mut settings_window_open) = settings_window_open.try_write() else {
+    return;
+  };
+  if !*settings_window_open {
+    return;
+  }
+  egui::Window::new("Settings")
+    .open(&mut settings_window_open)
+    .show(ctx, |ui| {
+      ScrollArea::vertical().show(ui, |ui| {
+        ui.heading("Default timeouts");
+        ui.weak("Used by requests that don't set their own. 0 is no limit.");
+        let mut timeouts = gui.gui_state.default_timeouts.borrow_mut();
+        egui::Grid::new("default_timeouts").show(ui, |ui| {
+          for (label, value) in timeout_fields(&mut timeouts) {
+            ui.label(label);
+            let mut ms = value.unwrap_or(0);
+            ui.add(DragValue::new(&mut ms).speed(100).suffix(" ms"));
+            *value = Some(ms);
+            ui.end_row();
+          }
+        });
+        ui.separator();
+        let mut client = gui.gui_state.client_settings.borrow_mut();
+        render_client_settings(ui, &mut client);
+        ui.separator();
+        if ui.button("Save").clicked() {
+          let _ = gui
+            .event_tx
+            .try_send(GuiEvent::SaveDefaultTimeouts(*timeouts));
+          let _ = gui
+            .event_tx
+            .try_send(GuiEvent::SaveClientSettings(client.clone()));
+        }
+      });
+    });
+}
+
+fn render_client_settings(ui: &mut egui::Ui, client: &mut ClientSettings) {
+  ui.heading("Proxy");
+  egui::Grid::new("proxy_settings").show(ui, |ui| {
+    ui.label("HTTP proxy");
+    ui.add(TextEdit::singleline(&mut client.http_proxy).hint_text("http://localhost:8888"));
+    ui.end_row();
+    ui.label("HTTPS proxy");
+    ui.add(TextEdit::singleline(&mut client.https_proxy).hint_text("http://localhost:8888"));
+    ui.end_row();
+    ui.label("No proxy for");
+    ui.add(
+      TextEdit::singleline(&mut client.no_proxy).hint_text("localhost, .internal.example.com"),
+    );
+    ui.end_row();
+  });
+
+  ui.heading("Certificates");
+  ui.label("Trusted CA certificates");
+  let mut remove_index = None;
+  for (index, path) in client.ca_certificates.iter().enumerate() {
+    ui.horizontal(|ui| {
+      if ui.button("x").clicked() {
+        remove_index = Some(index);
+      }
+      ui.label(path);
+    });
+  }
+  if let Some(index) = remove_index {
+    client.ca_certificates.remove(index);
+  }
+  if ui.button("Add CA Certificate").clicked() {
+    if let Some(file) = rfd::FileDialog::new().pick_file() {
+      client
+        .ca_certificates
+        .push(file.to_str().unwrap_or("").into());
+    }
+  }
+  ui.horizontal(|ui| {
+    ui.label("Skip verification for");
+    let mut hosts = client.insecure_hosts.join(", ");
+    let edit = TextEdit::singleline(&mut hosts).hint_text("dev.local, *.staging.example.com");
+    if ui.add(edit).changed() {
+      client.insecure_hosts = hosts
+        .split(',')
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .collect();
+    }
+  });
+  ui.label("Client certificates");
+  ui.weak("A pem certificate and key, or a .p12/.pfx file with its password");
+  let mut remove_index = None;
+  egui::Grid::new("client_certificates").show(ui, |ui| {
+    for (index, certificate) in client.client_certificates.iter_mut().enumerate() {
+      ui.add(
+        TextEdit::singleline(&mut certificate.host)
+          .hint_text("Host")
+          .desired_width(140.0),
+      );
+      for (path, hint) in [
+        (&mut certificate.certificate, "Certificate"),
+        (&mut certificate.key, "Key"),
+      ] {
+        ui.horizontal(|ui| {
+          ui.add(TextEdit::singleline(path).hint_text(hint));
+          if ui.button("Browse").clicked() {
+            if let Some(file) = rfd::FileDialog::new().pick_file() {
+              *path = file.to_str().unwrap_or("").into();
+            }
+          }
+        });
+      }
+      ui.add(
+        TextEdit::singleline(&mut certificate.password)
+          .password(true)
+          .hint_text("Password")
+          .desired_width(100.0),
+      );
+      if ui.button("x").clicked() {
+        remove_index = Some(index);
+      }
+      ui.end_row();
+    }
+  });
+  if let Some(index) = remove_index {
+    client.client_certificates.remove(index);
+  }
+  if ui.button("Add Client Certificate").clicked() {
+    client
+      .client_certificates
+      .push(ClientCertificate::default());
+  }
+
+  ui.heading("Requests");
+  ui.horizontal(|ui| {
+    egui::ComboBox::from_label("Redirects")
+      .selected_text(client.redirects.to_string())
+      .show_ui(ui, |ui| {
+        for redirects in [
+          RedirectPolicy::FOLLOW,
+          RedirectPolicy::LIMIT(5),
+          RedirectPolicy::OFF,
+        ] {
+          let label = match redirects {
+            RedirectPolicy::LIMIT(_) => "Follow up to".to_string(),
+            other => other.to_string(),
+          };
+          let selected =
+            std::mem::discriminant(&client.redirects) == std::mem::discriminant(&redirects);
+          if ui.selectable_label(selected, label).clicked() && !selected {
+            client.redirects = redirects;
+          }
+        }
+      });
+    if let RedirectPolicy::LIMIT(limit) = &mut client.redirects {
+      ui.add(DragValue::new(limit).range(1..=100));
+    }
+  });
+  egui::ComboBox::from_label("HTTP version")
+    .selected_text(client.http_version.to_string())
+    .show_ui(ui, |ui| {
+      for version in [HttpVersion::AUTO, HttpVersion::HTTP1, HttpVersion::HTTP2] {
+        let label = version.to_string();
+        ui.selectable_value(&mut client.http_version, version, label);
+      }
+    });
+}
+
+// The timeouts that can be set, by their labels
+pub fn timeout_fields(timeouts: &mut RequestTimeouts) -> [(&'static str, &mut Option<u64>); 3] {
+  [
+    ("Connect", &mut timeouts.connect_ms),
+    ("Read", &mut timeouts.read_ms),
+    ("Total", &mut timeouts.total_ms),
+  ]
+}
@@ -2,24 +2,56 @@ use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 
 use api::domain::{
-  collection::{Collection, CollectionFolder, CollectionRequest},
-  environment::EnvironmentFile,
-  request::{DBRequest, HttpRequest, OAuth2Request},
+  auth::{AuthSettings, OAuth2Prompt, OAuth2Token},
+  client::ClientSettings,
+  collection::{Collection, CollectionFolder, CollectionItem},
+  cookie::Cookie,
+  environment::{EnvironmentFile, EnvironmentValue},
+  graphql::GraphQLSchema,
+  grpc::{GrpcMethod, GrpcRequest},
+  request::{DBRequest, HttpRequest, OAuth2Request, RequestTimeouts},
   request_item::RequestHistoryItem,
-  response::DBResponse,
+  response::{DBResponse, ResponseBody, ResponseData},
   tab::Tab,
+  websocket::WebSocketRequest,
 };
 use uuid::Uuid;
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum GuiEvent {
   SelectRequest {
     col_id: String,
-    request: CollectionRequest,
+    item: CollectionItem,
   },
   SelectEnvironment(String),
-  SubmitRequest(HttpRequest),
+  // the tab's own auth settings are sent along, the request may carry the auth it inherits
+  SubmitRequest {
+    request: HttpRequest,
+    auth: AuthSettings,
+  },
+  FetchGraphQLSchema(HttpRequest),
+  ConnectWebSocket(WebSocketRequest),
+  SubmitGrpcRequest(GrpcRequest),
+  LoadGrpcMethods(GrpcRequest),
+  GrpcMethodsLoaded {
+    tab_id: Uuid,
+    result: Result<Vec<GrpcMethod>, String>,
+  },
+  GraphQLSchemaFetched {
+    url: String,
+    result: Result<GraphQLSchema, String>,
+  },
   SubmitOAuth2Request(OAuth2Request),
+  RefreshOAuth2Token(OAuth2Request),
+  // what the user has to do while a sign in waits for them
+  OAuth2Prompt(OAuth2Prompt),
+  OAuth2TokenReceived(Result<OAuth2Token, String>),
+  SaveResponseBody {
+    body: ResponseBody,
+    data: ResponseData,
+    path: String,
+  },
   RefreshCollections(Option<Vec<Collection>>),
   RefreshEnvironments(),
   RefreshRequestData {
@@ -31,6 +63,24 @@ pub enum GuiEvent {
   SetActiveTab(String),
   SaveCollection(Collection),
   SaveEnvironment(),
+  SaveGlobals(Vec<EnvironmentValue>),
+  SaveDefaultTimeouts(RequestTimeouts),
+  SaveClientSettings(ClientSettings),
+  LoadCookies(EnvironmentFile),
+  CookiesLoaded {
+    environment_id: String,
+    cookies: Vec<Cookie>,
+  },
+  SaveCookies {
+    environment: EnvironmentFile,
+    cookies: Vec<Cookie>,
+  },
+  // what the tab's last response set, shown with the response
+  ResponseCookies {
+    tab_id: Uuid,
+    cookies: Vec<Cookie>,
+  },
+  UpdateEnvironment(EnvironmentFile),
   NewCollection(Option<String>),
   NewEnvironment(Option<String>),
   NewRequest(),
@@ -1,23 +1,35 @@
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 pub mod components;
 mod events;
 
-use anyhow::Error;
 use api::{
+  db::repository::PostieDb,
   domain::{
+    auth::{AuthSettings, OAuth2Prompt, RequestAuth},
+    client::ClientSettings,
     collection::Collection,
+    cookie::Cookie,
     environment::{EnvironmentFile, EnvironmentValue},
+    graphql::GraphQLSchema,
+    grpc::GrpcMethod,
     header::Headers,
-    request::{DBRequest, HttpMethod, HttpRequest, OAuth2Request, OAuthRequestBody, PostieRequest},
+    request::{BodyType, DBRequest, HttpMethod, HttpRequest, RequestScripts, RequestTimeouts},
     request_item::RequestHistoryItem,
-    response::{DBResponse, ResponseData},
+    response::{DBResponse, ResponseBody, ResponseData, ResponseProgress, TestResult},
+    run::{RunReport, RunResult},
+    sse::SseEvent,
     tab::Tab,
+    websocket::{WebSocketCommand, WebSocketDirection, WebSocketMessage, WebSocketMessageKind},
   },
   PostieApi,
 };
 use components::{
   content_header_panel::ContentHeaderPanel, content_panel::ContentPanel,
-  content_side_panel::ContentSidePanel, import_modal::import_modal, menu_panel::MenuPanel,
-  new_modal::NewWindow, save_window::save_window, side_panel::side_panel,
+  content_side_panel::ContentSidePanel, cookies_window::cookies_window, export_modal::export_modal,
+  import_modal::import_modal, menu_panel::MenuPanel, new_modal::NewWindow,
+  runner_window::runner_window, save_window::save_window, settings_window::settings_window,
+  side_panel::side_panel,
 };
 use eframe::{egui, App, NativeOptions};
 use std::{
@@ -27,7 +39,7 @@ use std::{
   str::FromStr,
   sync::{Arc, Mutex},
 };
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use uuid::Uuid;
 
 // Holds app state that needs to be thread safe
@@ -41,11 +53,34 @@ pub struct ThreadSafeState {
   pub saved_responses: Arc<RwLock<HashMap<String, DBResponse>>>,
   pub request_history_items: Arc<RwLock<Vec<RequestHistoryItem>>>,
   pub response: Arc<RwLock<Option<ResponseData>>>,
-  pub oauth_response: Arc<RwLock<Option<ResponseData>>>,
   pub res_status: Arc<RwLock<String>>,
-  pub received_token: Arc<Mutex<bool>>,
   pub is_requesting: Arc<RwLock<Option<bool>>>,
   pub import_result: Arc<Mutex<Option<String>>>,
+  pub export_result: Arc<Mutex<Option<String>>>,
+  pub test_results: Arc<RwLock<Vec<TestResult>>>,
+  pub run_reports: Arc<RwLock<Vec<RunReport>>>,
+  // results of the run in progress, None when nothing is running
+  pub run_progress: Arc<Mutex<Option<Vec<RunResult>>>>,
+  pub run_error: Arc<Mutex<Option<String>>>,
+  // by tab id, kept after the connection closes so its log can still be read
+  pub websocket_sessions: Arc<Mutex<HashMap<Uuid, WebSocketSession>>>,
+  // events of the text/event-stream response being read, None when nothing is streaming
+  pub event_stream: Arc<Mutex<Option<Vec<SseEvent>>>>,
+  // stops the request in flight
+  pub stop_request: Arc<Mutex<Option<watch::Sender<bool>>>>,
+  // how much of the response body has been read, None when nothing is downloading
+  pub download_progress: Arc<Mutex<Option<ResponseProgress>>>,
+  // size and kind of each tab's last response body, and the file holding it when it was too big
+  // or binary to keep in the tab
+  pub response_bodies: Arc<Mutex<HashMap<Uuid, ResponseBody>>>,
+}
+
+pub struct WebSocketSession {
+  // id of the request that opened it, a reconnect replaces the session
+  pub id: Uuid,
+  pub log: Vec<WebSocketMessage>,
+  // None once the connection is closed
+  pub commands: Option<tokio::sync::mpsc::Sender<WebSocketCommand>>,
 }
 
 pub struct GuiState {
@@ -54,14 +89,11 @@ pub struct GuiState {
   pub selected_environment: Rc<RefCell<api::domain::environment::EnvironmentFile>>,
   pub selected_collection: Rc<RefCell<Option<api::domain::collection::Collection>>>,
   pub selected_http_method: api::domain::request::HttpMethod,
-  pub selected_auth_mode: api::domain::ui::AuthMode,
   pub selected_save_window_collection: Option<api::domain::collection::Collection>,
   pub selected_save_window_folder: Option<String>,
-  pub api_key: String,
-  pub api_key_name: String,
-  pub bearer_token: String,
-  pub oauth_config: OAuth2Request,
   pub oauth_token: String,
+  // how getting the last token went, or what the user has to do to sign in
+  pub oauth_status: String,
   pub url: String,
   pub body_str: String,
   pub import_window_open: RwLock<bool>,
@@ -71,7 +103,39 @@ pub struct GuiState {
   pub save_window_open: Rc<RwLock<bool>>,
   pub import_mode: RwLock<api::domain::ui::ImportMode>,
   pub import_file_path: String,
+  pub export_window_open: RwLock<bool>,
+  pub export_mode: RwLock<api::domain::ui::ExportMode>,
+  pub export_file_path: String,
+  pub selected_export_collection: Option<api::domain::collection::Collection>,
+  pub selected_export_environment: Option<EnvironmentFile>,
+  pub runner_window_open: Rc<RwLock<bool>>,
+  pub settings_window_open: Rc<RwLock<bool>>,
+  // timeouts of requests that don't override them
+  pub default_timeouts: Rc<RefCell<RequestTimeouts>>,
+  pub client_settings: Rc<RefCell<ClientSettings>>,
+  pub cookies_window_open: Rc<RwLock<bool>>,
+  // the cookie manager's cookies and the id of the environment they belong to
+  pub cookies: Rc<RefCell<Option<(String, Vec<Cookie>)>>>,
+  // filled in by the cookie manager's add row
+  pub new_cookie: Rc<RefCell<Cookie>>,
+  // cookies set by each tab's last response
+  pub response_cookies: Rc<RefCell<HashMap<Uuid, Vec<Cookie>>>>,
+  // introspected schemas by the url they were fetched from, or why fetching failed
+  pub graphql_schemas: Rc<RefCell<HashMap<String, Result<GraphQLSchema, String>>>>,
+  pub websocket_frame_mode: Rc<RefCell<api::domain::ui::WebSocketFrameMode>>,
+  pub websocket_draft: Rc<RefCell<String>>,
+  // .proto files picked for each grpc tab, server reflection is used for tabs without any
+  pub grpc_proto_files: Rc<RefCell<HashMap<Uuid, Vec<String>>>>,
+  // methods each grpc tab can call, or why loading them failed
+  pub grpc_methods: Rc<RefCell<HashMap<Uuid, Result<Vec<GrpcMethod>, String>>>>,
+  pub selected_run_collection: Option<api::domain::collection::Collection>,
+  pub selected_run_report: Option<String>,
+  pub run_iterations: usize,
+  pub run_data_file_path: String,
+  pub run_delay_ms: u64,
+  pub run_stop_on_failure: bool,
   pub env_vars: Rc<RefCell<Vec<EnvironmentValue>>>,
+  pub globals: Rc<RefCell<Vec<EnvironmentValue>>>,
   pub active_window: RwLock<api::domain::ui::ActiveWindow>,
   pub request_window_mode: RwLock<api::domain::ui::RequestWindowMode>,
 }
@@ -100,6 +164,25 @@ impl Gui {
       .load_environments()
       .await
       .unwrap_or(vec![EnvironmentFile::default()]);
+    let db_globals = api.read().await.load_globals().await.unwrap_or_default();
+    let db_run_reports = api
+      .read()
+      .await
+      .load_run_reports()
+      .await
+      .unwrap_or_default();
+    let db_default_timeouts = api
+      .read()
+      .await
+      .load_default_timeouts()
+      .await
+      .unwrap_or(RequestTimeouts::DEFAULTS);
+    let db_client_settings = api
+      .read()
+      .await
+      .load_client_settings()
+      .await
+      .unwrap_or_default();
     let saved_tabs = api.write().await.load_tabs().await.unwrap();
     let db_collections = api.write().await.load_collections().await.unwrap();
     let db_request_history_items = api
@@ -129,10 +212,18 @@ impl Gui {
         method: api::domain::request::HttpMethod::GET,
         url: "".into(),
         req_body: "".into(),
+        body_type: BodyType::default(),
         req_headers: Headers(vec![]),
         res_status: Some("".into()),
         res_body: "".into(),
         res_headers: Headers(vec![]),
+        collection_id: None,
+        request_id: None,
+        scripts: RequestScripts::default(),
+        assertions: vec![],
+        extract: vec![],
+        timeouts: RequestTimeouts::default(),
+        auth: AuthSettings::default(),
       };
       let mut default_tab_map: HashMap<String, Tab> = HashMap::new();
       default_tab_map.insert(Uuid::new_v4().to_string(), default_tab);
@@ -155,13 +246,19 @@ impl Gui {
       response: Arc::new(RwLock::new(Some(ResponseData::JSON(
         serde_json::from_str(&default_active_tab.res_body).unwrap_or(serde_json::Value::Null),
       )))),
-      res_status: Arc::new(RwLock::new(
-        default_active_tab.res_status.clone().unwrap().into(),
-      )),
-      oauth_response: Arc::new(RwLock::new(None)),
-      received_token: Arc::new(Mutex::new(false)),
+      res_status: Arc::new(RwLock::new(default_active_tab.res_status.clone().unwrap())),
       is_requesting: Arc::new(RwLock::new(None)),
       import_result: Arc::new(Mutex::new(None)),
+      export_result: Arc::new(Mutex::new(None)),
+      test_results: Arc::new(RwLock::new(vec![])),
+      run_reports: Arc::new(RwLock::new(db_run_reports)),
+      run_progress: Arc::new(Mutex::new(None)),
+      run_error: Arc::new(Mutex::new(None)),
+      websocket_sessions: Arc::new(Mutex::new(HashMap::new())),
+      event_stream: Arc::new(Mutex::new(None)),
+      stop_request: Arc::new(Mutex::new(None)),
+      download_progress: Arc::new(Mutex::new(None)),
+      response_bodies: Arc::new(Mutex::new(HashMap::new())),
     };
     let gui_state = GuiState {
       url: default_active_tab.url.clone(),
@@ -170,6 +267,7 @@ impl Gui {
       request_window_mode: RwLock::new(api::domain::ui::RequestWindowMode::BODY),
       body_str: default_active_tab.req_body.clone(),
       env_vars: Rc::new(RefCell::new(vec![])),
+      globals: Rc::new(RefCell::new(db_globals)),
       selected_http_method: default_active_tab.method.clone(),
       selected_collection: Rc::new(RefCell::new(None)),
       selected_environment: Rc::new(RefCell::new(EnvironmentFile {
@@ -183,24 +281,10 @@ impl Gui {
         }]),
       })),
       selected_history_item: Rc::new(RefCell::new(None)),
-      selected_auth_mode: api::domain::ui::AuthMode::NONE,
       selected_save_window_collection: None,
       selected_save_window_folder: None,
-      api_key_name: "".into(),
-      api_key: "".into(),
-      bearer_token: "".into(),
       oauth_token: "".into(),
-      oauth_config: OAuth2Request {
-        access_token_url: "".into(),
-        refresh_url: "".into(),
-        client_id: "".into(),
-        client_secret: "".into(),
-        request: OAuthRequestBody {
-          grant_type: "client_credentials".into(),
-          scope: "".into(),
-          audience: "".into(),
-        },
-      },
+      oauth_status: "".into(),
       import_window_open: RwLock::new(false),
       new_window_open: RwLock::new(false),
       new_window_mode: RwLock::new(api::domain::ui::NewWindowMode::COLLECTION),
@@ -208,6 +292,30 @@ impl Gui {
       new_name: "".into(),
       import_file_path: "".into(),
       import_mode: RwLock::new(api::domain::ui::ImportMode::COLLECTION),
+      export_window_open: RwLock::new(false),
+      export_mode: RwLock::new(api::domain::ui::ExportMode::COLLECTION),
+      export_file_path: "".into(),
+      selected_export_collection: None,
+      selected_export_environment: None,
+      runner_window_open: Rc::new(RwLock::new(false)),
+      settings_window_open: Rc::new(RwLock::new(false)),
+      default_timeouts: Rc::new(RefCell::new(db_default_timeouts)),
+      client_settings: Rc::new(RefCell::new(db_client_settings)),
+      cookies_window_open: Rc::new(RwLock::new(false)),
+      cookies: Rc::new(RefCell::new(None)),
+      new_cookie: Rc::new(RefCell::new(Cookie::default())),
+      response_cookies: Rc::new(RefCell::new(HashMap::new())),
+      graphql_schemas: Rc::new(RefCell::new(HashMap::new())),
+      grpc_proto_files: Rc::new(RefCell::new(HashMap::new())),
+      grpc_methods: Rc::new(RefCell::new(HashMap::new())),
+      websocket_frame_mode: Rc::new(RefCell::new(api::domain::ui::WebSocketFrameMode::TEXT)),
+      websocket_draft: Rc::new(RefCell::new(String::new())),
+      selected_run_collection: None,
+      selected_run_report: None,
+      run_iterations: 1,
+      run_data_file_path: "".into(),
+      run_delay_ms: 0,
+      run_stop_on_failure: false,
     };
     (worker_state, gui_state)
   }
@@ -226,6 +334,13 @@ impl Gui {
     let environments = self.worker_state.environments.clone();
     let requests = self.worker_state.saved_requests.clone();
     let request_history_items = self.worker_state.request_history_items.clone();
+    let test_results = self.worker_state.test_results.clone();
+    let websocket_sessions = self.worker_state.websocket_sessions.clone();
+    let event_stream = self.worker_state.event_stream.clone();
+    let stop_request = self.worker_state.stop_request.clone();
+    let download_progress = self.worker_state.download_progress.clone();
+    let response_bodies = self.worker_state.response_bodies.clone();
+    let is_requesting = self.worker_state.is_requesting.clone();
     tokio::spawn(async move {
       Self::start_event_worker(
         event_rx,
@@ -238,6 +353,13 @@ impl Gui {
         environments,
         requests,
         request_history_items,
+        test_results,
+        websocket_sessions,
+        event_stream,
+        stop_request,
+        download_progress,
+        response_bodies,
+        is_requesting,
       )
       .await
     });
@@ -253,7 +375,8 @@ impl Gui {
     let content_side_panel = ContentSidePanel::new();
     let menu_panel = MenuPanel::new();
     let new_modal = NewWindow::new();
-    let gui = Gui {
+
+    Gui {
       worker_state,
       event_tx,
       res_rx,
@@ -263,8 +386,111 @@ impl Gui {
       content_side_panel,
       menu_panel,
       new_modal,
+    }
+  }
+
+  // Tabs opened from a collection pick up its collection, folder and request variables
+  async fn collection_variables(
+    active_tab: &Arc<RwLock<Tab>>,
+    collections: &Arc<RwLock<Vec<Collection>>>,
+  ) -> Option<Vec<(String, String)>> {
+    let (col_id, item_id) = {
+      let tab = active_tab.read().await;
+      tab.collection_id.clone().zip(tab.request_id.clone())?
+    };
+    collections
+      .read()
+      .await
+      .iter()
+      .find(|c| c.info.id == col_id)
+      .map(|col| col.variables_for(&item_id))
+  }
+
+  // Tabs opened from a collection inherit the auth of the request's folders or the collection
+  async fn collection_auth(
+    active_tab: &Arc<RwLock<Tab>>,
+    collections: &Arc<RwLock<Vec<Collection>>>,
+  ) -> Option<RequestAuth> {
+    let (col_id, item_id) = {
+      let tab = active_tab.read().await;
+      tab.collection_id.clone().zip(tab.request_id.clone())?
     };
-    gui
+    let collections = collections.read().await;
+    let (auth, source) = collections
+      .iter()
+      .find(|c| c.info.id == col_id)?
+      .inherited_auth_for(&item_id)?;
+    log::debug!("using the {} of {}", auth, source);
+    Some(auth)
+  }
+
+  // Puts a response, or the error in its place, on the active tab
+  async fn show_response(
+    result: anyhow::Result<api::domain::response::Response>,
+    active_tab: &Arc<RwLock<Tab>>,
+    tabs: &Arc<RwLock<HashMap<String, Tab>>>,
+    test_results: &Arc<RwLock<Vec<TestResult>>>,
+    response_bodies: &Arc<Mutex<HashMap<Uuid, ResponseBody>>>,
+    res_tx: &tokio::sync::mpsc::Sender<events::GuiEvent>,
+  ) {
+    match result {
+      Ok(res) => {
+        println!("Res: {:?}", res);
+        *test_results.write().await = res.test_results.clone();
+        let tab_id = active_tab.read().await.id;
+        // the previous response's file is of no use once it is replaced
+        if let Some(previous) = response_bodies
+          .lock()
+          .unwrap()
+          .insert(tab_id, res.body.clone())
+        {
+          if let Some(file) = previous.file {
+            let _ = std::fs::remove_file(file);
+          }
+        }
+        let _ = res_tx
+          .send(events::GuiEvent::ResponseCookies {
+            tab_id,
+            cookies: res.cookies.clone(),
+          })
+          .await;
+        if let Some(environment) = res.environment.clone() {
+          let _ = res_tx
+            .send(events::GuiEvent::UpdateEnvironment(environment))
+            .await;
+        }
+        let mut active_tab_writer = active_tab.write().await;
+        active_tab_writer.res_status = Some(res.status);
+        active_tab_writer.res_body = res.data.to_raw_string();
+
+        let mut tabs_writer = tabs.write().await;
+        if let Some(tab_match) = tabs_writer.get_mut(&active_tab_writer.id.to_string()) {
+          tab_match.res_status = active_tab_writer.res_status.clone();
+          tab_match.res_body = active_tab_writer.res_body.clone();
+          tab_match.url = active_tab_writer.url.clone();
+        }
+      }
+      Err(err) => {
+        println!("Error with request: {:?}", err);
+        test_results.write().await.clear();
+        let tab_id = active_tab.read().await.id;
+        let _ = res_tx
+          .send(events::GuiEvent::ResponseCookies {
+            tab_id,
+            cookies: vec![],
+          })
+          .await;
+        let mut active_tab_writer = active_tab.write().await;
+        active_tab_writer.res_status = Some("Error".into());
+        active_tab_writer.res_body = serde_json::json!({ "error": format!("{err:#}") }).to_string();
+
+        let mut tabs_writer = tabs.write().await;
+        if let Some(tab_match) = tabs_writer.get_mut(&active_tab_writer.id.to_string()) {
+          tab_match.res_status = active_tab_writer.res_status.clone();
+          tab_match.res_body = active_tab_writer.res_body.clone();
+        }
+      }
+    }
   }
 
   // Async event listener spawned from start_event_worker
@@ -279,6 +505,13 @@ impl Gui {
     environments: Arc<RwLock<Vec<EnvironmentFile>>>,
     _requests: Arc<RwLock<HashMap<String, DBRequest>>>,
     _request_history_items: Arc<RwLock<Vec<RequestHistoryItem>>>,
+    test_results: Arc<RwLock<Vec<TestResult>>>,
+    websocket_sessions: Arc<Mutex<HashMap<Uuid, WebSocketSession>>>,
+    event_stream: Arc<Mutex<Option<Vec<SseEvent>>>>,
+    stop_request: Arc<Mutex<Option<watch::Sender<bool>>>>,
+    download_progress: Arc<Mutex<Option<ResponseProgress>>>,
+    response_bodies: Arc<Mutex<HashMap<Uuid, ResponseBody>>>,
+    is_requesting: Arc<RwLock<Option<bool>>>,
   ) {
     while let Some(event) = event_rx.recv().await {
       let api_for_worker = Arc::clone(&api);
@@ -287,70 +520,136 @@ impl Gui {
       let ctx_for_worker = ctx.clone();
       let res_tx_for_worker = res_tx.clone();
       let collections_for_worker = Arc::clone(&collections);
+      let test_results_for_worker = Arc::clone(&test_results);
+      let websocket_sessions_for_worker = Arc::clone(&websocket_sessions);
+      let event_stream_for_worker = Arc::clone(&event_stream);
+      let stop_request_for_worker = Arc::clone(&stop_request);
+      let download_progress_for_worker = Arc::clone(&download_progress);
+      let response_bodies_for_worker = Arc::clone(&response_bodies);
+      let is_requesting_for_worker = Arc::clone(&is_requesting);
       match event {
         events::GuiEvent::SetActiveTab(input) => {
           tokio::spawn(
             async move { res_tx_for_worker.try_send(events::GuiEvent::SetActiveTab(input)) },
           );
         }
-        events::GuiEvent::SubmitRequest(input) => {
+        events::GuiEvent::SubmitRequest {
+          request: mut input,
+          auth,
+        } => {
           println!("handling submit request");
+          // event streams can run for as long as the server keeps them open, so the request
+          // doesn't hold the api the other events use
+          let db = PostieDb {
+            pool: api_for_worker.read().await.db.pool.clone(),
+          };
           tokio::spawn(async move {
-            match api_for_worker
-              .write()
-              .await
-              .make_request(PostieRequest::HTTP(input))
-              .await
+            if let Some(variables) =
+              Self::collection_variables(&active_tab_for_worker, &collections_for_worker).await
             {
-              Ok(res) => {
-                println!("Res: {:?}", res);
-                let mut active_tab_writer = active_tab_for_worker.write().await;
-                active_tab_writer.res_status = Some(res.status);
-                active_tab_writer.res_body = res.data.to_raw_string();
-
-                let mut tabs_writer = tabs_for_worker.write().await;
-                if let Some(tab_match) = tabs_writer.get_mut(&active_tab_writer.id.to_string()) {
-                  tab_match.res_status = active_tab_writer.res_status.clone();
-                  tab_match.res_body = active_tab_writer.res_body.clone();
-                  tab_match.url = active_tab_writer.url.clone();
-                }
-
-                ctx_for_worker.request_repaint();
-              }
-              Err(err) => {
-                println!("Error with request: {:?}", err);
-              }
-            };
+              input.variables = variables;
+            }
+            if input.auth == RequestAuth::INHERIT {
+              input.auth = Self::collection_auth(&active_tab_for_worker, &collections_for_worker)
+                .await
+                .unwrap_or_default();
+            }
+            let (stop, stop_rx) = watch::channel(false);
+            *stop_request_for_worker.lock().unwrap() = Some(stop);
+            *event_stream_for_worker.lock().unwrap() = None;
+            *is_requesting_for_worker.write().await = Some(true);
+            let progress_ctx = ctx_for_worker.clone();
+            let progress_for_worker = Arc::clone(&download_progress_for_worker);
+            let mut api = PostieApi::with_db(db);
+            // large and binary bodies are saved from their file, show_response deletes it
+            api.keep_response_files = true;
+            let result = api
+              .make_streaming_request(
+                input,
+                auth,
+                Some(stop_rx),
+                |event| {
+                  event_stream_for_worker
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(Vec::new)
+                    .push(event.clone());
+                  ctx_for_worker.request_repaint();
+                },
+                move |progress| {
+                  *progress_for_worker.lock().unwrap() = Some(progress);
+                  progress_ctx.request_repaint();
+                },
+              )
+              .await;
+            *stop_request_for_worker.lock().unwrap() = None;
+            *event_stream_for_worker.lock().unwrap() = None;
+            *download_progress_for_worker.lock().unwrap() = None;
+            *is_requesting_for_worker.write().await = Some(false);
+            Self::show_response(
+              result,
+              &active_tab_for_worker,
+              &tabs_for_worker,
+              &test_results_for_worker,
+              &response_bodies_for_worker,
+              &res_tx_for_worker,
+            )
+            .await;
+            ctx_for_worker.request_repaint();
             res_tx_for_worker.try_send(events::GuiEvent::SetActiveTab(String::from(
-              (*active_tab_for_worker.try_read().unwrap()).id,
+              active_tab_for_worker.try_read().unwrap().id,
             )))
             // TODO after response is saved, re-run db calls to refresh request/response data
           });
         }
         events::GuiEvent::SubmitOAuth2Request(data) => {
           println!("submitting oauth 2 request");
+          // signing in can take minutes, so it doesn't hold the api other requests use
+          let db = PostieDb {
+            pool: api_for_worker.read().await.db.pool.clone(),
+          };
+          tokio::spawn(async move {
+            let prompt_tx = res_tx_for_worker.clone();
+            let prompt_ctx = ctx_for_worker.clone();
+            let result = PostieApi::with_db(db)
+              .request_oauth2_token(&data, move |prompt| {
+                let _ = prompt_tx.try_send(events::GuiEvent::OAuth2Prompt(prompt));
+                prompt_ctx.request_repaint();
+              })
+              .await
+              .map_err(|e| e.to_string());
+            let _ = res_tx_for_worker
+              .send(events::GuiEvent::OAuth2TokenReceived(result))
+              .await;
+            ctx_for_worker.request_repaint();
+          });
+        }
+        events::GuiEvent::RefreshOAuth2Token(data) => {
           tokio::spawn(async move {
-            let res = api_for_worker
+            let result = api_for_worker
               .write()
               .await
-              .make_request(PostieRequest::OAUTH(data))
+              .refresh_oauth2_token(&data)
               .await
-              .ok()
-              .unwrap();
-            println!("{:?}", &res);
-            let _ = Ok::<ResponseData, Error>(res.data);
+              .map_err(|e| e.to_string());
+            let _ = res_tx_for_worker
+              .send(events::GuiEvent::OAuth2TokenReceived(result))
+              .await;
+            ctx_for_worker.request_repaint();
           });
         }
-        events::GuiEvent::SelectRequest { col_id: _, request } => {
+        events::GuiEvent::SelectRequest { col_id, item } => {
+          let request = item.request.clone();
           println!("request selected");
           tokio::spawn(async move {
             let mut tabs_guard = tabs_for_worker.write().await;
 
+            // requests can share a url and method, so tabs are matched to the saved request
             let existing_tab_id = tabs_guard
               .values()
               .find(|t| {
-                t.url == request.url.raw
-                  && t.method == HttpMethod::from_str(&request.method).unwrap_or(HttpMethod::GET)
+                t.collection_id.as_deref() == Some(col_id.as_str())
+                  && t.request_id.as_deref() == Some(item.id.as_str())
               })
               .map(|t| t.id.to_string());
 
@@ -366,15 +665,33 @@ impl Gui {
 
                 new_tab.url = request.url.raw;
                 new_tab.method = HttpMethod::from_str(&request.method).unwrap_or(HttpMethod::GET);
+                new_tab.collection_id = Some(col_id);
+                new_tab.scripts = item.scripts();
+                new_tab.assertions = item.assertions.clone().unwrap_or_default();
+                new_tab.extract = item.extract.clone().unwrap_or_default();
+                new_tab.timeouts = item.timeouts.unwrap_or_default();
+                new_tab.request_id = Some(item.id);
 
                 if let Some(body) = request.body {
-                  new_tab.req_body = body.raw.unwrap_or_default();
+                  if let Some(mapped) = body.to_request_body() {
+                    new_tab.body_type = mapped.body_type();
+                    new_tab.req_body = body.raw.unwrap_or_else(|| mapped.to_text());
+                  }
                 }
 
                 if let Some(headers) = request.header {
                   new_tab.req_headers = headers.into_iter().map(|h| (h.key, h.value)).collect();
                 }
 
+                // postman leaves out the auth of requests that inherit it
+                if let Some(auth) = request.auth {
+                  new_tab.auth = AuthSettings::from_request_auth(&auth.to_request_auth());
+                }
+
+                // save straight away so the tab stays linked to its collection request
+                if let Err(e) = api_for_worker.write().await.save_tab(&new_tab).await {
+                  println!("Error saving tab: {:?}", e);
+                }
                 tabs_guard.insert(new_id.clone(), new_tab);
                 new_id
               }
@@ -442,8 +759,7 @@ impl Gui {
           *request_history_item_write_guard = request_history_items;
           *saved_requests_write_guard = requests_by_id;
           *saved_responses_write_guard = responses_by_id;
-          let tabs_by_id: HashMap<Uuid, Tab> =
-            saved_tabs.into_iter().map(|r| (r.id.clone(), r)).collect();
+          let tabs_by_id: HashMap<Uuid, Tab> = saved_tabs.into_iter().map(|r| (r.id, r)).collect();
           *tabs_write_guard = tabs_by_id;
         }
         events::GuiEvent::NewCollection(data) => {
@@ -455,6 +771,7 @@ impl Gui {
             },
             item: vec![],
             auth: None,
+            variable: None,
           };
         }
         events::GuiEvent::NewEnvironment(data) => {
@@ -486,6 +803,12 @@ impl Gui {
                   headers: None,
                   body: None,
                   environment: selected_env.clone().unwrap_or_default(),
+                  variables: vec![],
+                  scripts: RequestScripts::default(),
+                  assertions: vec![],
+                  extract: vec![],
+                  timeouts: RequestTimeouts::default(),
+                  auth: RequestAuth::NONE,
                 },
                 None,
               )
@@ -520,6 +843,12 @@ impl Gui {
                     headers: None,
                     body: None,
                     environment: selected_env.unwrap_or_default(),
+                    variables: vec![],
+                    scripts: RequestScripts::default(),
+                    assertions: vec![],
+                    extract: vec![],
+                    timeouts: RequestTimeouts::default(),
+                    auth: RequestAuth::NONE,
                   },
                   Some(fol_name),
                 )
@@ -558,6 +887,208 @@ impl Gui {
             }
           });
         }
+        events::GuiEvent::ConnectWebSocket(mut input) => {
+          println!("connecting to websocket {}", input.url);
+          tokio::spawn(async move {
+            if let Some(variables) =
+              Self::collection_variables(&active_tab_for_worker, &collections_for_worker).await
+            {
+              input.variables = variables;
+            }
+            let sessions = websocket_sessions_for_worker;
+            let (tab_id, session_id) = (input.tab_id, input.id);
+            // reconnecting closes the tab's previous connection
+            let previous = sessions.lock().unwrap().remove(&tab_id);
+            if let Some(commands) = previous.and_then(|s| s.commands) {
+              let _ = commands.try_send(WebSocketCommand::Close);
+            }
+            let connection = api_for_worker.read().await.connect_websocket(&input).await;
+            let connection = match connection {
+              Ok(connection) => connection,
+              Err(e) => {
+                let error = WebSocketMessage::new(
+                  WebSocketDirection::Event,
+                  WebSocketMessageKind::Error,
+                  format!("{e:#}"),
+                );
+                sessions.lock().unwrap().insert(
+                  tab_id,
+                  WebSocketSession {
+                    id: session_id,
+                    log: vec![error],
+                    commands: None,
+                  },
+                );
+                ctx_for_worker.request_repaint();
+                return;
+              }
+            };
+            let (commands, commands_rx) = tokio::sync::mpsc::channel(16);
+            sessions.lock().unwrap().insert(
+              tab_id,
+              WebSocketSession {
+                id: session_id,
+                log: vec![],
+                commands: Some(commands),
+              },
+            );
+            let transcript = connection
+              .run(commands_rx, |message| {
+                if let Some(session) = sessions.lock().unwrap().get_mut(&tab_id) {
+                  if session.id == session_id {
+                    session.log.push(message.clone());
+                  }
+                }
+                ctx_for_worker.request_repaint();
+              })
+              .await;
+            if let Some(session) = sessions.lock().unwrap().get_mut(&tab_id) {
+              if session.id == session_id {
+                session.commands = None;
+              }
+            }
+            if let Err(e) = api_for_worker
+              .write()
+              .await
+              .save_websocket_session(&input, &transcript)
+              .await
+            {
+              println!("Error saving websocket session: {:?}", e);
+            }
+            ctx_for_worker.request_repaint();
+          });
+        }
+        events::GuiEvent::SubmitGrpcRequest(mut input) => {
+          println!("calling grpc method {}", input.url);
+          tokio::spawn(async move {
+            if let Some(variables) =
+              Self::collection_variables(&active_tab_for_worker, &collections_for_worker).await
+            {
+              input.variables = variables;
+            }
+            let result = api_for_worker.write().await.send_grpc_request(input).await;
+            Self::show_response(
+              result,
+              &active_tab_for_worker,
+              &tabs_for_worker,
+              &test_results_for_worker,
+              &response_bodies_for_worker,
+              &res_tx_for_worker,
+            )
+            .await;
+            let tab = active_tab_for_worker.read().await.clone();
+            if let Err(e) = api_for_worker.write().await.save_tab(&tab).await {
+              println!("Error saving tab: {:?}", e);
+            }
+            ctx_for_worker.request_repaint();
+            res_tx_for_worker.try_send(events::GuiEvent::SetActiveTab(String::from(tab.id)))
+          });
+        }
+        events::GuiEvent::LoadGrpcMethods(input) => {
+          println!("loading grpc methods for {}", input.url);
+          tokio::spawn(async move {
+            let tab_id = input.tab_id;
+            let result = api_for_worker
+              .read()
+              .await
+              .load_grpc_methods(&input)
+              .await
+              .map_err(|e| format!("Error loading methods: {e:#}"));
+            let _ = res_tx_for_worker
+              .send(events::GuiEvent::GrpcMethodsLoaded { tab_id, result })
+              .await;
+            ctx_for_worker.request_repaint();
+          });
+        }
+        events::GuiEvent::FetchGraphQLSchema(input) => {
+          println!("fetching graphql schema from {}", input.url);
+          tokio::spawn(async move {
+            let url = input.url.clone();
+            let result = api_for_worker
+              .write()
+              .await
+              .introspect_graphql_schema(input)
+              .await
+              .map_err(|e| format!("Error fetching schema: {e:#}"));
+            let _ = res_tx_for_worker
+              .send(events::GuiEvent::GraphQLSchemaFetched { url, result })
+              .await;
+            ctx_for_worker.request_repaint();
+          });
+        }
+        events::GuiEvent::SaveResponseBody { body, data, path } => {
+          println!("saving response to {}", path);
+          tokio::spawn(async move {
+            if let Err(e) = api::utilities::response::save_body(&body, &data, &path).await {
+              println!("Error saving response: {:?}", e);
+            }
+          });
+        }
+        events::GuiEvent::SaveDefaultTimeouts(timeouts) => {
+          tokio::spawn(async move {
+            if let Err(e) = api_for_worker
+              .write()
+              .await
+              .save_default_timeouts(&timeouts)
+              .await
+            {
+              println!("Error saving default timeouts: {:?}", e);
+            }
+          });
+        }
+        events::GuiEvent::SaveClientSettings(settings) => {
+          tokio::spawn(async move {
+            if let Err(e) = api_for_worker
+              .write()
+              .await
+              .save_client_settings(&settings)
+              .await
+            {
+              println!("Error saving client settings: {:?}", e);
+            }
+          });
+        }
+        events::GuiEvent::LoadCookies(environment) => {
+          tokio::spawn(async move {
+            let cookies = match api_for_worker.read().await.load_cookies(&environment).await {
+              Ok(cookies) => cookies,
+              Err(e) => {
+                println!("Error loading cookies: {:?}", e);
+                vec![]
+              }
+            };
+            let _ = res_tx_for_worker
+              .send(events::GuiEvent::CookiesLoaded {
+                environment_id: environment.id,
+                cookies,
+              })
+              .await;
+            ctx_for_worker.request_repaint();
+          });
+        }
+        events::GuiEvent::SaveCookies {
+          environment,
+          cookies,
+        } => {
+          tokio::spawn(async move {
+            if let Err(e) = api_for_worker
+              .write()
+              .await
+              .save_cookies(&environment, &cookies)
+              .await
+            {
+              println!("Error saving cookies: {:?}", e);
+            }
+          });
+        }
+        events::GuiEvent::SaveGlobals(globals) => {
+          println!("saving {} globals", globals.len());
+          tokio::spawn(async move {
+            if let Err(e) = api_for_worker.write().await.save_globals(&globals).await {
+              println!("Error saving globals: {:?}", e);
+            }
+          });
+        }
         events::GuiEvent::RemoveTab(id) => {
           api.write().await.delete_tab(id).await.unwrap();
         }
@@ -645,9 +1176,12 @@ impl Gui {
       active_tab.res_status.clone().unwrap_or("".into()),
     ));
     println!("setting res body to {:?}", &active_tab.res_body);
-    let response_data = ResponseData::JSON(
-      serde_json::from_str(&active_tab.res_body).unwrap_or(serde_json::Value::Null),
-    );
+    // previews of large bodies and descriptions of binary ones aren't json
+    let response_data = match serde_json::from_str(&active_tab.res_body) {
+      Ok(json) => ResponseData::JSON(json),
+      Err(_) if !active_tab.res_body.is_empty() => ResponseData::TEXT(active_tab.res_body.clone()),
+      Err(_) => ResponseData::JSON(serde_json::Value::Null),
+    };
     self.worker_state.response = Arc::new(RwLock::new(Some(response_data)));
   }
 }
@@ -662,6 +1196,78 @@ impl App for Gui {
           self.set_gui_values_from_active_tab();
           ctx.request_repaint();
         }
+        events::GuiEvent::UpdateEnvironment(environment) => {
+          // scripts changed some values, keep the selection and the env list in sync
+          if let Ok(mut envs) = self.worker_state.environments.try_write() {
+            if let Some(existing) = envs.iter_mut().find(|e| e.id == environment.id) {
+              *existing = environment.clone();
+            }
+          }
+          *self.gui_state.selected_environment.borrow_mut() = environment;
+          ctx.request_repaint();
+        }
+        events::GuiEvent::GraphQLSchemaFetched { url, result } => {
+          self
+            .gui_state
+            .graphql_schemas
+            .borrow_mut()
+            .insert(url, result);
+          ctx.request_repaint();
+        }
+        events::GuiEvent::OAuth2Prompt(prompt) => {
+          self.gui_state.oauth_status = match prompt {
+            OAuth2Prompt::OpenBrowser(url) => {
+              ctx.open_url(egui::OpenUrl::new_tab(&url));
+              format!("Sign in in the browser, or open {}", url)
+            }
+            OAuth2Prompt::EnterCode {
+              user_code,
+              verification_uri,
+              verification_uri_complete,
+            } => {
+              if let Some(url) = verification_uri_complete {
+                ctx.open_url(egui::OpenUrl::new_tab(url));
+              }
+              format!("Enter the code {} at {}", user_code, verification_uri)
+            }
+          };
+        }
+        events::GuiEvent::OAuth2TokenReceived(result) => match result {
+          Ok(token) => {
+            self.gui_state.oauth_status = match token.expires_at {
+              Some(expires) => format!("Expires {}", expires.format("%Y-%m-%d %H:%M:%S UTC")),
+              None => "Received a token".into(),
+            };
+            self.gui_state.oauth_token = token.access_token;
+          }
+          Err(e) => self.gui_state.oauth_status = e,
+        },
+        events::GuiEvent::CookiesLoaded {
+          environment_id,
+          cookies,
+        } => {
+          *self.gui_state.cookies.borrow_mut() = Some((environment_id, cookies));
+          ctx.request_repaint();
+        }
+        events::GuiEvent::ResponseCookies { tab_id, cookies } => {
+          // the manager shows what's in the jar, which the response just changed
+          if !cookies.is_empty() {
+            *self.gui_state.cookies.borrow_mut() = None;
+          }
+          self
+            .gui_state
+            .response_cookies
+            .borrow_mut()
+            .insert(tab_id, cookies);
+        }
+        events::GuiEvent::GrpcMethodsLoaded { tab_id, result } => {
+          self
+            .gui_state
+            .grpc_methods
+            .borrow_mut()
+            .insert(tab_id, result);
+          ctx.request_repaint();
+        }
         events::GuiEvent::RefreshCollections(data) => {
           let cols = data.unwrap();
           if let Ok(mut cols_lock) = self.worker_state.collections.try_write() {
@@ -681,10 +1287,7 @@ impl App for Gui {
       self.worker_state.active_tab.clone(),
       self.gui_state.selected_environment.clone(),
       self.gui_state.headers.clone(),
-      self.gui_state.selected_auth_mode.clone(),
-      self.gui_state.api_key_name.clone(),
-      self.gui_state.api_key.clone(),
-      self.gui_state.bearer_token.clone(),
+      self.gui_state.grpc_proto_files.clone(),
       self.gui_state.oauth_token.clone(),
     );
     self
@@ -694,15 +1297,21 @@ impl App for Gui {
       .content_panel
       .show(ctx, &self.gui_state, &self.worker_state, &self.event_tx);
     import_modal(self, ctx);
+    export_modal(self, ctx);
     self
       .new_modal
       .show(ctx, &self.gui_state, &self.worker_state, &self.event_tx);
     save_window(self, ctx);
+    runner_window(self, ctx);
+    settings_window(self, ctx);
+    cookies_window(self, ctx);
   }
 }
 
 #[tokio::main]
 async fn main() {
+  env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn,api=debug"))
+    .init();
   let app = PostieApi::new().await;
   let (req_tx, req_rx) = tokio::sync::mpsc::channel(32);
   let (res_tx, res_rx) = tokio::sync::mpsc::channel(32);
@@ -0,0 +1,2 @@
+ALTER TABLE tabs ADD COLUMN collection_id TEXT;
+ALTER TABLE tabs ADD COLUMN request_id TEXT;
@@ -0,0 +1 @@
+ALTER TABLE request_history ADD COLUMN test_results JSON;
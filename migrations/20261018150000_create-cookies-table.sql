@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS cookies (
+  scope TEXT NOT NULL,
+  domain TEXT NOT NULL,
+  path TEXT NOT NULL,
+  name TEXT NOT NULL,
+  value TEXT NOT NULL,
+  host_only BOOLEAN NOT NULL,
+  expires TEXT,
+  secure BOOLEAN NOT NULL,
+  http_only BOOLEAN NOT NULL,
+  same_site TEXT,
+  PRIMARY KEY (scope, domain, path, name)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE tabs ADD COLUMN scripts TEXT;
+ALTER TABLE tabs ADD COLUMN assertions TEXT;
+ALTER TABLE tabs ADD COLUMN extract TEXT;